@@ -0,0 +1,66 @@
+use miette::IntoDiagnostic;
+use pallas_primitives::{babbage::PlutusData, Fragment};
+use std::path::PathBuf;
+use uplc::{
+    ast::{Constant, Term},
+    json::json_to_plutus_data,
+    parser,
+};
+
+#[derive(clap::Args)]
+/// Apply parameters to a validator of a built project
+///
+/// The validator in plutus.json and its assets are replaced by the applied
+/// script, along with its new hash and addresses.
+pub struct Args {
+    /// Path to project
+    #[clap(short, long)]
+    directory: Option<PathBuf>,
+
+    /// Title of the validator in plutus.json, e.g. `swap.spend`
+    validator: String,
+
+    /// Parameters to apply, in order: CBOR hex, a UPLC data constant like
+    /// `(con data #182a)` or JSON like `{ "int": 42 }`
+    params: Vec<String>,
+}
+
+pub fn exec(
+    Args {
+        directory,
+        validator,
+        params,
+    }: Args,
+) -> miette::Result<()> {
+    let params = params
+        .iter()
+        .map(|param| parse_param(param))
+        .collect::<miette::Result<Vec<_>>>()?;
+
+    crate::with_project(directory, crate::Format::Human, |p| {
+        p.apply(&validator, &params)
+    })
+}
+
+fn parse_param(param: &str) -> miette::Result<PlutusData> {
+    let param = param.trim();
+
+    if param.starts_with('{') {
+        let json = serde_json::from_str(param).into_diagnostic()?;
+
+        json_to_plutus_data(&json).into_diagnostic()
+    } else if param.starts_with('(') {
+        match parser::term(param).into_diagnostic()? {
+            Term::Constant(Constant::Data(data)) => Ok(data),
+            _ => miette::bail!(
+                "expected a data constant like (con data #182a), found {}",
+                param
+            ),
+        }
+    } else {
+        let bytes = hex::decode(param).into_diagnostic()?;
+
+        PlutusData::decode_fragment(&bytes)
+            .map_err(|error| miette::miette!("invalid CBOR encoded data {}: {}", param, error))
+    }
+}
@@ -1,5 +1,7 @@
 use std::path::PathBuf;
 
+use crate::Format;
+
 #[derive(clap::Args)]
 /// Build an Aiken project
 pub struct Args {
@@ -10,8 +12,60 @@ pub struct Args {
     /// Also dump textual uplc
     #[clap(short, long)]
     uplc: bool,
+
+    /// Keep traces and error messages in the compiled validators
+    #[clap(long)]
+    keep_traces: bool,
+
+    /// Wipe the build cache before building
+    ///
+    /// The cache only spares type-checking modules when the validators are up
+    /// to date. Generating the validators again type-checks every module.
+    #[clap(long)]
+    clean: bool,
+
+    /// Keep building whenever a source file or aiken.toml changes, until interrupted with Ctrl-C
+    #[clap(short, long)]
+    watch: bool,
+
+    /// Fail on warnings that aren't allowed in aiken.toml
+    #[clap(long)]
+    deny_warnings: bool,
+
+    /// How to print errors and warnings
+    #[clap(long, value_enum, default_value_t = Format::Human)]
+    format: Format,
 }
 
-pub fn exec(Args { directory, uplc }: Args) -> miette::Result<()> {
-    crate::with_project(directory, |p| p.build(uplc))
+pub fn exec(
+    Args {
+        directory,
+        uplc,
+        keep_traces,
+        clean,
+        watch,
+        deny_warnings,
+        format,
+    }: Args,
+) -> miette::Result<()> {
+    let mut clean = clean;
+
+    let action = |p: &mut aiken_project::Project| {
+        // Only wipe the cache before the first run when watching
+        if std::mem::take(&mut clean) {
+            p.clean()?;
+        }
+
+        if deny_warnings {
+            p.deny_warnings();
+        }
+
+        p.build(uplc, keep_traces)
+    };
+
+    if watch {
+        crate::watch_project(directory, format, action)
+    } else {
+        crate::with_project(directory, format, action)
+    }
 }
@@ -1,13 +1,58 @@
 use std::path::PathBuf;
 
+use crate::Format;
+
 #[derive(clap::Args)]
 /// Type-check an Aiken project
 pub struct Args {
     /// Path to project
     #[clap(short, long)]
     directory: Option<PathBuf>,
+
+    /// Wipe the build cache before checking
+    #[clap(long)]
+    clean: bool,
+
+    /// Keep checking whenever a source file or aiken.toml changes, until interrupted with Ctrl-C
+    #[clap(short, long)]
+    watch: bool,
+
+    /// Fail on warnings that aren't allowed in aiken.toml
+    #[clap(long)]
+    deny_warnings: bool,
+
+    /// How to print errors and warnings
+    #[clap(long, value_enum, default_value_t = Format::Human)]
+    format: Format,
 }
 
-pub fn exec(Args { directory }: Args) -> miette::Result<()> {
-    crate::with_project(directory, |p| p.check())
+pub fn exec(
+    Args {
+        directory,
+        clean,
+        watch,
+        deny_warnings,
+        format,
+    }: Args,
+) -> miette::Result<()> {
+    let mut clean = clean;
+
+    let action = |p: &mut aiken_project::Project| {
+        // Only wipe the cache before the first run when watching
+        if std::mem::take(&mut clean) {
+            p.clean()?;
+        }
+
+        if deny_warnings {
+            p.deny_warnings();
+        }
+
+        p.check()
+    };
+
+    if watch {
+        crate::watch_project(directory, format, action)
+    } else {
+        crate::with_project(directory, format, action)
+    }
 }
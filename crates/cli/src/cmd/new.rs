@@ -54,8 +54,10 @@ impl Creator {
         write(
             self.validators.join("always_true.ak"),
             indoc! {"
-                pub fn spend() -> Bool {
+                validator {
+                  fn spend(_datum: Data, _redeemer: Data, _context: Data) -> Bool {
                     True
+                  }
                 }
             "},
         )
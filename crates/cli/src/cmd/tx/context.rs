@@ -0,0 +1,241 @@
+use std::{collections::BTreeMap, fs, path::Path, str::FromStr};
+
+use miette::IntoDiagnostic;
+use pallas_addresses::Address;
+use pallas_codec::utils::{CborWrap, KeyValuePairs};
+use pallas_crypto::hash::Hash;
+use pallas_primitives::{
+    babbage::{
+        CostMdls, DatumOption, PlutusV1Script, PlutusV2Script, PostAlonzoTransactionOutput, Script,
+        TransactionInput, TransactionOutput, Value,
+    },
+    Fragment,
+};
+use serde::Deserialize;
+use uplc::{
+    json::json_to_plutus_data,
+    machine::cost_model::ExBudget,
+    tx::script_context::{ResolvedInput, SlotConfig},
+};
+
+/// Everything needed to simulate a transaction, as read from a single JSON
+/// file:
+///
+/// ```json
+/// {
+///   "transaction": "84a8...",
+///   "utxos": [
+///     { "input": "8258...", "output": "a300..." },
+///     {
+///       "transaction_id": "975c...", "index": 0,
+///       "address": "addr_test1...",
+///       "value": { "lovelace": 2000000, "assets": { "<policy>": { "<name>": 1 } } },
+///       "datum": { "inline": { "int": 42 } },
+///       "reference_script": { "plutus_v2": "5909..." }
+///     }
+///   ],
+///   "cost_models": { "plutus_v1": [...], "plutus_v2": [...] },
+///   "slot_config": { "zero_time": 1660003200000, "zero_slot": 0, "slot_length": 1000 },
+///   "initial_budget": { "mem": 14000000, "cpu": 10000000000 }
+/// }
+/// ```
+///
+/// Only `transaction` and `utxos` are mandatory, and an `initial_budget`
+/// requires `cost_models`.
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct Context {
+    transaction: String,
+    utxos: Vec<Utxo>,
+    cost_models: Option<CostMdls>,
+    slot_config: Option<SlotConfigJson>,
+    initial_budget: Option<BudgetJson>,
+}
+
+#[derive(Deserialize)]
+#[serde(untagged, deny_unknown_fields)]
+enum Utxo {
+    Cbor {
+        input: String,
+        output: String,
+    },
+    Structured {
+        transaction_id: String,
+        index: u64,
+        address: String,
+        value: ValueJson,
+        datum: Option<DatumJson>,
+        reference_script: Option<ScriptJson>,
+    },
+}
+
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct ValueJson {
+    lovelace: u64,
+    #[serde(default)]
+    assets: BTreeMap<String, BTreeMap<String, u64>>,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "snake_case")]
+enum DatumJson {
+    Hash(String),
+    Inline(serde_json::Value),
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "snake_case")]
+enum ScriptJson {
+    PlutusV1(String),
+    PlutusV2(String),
+}
+
+#[derive(Deserialize)]
+struct SlotConfigJson {
+    zero_time: u64,
+    zero_slot: u64,
+    slot_length: u32,
+}
+
+#[derive(Deserialize)]
+struct BudgetJson {
+    mem: i64,
+    cpu: i64,
+}
+
+/// A context file, decoded into the ledger types the evaluator expects.
+pub struct Loaded {
+    pub tx_bytes: Vec<u8>,
+    pub utxos: Vec<ResolvedInput>,
+    pub cost_mdls: Option<CostMdls>,
+    pub slot_config: Option<SlotConfig>,
+    pub initial_budget: Option<ExBudget>,
+}
+
+pub fn load(path: &Path) -> miette::Result<Loaded> {
+    let raw = fs::read_to_string(path).into_diagnostic()?;
+
+    let context: Context = serde_json::from_str(&raw)
+        .map_err(|err| miette::miette!("invalid context file {}: {}", path.display(), err))?;
+
+    if context.initial_budget.is_some() && context.cost_models.is_none() {
+        miette::bail!("an initial_budget can only be used along with cost_models");
+    }
+
+    let utxos = context
+        .utxos
+        .into_iter()
+        .enumerate()
+        .map(|(i, utxo)| {
+            utxo.resolve()
+                .map_err(|err| miette::miette!("invalid utxo at position {}: {}", i, err))
+        })
+        .collect::<miette::Result<_>>()?;
+
+    Ok(Loaded {
+        tx_bytes: hex::decode(context.transaction.trim()).into_diagnostic()?,
+        utxos,
+        cost_mdls: context.cost_models,
+        slot_config: context.slot_config.map(|config| SlotConfig {
+            zero_time: config.zero_time,
+            zero_slot: config.zero_slot,
+            slot_length: config.slot_length,
+        }),
+        initial_budget: context.initial_budget.map(|budget| ExBudget {
+            mem: budget.mem,
+            cpu: budget.cpu,
+        }),
+    })
+}
+
+impl Utxo {
+    fn resolve(self) -> Result<ResolvedInput, String> {
+        match self {
+            Utxo::Cbor { input, output } => Ok(ResolvedInput {
+                input: TransactionInput::decode_fragment(&decode_hex(&input)?)
+                    .map_err(|err| err.to_string())?,
+                output: TransactionOutput::decode_fragment(&decode_hex(&output)?)
+                    .map_err(|err| err.to_string())?,
+            }),
+            Utxo::Structured {
+                transaction_id,
+                index,
+                address,
+                value,
+                datum,
+                reference_script,
+            } => {
+                let input = TransactionInput {
+                    transaction_id: Hash::from_str(&transaction_id)
+                        .map_err(|_| format!("invalid transaction id {}", transaction_id))?,
+                    index,
+                };
+
+                let address = Address::from_bech32(&address)
+                    .or_else(|_| Address::from_hex(&address))
+                    .map_err(|_| format!("invalid address {}", address))?;
+
+                let datum_option = match datum {
+                    None => None,
+                    Some(DatumJson::Hash(hash)) => Some(DatumOption::Hash(
+                        Hash::from_str(&hash)
+                            .map_err(|_| format!("invalid datum hash {}", hash))?,
+                    )),
+                    Some(DatumJson::Inline(data)) => Some(DatumOption::Data(CborWrap(
+                        json_to_plutus_data(&data).map_err(|err| err.to_string())?,
+                    ))),
+                };
+
+                let script_ref = match reference_script {
+                    None => None,
+                    Some(ScriptJson::PlutusV1(cbor)) => Some(CborWrap(Script::PlutusV1Script(
+                        PlutusV1Script(decode_hex(&cbor)?.into()),
+                    ))),
+                    Some(ScriptJson::PlutusV2(cbor)) => Some(CborWrap(Script::PlutusV2Script(
+                        PlutusV2Script(decode_hex(&cbor)?.into()),
+                    ))),
+                };
+
+                let output = TransactionOutput::PostAlonzo(PostAlonzoTransactionOutput {
+                    address: address.to_vec().into(),
+                    value: value.into_value()?,
+                    datum_option,
+                    script_ref,
+                });
+
+                Ok(ResolvedInput { input, output })
+            }
+        }
+    }
+}
+
+impl ValueJson {
+    fn into_value(self) -> Result<Value, String> {
+        if self.assets.is_empty() {
+            return Ok(Value::Coin(self.lovelace));
+        }
+
+        let assets = self
+            .assets
+            .into_iter()
+            .map(|(policy_id, tokens)| {
+                let policy_id = Hash::from_str(&policy_id)
+                    .map_err(|_| format!("invalid policy id {}", policy_id))?;
+
+                let tokens = tokens
+                    .into_iter()
+                    .map(|(name, quantity)| Ok((decode_hex(&name)?.into(), quantity)))
+                    .collect::<Result<_, String>>()?;
+
+                Ok((policy_id, KeyValuePairs::Def(tokens)))
+            })
+            .collect::<Result<_, String>>()?;
+
+        Ok(Value::Multiasset(self.lovelace, KeyValuePairs::Def(assets)))
+    }
+}
+
+fn decode_hex(s: &str) -> Result<Vec<u8>, String> {
+    hex::decode(s.trim()).map_err(|_| format!("invalid hex string {}", s))
+}
@@ -1,4 +1,7 @@
+mod context;
+pub mod script_context;
 pub mod simulate;
+mod slot_config;
 
 use clap::Subcommand;
 
@@ -7,10 +10,12 @@ use clap::Subcommand;
 #[clap(setting(clap::AppSettings::DeriveDisplayOrder))]
 pub enum Cmd {
     Simulate(simulate::Args),
+    ScriptContext(script_context::Args),
 }
 
 pub fn exec(cmd: Cmd) -> miette::Result<()> {
     match cmd {
         Cmd::Simulate(args) => simulate::exec(args),
+        Cmd::ScriptContext(args) => script_context::exec(args),
     }
 }
@@ -0,0 +1,217 @@
+use miette::IntoDiagnostic;
+use pallas_primitives::{
+    babbage::{RedeemerTag, TransactionInput, TransactionOutput},
+    Fragment,
+};
+use pallas_traverse::{Era, MultiEraTx};
+use std::fs;
+use std::path::PathBuf;
+use uplc::{
+    json::plutus_data_to_json,
+    tx::{self, script_context::ResolvedInput, to_plutus_data::ToPlutusData},
+    PlutusData,
+};
+
+use super::slot_config;
+
+#[derive(clap::Args)]
+/// Print the datum, redeemer and script context a script receives
+///
+/// The output can be fed back to `aiken uplc eval`, e.g.
+/// `aiken uplc eval script.uplc "(con data #<hex>)"`.
+pub struct Args {
+    /// A file containing cbor hex for a transaction
+    input: PathBuf,
+
+    /// Toggle whether input is raw cbor or a hex string
+    #[clap(short, long)]
+    cbor: bool,
+
+    /// A file containing cbor hex for the raw inputs
+    raw_inputs: PathBuf,
+
+    /// A file containing cbor hex for the raw outputs
+    raw_outputs: PathBuf,
+
+    /// Tag of the redeemer to inspect
+    #[clap(short, long, value_enum, default_value_t = Tag::Spend)]
+    tag: Tag,
+
+    /// Index of the redeemer to inspect
+    #[clap(short, long, default_value_t = 0)]
+    index: u32,
+
+    /// Output format
+    #[clap(short, long, value_enum, default_value_t = Format::Cbor)]
+    format: Format,
+
+    #[clap(flatten)]
+    slot_config: slot_config::Args,
+}
+
+#[derive(clap::ValueEnum, Clone, Copy)]
+enum Tag {
+    Spend,
+    Mint,
+    Cert,
+    Reward,
+}
+
+impl From<Tag> for RedeemerTag {
+    fn from(tag: Tag) -> Self {
+        match tag {
+            Tag::Spend => RedeemerTag::Spend,
+            Tag::Mint => RedeemerTag::Mint,
+            Tag::Cert => RedeemerTag::Cert,
+            Tag::Reward => RedeemerTag::Reward,
+        }
+    }
+}
+
+#[derive(clap::ValueEnum, Clone, Copy)]
+enum Format {
+    /// Hex encoded cbor
+    Cbor,
+    /// Structured Data, e.g. `Constr 0 [I 42, B #00]`
+    Text,
+    /// The detailed JSON schema of the cardano-cli
+    Json,
+}
+
+pub fn exec(
+    Args {
+        input,
+        cbor,
+        raw_inputs,
+        raw_outputs,
+        tag,
+        index,
+        format,
+        slot_config,
+    }: Args,
+) -> miette::Result<()> {
+    let (tx_bytes, inputs_bytes, outputs_bytes) = if cbor {
+        (
+            fs::read(input).into_diagnostic()?,
+            fs::read(raw_inputs).into_diagnostic()?,
+            fs::read(raw_outputs).into_diagnostic()?,
+        )
+    } else {
+        let cbor_hex = fs::read_to_string(input).into_diagnostic()?;
+        let inputs_hex = fs::read_to_string(raw_inputs).into_diagnostic()?;
+        let outputs_hex = fs::read_to_string(raw_outputs).into_diagnostic()?;
+
+        (
+            hex::decode(cbor_hex.trim()).into_diagnostic()?,
+            hex::decode(inputs_hex.trim()).into_diagnostic()?,
+            hex::decode(outputs_hex.trim()).into_diagnostic()?,
+        )
+    };
+
+    let tx = MultiEraTx::decode(Era::Babbage, &tx_bytes)
+        .or_else(|_| MultiEraTx::decode(Era::Alonzo, &tx_bytes))
+        .into_diagnostic()?;
+
+    let inputs = Vec::<TransactionInput>::decode_fragment(&inputs_bytes)
+        .map_err(|err| miette::miette!("{}", err))?;
+    let outputs = Vec::<TransactionOutput>::decode_fragment(&outputs_bytes)
+        .map_err(|err| miette::miette!("{}", err))?;
+
+    let resolved_inputs: Vec<ResolvedInput> = inputs
+        .iter()
+        .zip(outputs.iter())
+        .map(|(input, output)| ResolvedInput {
+            input: input.clone(),
+            output: output.clone(),
+        })
+        .collect();
+
+    let tx_babbage = match tx.as_babbage() {
+        Some(tx) => tx,
+        None => miette::bail!("Wrong era. Please use babbage"),
+    };
+
+    let slot_config = slot_config.slot_config()?;
+
+    let args = tx::script_arguments(
+        tx_babbage,
+        &resolved_inputs,
+        &slot_config,
+        tag.into(),
+        index,
+    )
+    .map_err(|err| miette::miette!("{}", err))?;
+
+    let script_context = args.script_context.to_plutus_data();
+
+    match format {
+        Format::Json => {
+            let json = serde_json::json!({
+                "datum": args.datum.as_ref().map(plutus_data_to_json),
+                "redeemer": plutus_data_to_json(&args.redeemer),
+                "script_context": plutus_data_to_json(&script_context),
+            });
+
+            println!("{}", serde_json::to_string_pretty(&json).into_diagnostic()?);
+        }
+        Format::Cbor | Format::Text => {
+            let show = |data: &PlutusData| -> miette::Result<String> {
+                match format {
+                    Format::Text => Ok(json_to_text(&plutus_data_to_json(data))),
+                    _ => data
+                        .encode_fragment()
+                        .map(hex::encode)
+                        .map_err(|err| miette::miette!("{}", err)),
+                }
+            };
+
+            if let Some(datum) = &args.datum {
+                println!("\nDatum\n-----\n\n{}", show(datum)?);
+            }
+
+            println!("\nRedeemer\n--------\n\n{}", show(&args.redeemer)?);
+
+            println!(
+                "\nScript Context\n--------------\n\n{}\n",
+                show(&script_context)?
+            );
+        }
+    }
+
+    Ok(())
+}
+
+/// Render the JSON form of some Data the way it is shown by Plutus tooling,
+/// e.g. `Constr 0 [I 42, B #00]`.
+fn json_to_text(json: &serde_json::Value) -> String {
+    let list = |items: &serde_json::Value| {
+        items
+            .as_array()
+            .map(|items| items.iter().map(json_to_text).collect::<Vec<_>>())
+            .unwrap_or_default()
+            .join(", ")
+    };
+
+    if let Some(index) = json.get("constructor") {
+        format!("Constr {} [{}]", index, list(&json["fields"]))
+    } else if let Some(pairs) = json.get("map").and_then(|pairs| pairs.as_array()) {
+        let pairs = pairs
+            .iter()
+            .map(|pair| {
+                format!(
+                    "({}, {})",
+                    json_to_text(&pair["k"]),
+                    json_to_text(&pair["v"])
+                )
+            })
+            .collect::<Vec<_>>();
+
+        format!("Map [{}]", pairs.join(", "))
+    } else if let Some(items) = json.get("list") {
+        format!("List [{}]", list(items))
+    } else if let Some(bytes) = json.get("bytes").and_then(|bytes| bytes.as_str()) {
+        format!("B #{}", bytes)
+    } else {
+        format!("I {}", json["int"].to_string().trim_matches('"'))
+    }
+}
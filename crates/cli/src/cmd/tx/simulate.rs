@@ -1,46 +1,50 @@
 use miette::IntoDiagnostic;
 use pallas_primitives::{
-    babbage::{TransactionInput, TransactionOutput},
+    babbage::{RedeemerTag, TransactionInput, TransactionOutput},
     Fragment,
 };
 use pallas_traverse::{Era, MultiEraTx};
+use serde_json::json;
 use std::fs;
 use std::path::PathBuf;
 use uplc::{
     machine::cost_model::ExBudget,
-    tx::{
-        self,
-        script_context::{ResolvedInput, SlotConfig},
-    },
+    tx::{self, script_context::ResolvedInput},
 };
 
+use super::{context, slot_config};
+
 #[derive(clap::Args)]
 /// Simulate a transaction by evaluating it's script
 pub struct Args {
     /// A file containing cbor hex for a transaction
-    input: PathBuf,
+    #[clap(required_unless_present = "context")]
+    input: Option<PathBuf>,
 
     /// Toggle whether input is raw cbor or a hex string
     #[clap(short, long)]
     cbor: bool,
 
     /// A file containing cbor hex for the raw inputs
-    raw_inputs: PathBuf,
+    #[clap(required_unless_present = "context")]
+    raw_inputs: Option<PathBuf>,
 
     /// A file containing cbor hex for the raw outputs
-    raw_outputs: PathBuf,
+    #[clap(required_unless_present = "context")]
+    raw_outputs: Option<PathBuf>,
 
-    /// Time between each slot
-    #[clap(short, long, default_value_t = 1000)]
-    slot_length: u32,
+    /// A JSON file holding the transaction, the utxos it resolves and
+    /// optionally cost models, a slot config and an initial budget. The slot
+    /// config options take precedence over the one of the file
+    #[clap(long, conflicts_with_all = &["input", "raw-inputs", "raw-outputs", "cbor"])]
+    context: Option<PathBuf>,
 
-    /// Time of shelley hardfork
-    #[clap(long, default_value_t = 1596059091000)]
-    zero_time: u64,
+    /// Print the outcome of each redeemer as JSON
+    #[clap(long)]
+    json: bool,
 
-    /// Slot number at the start of the shelley hardfork
-    #[clap(long, default_value_t = 4492800)]
-    zero_slot: u64,
+    #[clap(flatten)]
+    slot_config: slot_config::Args,
 }
 
 pub fn exec(
@@ -49,11 +53,138 @@ pub fn exec(
         cbor,
         raw_inputs,
         raw_outputs,
-        slot_length,
-        zero_time,
-        zero_slot,
+        context,
+        json,
+        slot_config,
     }: Args,
 ) -> miette::Result<()> {
+    let slot_config = slot_config.explicit()?;
+
+    let context = match context {
+        Some(path) => context::load(&path)?,
+        None => load_files(
+            input.expect("required by clap"),
+            cbor,
+            raw_inputs.expect("required by clap"),
+            raw_outputs.expect("required by clap"),
+        )?,
+    };
+
+    let tx = MultiEraTx::decode(Era::Babbage, &context.tx_bytes)
+        .or_else(|_| MultiEraTx::decode(Era::Alonzo, &context.tx_bytes))
+        .into_diagnostic()?;
+
+    let tx_babbage = match tx.as_babbage() {
+        Some(tx) => tx,
+        None => miette::bail!("Wrong era. Please use babbage"),
+    };
+
+    if !json {
+        println!("Simulating: {}", tx.hash());
+    }
+
+    let result = tx::eval_phase_two_detailed(
+        tx_babbage,
+        &context.utxos,
+        context.cost_mdls.as_ref(),
+        context.initial_budget.as_ref(),
+        // Options given on the command line win over the context file
+        &slot_config.or(context.slot_config).unwrap_or_default(),
+        true,
+    );
+
+    let reports = match result {
+        Ok(reports) => reports,
+        Err(err) => {
+            if json {
+                let output = json!({
+                    "transaction": tx.hash().to_string(),
+                    "redeemers": [],
+                    "error": err.to_string(),
+                });
+
+                println!(
+                    "{}",
+                    serde_json::to_string_pretty(&output).into_diagnostic()?
+                );
+            } else {
+                eprintln!("\nError\n-----\n\n{}\n", err);
+            }
+
+            miette::bail!("simulation failed");
+        }
+    };
+
+    let total_budget_used = reports
+        .iter()
+        .fold(ExBudget { mem: 0, cpu: 0 }, |accum, curr| ExBudget {
+            mem: accum.mem + curr.ex_units.mem as i64,
+            cpu: accum.cpu + curr.ex_units.steps as i64,
+        });
+
+    let failures = reports.iter().filter(|r| r.error.is_some()).count();
+
+    if json {
+        let redeemers = reports
+            .iter()
+            .map(|report| {
+                json!({
+                    "tag": tag_to_str(&report.tag),
+                    "index": report.index,
+                    "ex_units": {
+                        "mem": report.ex_units.mem,
+                        "cpu": report.ex_units.steps,
+                    },
+                    "logs": report.logs,
+                    "error": report.error.as_ref().map(|err| err.to_string().trim_end().to_string()),
+                })
+            })
+            .collect::<Vec<_>>();
+
+        let output = json!({
+            "transaction": tx.hash().to_string(),
+            "redeemers": redeemers,
+            "total": {
+                "mem": total_budget_used.mem,
+                "cpu": total_budget_used.cpu,
+            },
+        });
+
+        println!(
+            "{}",
+            serde_json::to_string_pretty(&output).into_diagnostic()?
+        );
+    } else {
+        for report in reports.iter() {
+            if let Some(err) = &report.error {
+                eprintln!("\nError\n-----\n\n{}\n", err);
+
+                for log in report.logs.iter() {
+                    eprintln!("{}", log);
+                }
+            }
+        }
+
+        println!("\nTotal Budget Used\n-----------------\n");
+
+        println!("mem: {}", total_budget_used.mem);
+        println!("cpu: {}", total_budget_used.cpu);
+    }
+
+    if failures > 0 {
+        miette::bail!("{} of {} redeemer(s) failed", failures, reports.len());
+    }
+
+    Ok(())
+}
+
+/// Read a transaction and the outputs it spends from three separate files.
+fn load_files(
+    input: PathBuf,
+    cbor: bool,
+    raw_inputs: PathBuf,
+    raw_outputs: PathBuf,
+) -> miette::Result<context::Loaded> {
     let (tx_bytes, inputs_bytes, outputs_bytes) = if cbor {
         (
             fs::read(input).into_diagnostic()?,
@@ -72,14 +203,12 @@ pub fn exec(
         )
     };
 
-    let tx = MultiEraTx::decode(Era::Babbage, &tx_bytes)
-        .or_else(|_| MultiEraTx::decode(Era::Alonzo, &tx_bytes))
-        .into_diagnostic()?;
+    let inputs = Vec::<TransactionInput>::decode_fragment(&inputs_bytes)
+        .map_err(|err| miette::miette!("{}", err))?;
+    let outputs = Vec::<TransactionOutput>::decode_fragment(&outputs_bytes)
+        .map_err(|err| miette::miette!("{}", err))?;
 
-    let inputs = Vec::<TransactionInput>::decode_fragment(&inputs_bytes).unwrap();
-    let outputs = Vec::<TransactionOutput>::decode_fragment(&outputs_bytes).unwrap();
-
-    let resolved_inputs: Vec<ResolvedInput> = inputs
+    let utxos: Vec<ResolvedInput> = inputs
         .iter()
         .zip(outputs.iter())
         .map(|(input, output)| ResolvedInput {
@@ -88,38 +217,20 @@ pub fn exec(
         })
         .collect();
 
-    println!("Simulating: {}", tx.hash());
-
-    if let Some(tx_babbage) = tx.as_babbage() {
-        let slot_config = SlotConfig {
-            zero_time,
-            zero_slot,
-            slot_length,
-        };
-
-        let result =
-            tx::eval_phase_two(tx_babbage, &resolved_inputs, None, None, &slot_config, true);
-
-        match result {
-            Ok(redeemers) => {
-                println!("\nTotal Budget Used\n-----------------\n");
-
-                let total_budget_used =
-                    redeemers
-                        .iter()
-                        .fold(ExBudget { mem: 0, cpu: 0 }, |accum, curr| ExBudget {
-                            mem: accum.mem + curr.ex_units.mem as i64,
-                            cpu: accum.cpu + curr.ex_units.steps as i64,
-                        });
+    Ok(context::Loaded {
+        tx_bytes,
+        utxos,
+        cost_mdls: None,
+        slot_config: None,
+        initial_budget: None,
+    })
+}
 
-                println!("mem: {}", total_budget_used.mem);
-                println!("cpu: {}", total_budget_used.cpu);
-            }
-            Err(err) => {
-                eprintln!("\nError\n-----\n\n{}\n", err);
-            }
-        }
+fn tag_to_str(tag: &RedeemerTag) -> &'static str {
+    match tag {
+        RedeemerTag::Spend => "spend",
+        RedeemerTag::Mint => "mint",
+        RedeemerTag::Cert => "cert",
+        RedeemerTag::Reward => "reward",
     }
-
-    Ok(())
 }
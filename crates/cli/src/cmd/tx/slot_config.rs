@@ -0,0 +1,77 @@
+use std::{fs, path::PathBuf};
+
+use miette::IntoDiagnostic;
+use uplc::tx::script_context::SlotConfig;
+
+/// Options to select the slot config used to translate validity intervals
+/// into POSIX time. In order of precedence: a genesis file, a named network,
+/// or the individual values (mainnet by default). When given, they take
+/// precedence over the slot config of a context file.
+#[derive(clap::Args)]
+pub struct Args {
+    /// Network to take the slot config from
+    #[clap(long, value_enum)]
+    network: Option<Network>,
+
+    /// A Shelley genesis file to derive the slot config from
+    #[clap(long, conflicts_with = "network")]
+    genesis: Option<PathBuf>,
+
+    /// Time between each slot [default: 1000]
+    #[clap(short, long)]
+    slot_length: Option<u32>,
+
+    /// Time of shelley hardfork [default: 1596059091000]
+    #[clap(long)]
+    zero_time: Option<u64>,
+
+    /// Slot number at the start of the shelley hardfork [default: 4492800]
+    #[clap(long)]
+    zero_slot: Option<u64>,
+}
+
+#[derive(clap::ValueEnum, Clone, Copy)]
+enum Network {
+    Mainnet,
+    Preprod,
+    Preview,
+}
+
+impl Args {
+    pub fn slot_config(&self) -> miette::Result<SlotConfig> {
+        Ok(self.explicit()?.unwrap_or_default())
+    }
+
+    /// The slot config selected by the options, or `None` when none of them
+    /// is given. Values that aren't given are taken from mainnet.
+    pub fn explicit(&self) -> miette::Result<Option<SlotConfig>> {
+        if let Some(genesis) = &self.genesis {
+            let genesis = fs::read_to_string(genesis).into_diagnostic()?;
+
+            return SlotConfig::from_shelley_genesis(&genesis)
+                .map(Some)
+                .map_err(|err| miette::miette!("{}", err));
+        }
+
+        Ok(match self.network {
+            Some(Network::Mainnet) => Some(SlotConfig::mainnet()),
+            Some(Network::Preprod) => Some(SlotConfig::preprod()),
+            Some(Network::Preview) => Some(SlotConfig::preview()),
+            None if self.zero_time.is_none()
+                && self.zero_slot.is_none()
+                && self.slot_length.is_none() =>
+            {
+                None
+            }
+            None => {
+                let mainnet = SlotConfig::mainnet();
+
+                Some(SlotConfig {
+                    zero_time: self.zero_time.unwrap_or(mainnet.zero_time),
+                    zero_slot: self.zero_slot.unwrap_or(mainnet.zero_slot),
+                    slot_length: self.slot_length.unwrap_or(mainnet.slot_length),
+                })
+            }
+        })
+    }
+}
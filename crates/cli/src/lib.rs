@@ -1,38 +1,306 @@
 pub mod cmd;
 
-use aiken_project::{config::Config, Project};
+use aiken_project::{config::Config, error::Warning, Project};
 use miette::IntoDiagnostic;
-use std::env;
-use std::path::PathBuf;
+use notify::{RecursiveMode, Watcher};
+use serde_json::json;
+use std::path::{Path, PathBuf};
+use std::sync::mpsc;
+use std::time::Duration;
+use std::{env, fs};
 
-pub fn with_project<A>(directory: Option<PathBuf>, mut action: A) -> miette::Result<()>
+/// How long the sources must stay untouched before a change is picked up, as
+/// saving a file often shows up as several events.
+const DEBOUNCE: Duration = Duration::from_millis(200);
+
+/// How errors and warnings are printed
+#[derive(clap::ValueEnum, Clone, Copy, PartialEq, Eq)]
+pub enum Format {
+    /// Rendered with their source code, on stderr
+    Human,
+    /// One JSON record per line, on stdout
+    Json,
+}
+
+pub fn with_project<A>(
+    directory: Option<PathBuf>,
+    format: Format,
+    mut action: A,
+) -> miette::Result<()>
 where
     A: FnMut(&mut Project) -> Result<(), aiken_project::error::Error>,
 {
-    let project_path = if let Some(d) = directory {
-        d
-    } else {
-        env::current_dir().into_diagnostic()?
-    };
+    let project_path = project_path(directory)?;
 
-    let config = Config::load(project_path.clone()).into_diagnostic()?;
+    let config = match Config::load(project_path.clone()) {
+        Ok(config) => config,
+        Err(error) => return report(vec![], format, Err(error)),
+    };
 
     let mut project = Project::new(config, project_path);
 
     let build_result = action(&mut project);
 
-    let warning_count = project.warnings.len();
+    report(std::mem::take(&mut project.warnings), format, build_result)
+}
+
+/// Run `action` on the project, and again every time its sources or its
+/// configuration change, until interrupted.
+pub fn watch_project<A>(directory: Option<PathBuf>, format: Format, action: A) -> miette::Result<()>
+where
+    A: FnMut(&mut Project) -> Result<(), aiken_project::error::Error>,
+{
+    // Events come with absolute paths
+    let project_path = fs::canonicalize(project_path(directory)?).into_diagnostic()?;
+
+    let (sender, events) = mpsc::channel();
+
+    let mut watcher = notify::recommended_watcher(sender).into_diagnostic()?;
+
+    // The root is watched rather than `lib` and `validators`, which may not
+    // exist yet; events for other files are ignored.
+    watcher
+        .watch(&project_path, RecursiveMode::Recursive)
+        .into_diagnostic()?;
+
+    watch(&project_path, format, action, &events)
+}
+
+/// Run `action` on the project at `project_path`, and again for every change
+/// among `events`, until they end.
+fn watch<A>(
+    project_path: &Path,
+    format: Format,
+    mut action: A,
+    events: &mpsc::Receiver<notify::Result<notify::Event>>,
+) -> miette::Result<()>
+where
+    A: FnMut(&mut Project) -> Result<(), aiken_project::error::Error>,
+{
+    let mut project = None;
+
+    loop {
+        // Clear the screen, unless something else is reading the output
+        if format == Format::Human {
+            print!("\x1B[2J\x1B[1;1H");
+        }
+
+        let result = match project.as_mut() {
+            Some(project) => Ok(project),
+            None => Config::load(project_path.to_path_buf())
+                .map(|config| project.insert(Project::new(config, project_path.to_path_buf()))),
+        };
+
+        let result = match result {
+            Ok(project) => {
+                let build_result = action(project);
+
+                report(std::mem::take(&mut project.warnings), format, build_result)
+            }
+            Err(error) => report(vec![], format, Err(error)),
+        };
+
+        // Errors and warnings are already on stdout as JSON records, only
+        // tell tools that the run is over.
+        match format {
+            Format::Human => {
+                if let Err(error) = result {
+                    eprintln!("{:?}", error);
+                }
+
+                eprintln!("watching for changes...");
+            }
+            Format::Json => println!("{}", json!({ "event": "watching" })),
+        }
+
+        match wait_for_changes(project_path, events)? {
+            // A new configuration needs a new project
+            Some(true) => project = None,
+            Some(false) => {}
+            None => return Ok(()),
+        }
+    }
+}
+
+/// Block until a source file or the configuration of the project changes,
+/// and tell whether the configuration did, or `None` once the events end.
+fn wait_for_changes(
+    root: &Path,
+    events: &mpsc::Receiver<notify::Result<notify::Event>>,
+) -> miette::Result<Option<bool>> {
+    let mut changed = false;
+    let mut config_changed = false;
+
+    loop {
+        let event = if changed {
+            match events.recv_timeout(DEBOUNCE) {
+                Ok(event) => event,
+                Err(mpsc::RecvTimeoutError::Timeout | mpsc::RecvTimeoutError::Disconnected) => {
+                    return Ok(Some(config_changed))
+                }
+            }
+        } else {
+            match events.recv() {
+                Ok(event) => event,
+                Err(mpsc::RecvError) => return Ok(None),
+            }
+        };
+
+        let event = event.into_diagnostic()?;
+
+        if event.kind.is_access() {
+            continue;
+        }
+
+        for path in event.paths {
+            let path = path.strip_prefix(root).unwrap_or(&path);
+
+            if path == Path::new("aiken.toml") {
+                changed = true;
+                config_changed = true;
+            } else if (path.starts_with("lib") || path.starts_with("validators"))
+                && path.extension().map(|ext| ext == "ak").unwrap_or(false)
+            {
+                changed = true;
+            }
+        }
+    }
+}
+
+fn project_path(directory: Option<PathBuf>) -> miette::Result<PathBuf> {
+    match directory {
+        Some(d) => Ok(d),
+        None => env::current_dir().into_diagnostic(),
+    }
+}
 
-    for warning in project.warnings {
-        warning.report()
+fn report(
+    warnings: Vec<Warning>,
+    format: Format,
+    build_result: Result<(), aiken_project::error::Error>,
+) -> miette::Result<()> {
+    let warning_count = warnings.len();
+
+    for warning in warnings {
+        match format {
+            Format::Human => warning.report(),
+            Format::Json => println!("{}", warning.to_json()),
+        }
     }
 
     if let Err(err) = build_result {
-        err.report();
+        match format {
+            Format::Human => err.report(),
+            Format::Json => {
+                for record in err.to_json() {
+                    println!("{}", record)
+                }
+            }
+        }
 
         miette::bail!("failed: {} error(s), {warning_count} warning(s)", err.len(),);
     };
 
-    println!("finished with {warning_count} warning(s)");
+    if format == Format::Human {
+        println!("finished with {warning_count} warning(s)");
+    }
+
     Ok(())
 }
+
+#[cfg(test)]
+mod tests {
+    use std::{cell::Cell, env, fs, path::PathBuf, process, sync::mpsc};
+
+    use notify::{event::ModifyKind, Event, EventKind};
+
+    use super::{watch, Format};
+
+    /// Create a project named after `test`, with a library module `util`.
+    fn project(test: &str) -> PathBuf {
+        let dir = env::temp_dir().join(format!("aiken-watch-{}-{}", process::id(), test));
+
+        fs::create_dir_all(dir.join("lib")).unwrap();
+        fs::write(
+            dir.join("aiken.toml"),
+            "name = \"test\"\nversion = \"1.0.0\"\n",
+        )
+        .unwrap();
+        fs::write(dir.join("lib").join("util.ak"), "pub fn one() {\n  1\n}\n").unwrap();
+
+        dir
+    }
+
+    fn modified(path: PathBuf) -> notify::Result<Event> {
+        Ok(Event::new(EventKind::Modify(ModifyKind::Any)).add_path(path))
+    }
+
+    #[test]
+    fn runs_again_on_changes_until_the_events_end() {
+        let dir = project("runs_again_on_changes_until_the_events_end");
+
+        let (sender, events) = mpsc::channel();
+
+        let runs = Cell::new(0);
+
+        // Changes to other files are ignored, and those that come together
+        // only run the action once.
+        sender
+            .send(modified(dir.join("build").join("plutus.json")))
+            .unwrap();
+        sender
+            .send(modified(dir.join("lib").join("util.ak")))
+            .unwrap();
+        sender.send(modified(dir.join("aiken.toml"))).unwrap();
+
+        drop(sender);
+
+        let result = watch(
+            &dir,
+            Format::Json,
+            |project| {
+                runs.set(runs.get() + 1);
+
+                project.check()
+            },
+            &events,
+        );
+
+        fs::remove_dir_all(dir).unwrap();
+
+        assert!(result.is_ok());
+        assert_eq!(runs.get(), 2);
+    }
+
+    #[test]
+    fn waits_for_relevant_changes() {
+        let dir = project("waits_for_relevant_changes");
+
+        let (sender, events) = mpsc::channel();
+
+        let runs = Cell::new(0);
+
+        sender.send(modified(dir.join("README.md"))).unwrap();
+        sender
+            .send(modified(dir.join("lib").join("notes.txt")))
+            .unwrap();
+
+        drop(sender);
+
+        let result = watch(
+            &dir,
+            Format::Json,
+            |project| {
+                runs.set(runs.get() + 1);
+
+                project.check()
+            },
+            &events,
+        );
+
+        fs::remove_dir_all(dir).unwrap();
+
+        assert!(result.is_ok());
+        assert_eq!(runs.get(), 1);
+    }
+}
@@ -1,5 +1,7 @@
 use std::{fmt, ops::Range, sync::Arc};
 
+use serde::{Deserialize, Serialize};
+
 use crate::{
     builtins::{self, bool},
     expr::{TypedExpr, UntypedExpr},
@@ -14,7 +16,7 @@ pub const TRY_VARIABLE: &str = "_try";
 pub type TypedModule = Module<TypeInfo, TypedDefinition>;
 pub type UntypedModule = Module<(), UntypedDefinition>;
 
-#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Serialize, Deserialize)]
 pub enum ModuleKind {
     Lib,
     Validator,
@@ -70,6 +72,7 @@ pub type TypedDefinition = Definition<Arc<Type>, TypedExpr, String, String>;
 pub type UntypedDefinition = Definition<(), UntypedExpr, (), ()>;
 
 pub type TypedFunction = Function<Arc<Type>, TypedExpr>;
+pub type UntypedFunction = Function<(), UntypedExpr>;
 
 #[derive(Debug, Clone, PartialEq)]
 pub struct Function<T, Expr> {
@@ -84,6 +87,21 @@ pub struct Function<T, Expr> {
     pub end_position: usize,
 }
 
+pub type TypedValidator = Validator<Arc<Type>, TypedExpr>;
+pub type UntypedValidator = Validator<(), UntypedExpr>;
+
+/// A `validator(params) { fn spend(..) { .. } fn mint(..) { .. } }` block.
+/// Each handler is compiled to its own script, taking the parameters of the
+/// validator before its own arguments.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Validator<T, Expr> {
+    pub doc: Option<String>,
+    pub location: Span,
+    pub params: Vec<Arg<T>>,
+    pub handlers: Vec<Function<T, Expr>>,
+    pub end_position: usize,
+}
+
 #[derive(Debug, Clone, PartialEq)]
 pub struct TypeAlias<T> {
     pub alias: String,
@@ -138,6 +156,8 @@ pub enum Definition<T, Expr, ConstantRecordTag, PackageName> {
     Use(Use<PackageName>),
 
     ModuleConstant(ModuleConstant<T, ConstantRecordTag>),
+
+    Validator(Validator<T, Expr>),
 }
 
 impl<A, B, C, E> Definition<A, B, C, E> {
@@ -147,7 +167,8 @@ impl<A, B, C, E> Definition<A, B, C, E> {
             | Definition::Use(Use { location, .. })
             | Definition::TypeAlias(TypeAlias { location, .. })
             | Definition::DataType(DataType { location, .. })
-            | Definition::ModuleConstant(ModuleConstant { location, .. }) => *location,
+            | Definition::ModuleConstant(ModuleConstant { location, .. })
+            | Definition::Validator(Validator { location, .. }) => *location,
         }
     }
 
@@ -157,7 +178,8 @@ impl<A, B, C, E> Definition<A, B, C, E> {
             Definition::Fn(Function { doc, .. })
             | Definition::TypeAlias(TypeAlias { doc, .. })
             | Definition::DataType(DataType { doc, .. })
-            | Definition::ModuleConstant(ModuleConstant { doc, .. }) => {
+            | Definition::ModuleConstant(ModuleConstant { doc, .. })
+            | Definition::Validator(Validator { doc, .. }) => {
                 let _ = std::mem::replace(doc, Some(new_doc));
             }
         }
@@ -173,7 +195,7 @@ pub struct DefinitionLocation<'module> {
 pub type TypedConstant = Constant<Arc<Type>, String>;
 pub type UntypedConstant = Constant<(), ()>;
 
-#[derive(Debug, Clone, PartialEq)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub enum Constant<T, RecordTag> {
     Int {
         location: Span,
@@ -248,9 +270,37 @@ impl<A, B> Constant<A, B> {
     }
 }
 
+/// The value of an integer literal as written in the source: decimal, `0x`
+/// hexadecimal or `0b` binary, with optional `_` separators and an optional
+/// leading `-`. `None` when it doesn't fit in 128 bits.
+pub fn int_value(literal: &str) -> Option<i128> {
+    let (negative, literal) = match literal.strip_prefix('-') {
+        Some(literal) => (true, literal),
+        None => (false, literal),
+    };
+
+    let (radix, digits) = if let Some(digits) = literal.strip_prefix("0x") {
+        (16, digits)
+    } else if let Some(digits) = literal.strip_prefix("0b") {
+        (2, digits)
+    } else {
+        (10, literal)
+    };
+
+    let digits = digits.replace('_', "");
+
+    let value = if negative {
+        i128::from_str_radix(&format!("-{}", digits), radix)
+    } else {
+        i128::from_str_radix(&digits, radix)
+    };
+
+    value.ok()
+}
+
 pub type TypedCallArg = CallArg<TypedExpr>;
 
-#[derive(Debug, Clone, PartialEq, Eq)]
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
 pub struct CallArg<A> {
     pub label: Option<String>,
     pub location: Span,
@@ -810,7 +860,7 @@ pub enum TodoKind {
     EmptyFunction,
 }
 
-#[derive(Copy, Clone, PartialEq, Eq)]
+#[derive(Copy, Clone, PartialEq, Eq, Serialize, Deserialize)]
 pub struct Span {
     pub start: usize,
     pub end: usize,
@@ -7,7 +7,7 @@ use crate::{
         Annotation, Arg, AssignmentKind, BinOp, CallArg, Clause, DefinitionLocation, IfBranch,
         Pattern, RecordUpdateSpread, Span, TodoKind, TypedRecordUpdateArg, UntypedRecordUpdateArg,
     },
-    builtins::{bool, nil},
+    builtins::{bool, int, nil},
     tipo::{ModuleValueConstructor, PatternConstructor, Type, ValueConstructor},
 };
 
@@ -148,6 +148,19 @@ pub enum TypedExpr {
         tipo: Arc<Type>,
     },
 
+    ErrorTerm {
+        location: Span,
+        label: Option<String>,
+        tipo: Arc<Type>,
+    },
+
+    Trace {
+        location: Span,
+        tipo: Arc<Type>,
+        then: Box<Self>,
+        text: String,
+    },
+
     RecordUpdate {
         location: Span,
         tipo: Arc<Type>,
@@ -159,17 +172,25 @@ pub enum TypedExpr {
         location: Span,
         value: Box<Self>,
     },
+
+    NegateInt {
+        location: Span,
+        value: Box<Self>,
+    },
 }
 
 impl TypedExpr {
     pub fn tipo(&self) -> Arc<Type> {
         match self {
             Self::Negate { .. } => bool(),
+            Self::NegateInt { .. } => int(),
             Self::Var { constructor, .. } => constructor.tipo.clone(),
             Self::Try { then, .. } => then.tipo(),
             Self::Fn { tipo, .. }
             | Self::Int { tipo, .. }
             | Self::Todo { tipo, .. }
+            | Self::ErrorTerm { tipo, .. }
+            | Self::Trace { tipo, .. }
             | Self::When { tipo, .. }
             | Self::List { tipo, .. }
             | Self::Call { tipo, .. }
@@ -214,9 +235,12 @@ impl TypedExpr {
             | TypedExpr::Call { .. }
             | TypedExpr::When { .. }
             | TypedExpr::Todo { .. }
+            | TypedExpr::ErrorTerm { .. }
+            | TypedExpr::Trace { .. }
             | TypedExpr::BinOp { .. }
             // | TypedExpr::Tuple { .. }
             | TypedExpr::Negate { .. }
+            | TypedExpr::NegateInt { .. }
             | TypedExpr::String { .. }
             | TypedExpr::Sequence { .. }
             | TypedExpr::Pipeline { .. }
@@ -252,6 +276,7 @@ impl TypedExpr {
             | Self::Try { location, .. }
             | Self::Var { location, .. }
             | Self::Todo { location, .. }
+            | Self::ErrorTerm { location, .. }
             | Self::When { location, .. }
             | Self::Call { location, .. }
             | Self::List { location, .. }
@@ -259,6 +284,7 @@ impl TypedExpr {
             // | Self::Tuple { location, .. }
             | Self::String { location, .. }
             | Self::Negate { location, .. }
+            | Self::NegateInt { location, .. }
             | Self::Pipeline { location, .. }
             | Self::ByteArray { location, .. }
             | Self::Assignment { location, .. }
@@ -272,6 +298,8 @@ impl TypedExpr {
                 ..
             } => branches.first().body.type_defining_location(),
 
+            Self::Trace { then, .. } => then.type_defining_location(),
+
             Self::Sequence {
                 expressions,
                 location,
@@ -290,6 +318,8 @@ impl TypedExpr {
             | Self::Int { location, .. }
             | Self::Var { location, .. }
             | Self::Todo { location, .. }
+            | Self::ErrorTerm { location, .. }
+            | Self::Trace { location, .. }
             | Self::When { location, .. }
             | Self::Call { location, .. }
             | Self::If { location, .. }
@@ -298,6 +328,7 @@ impl TypedExpr {
             // | Self::Tuple { location, .. }
             | Self::String { location, .. }
             | Self::Negate { location, .. }
+            | Self::NegateInt { location, .. }
             | Self::Sequence { location, .. }
             | Self::Pipeline { location, .. }
             | Self::ByteArray { location, .. }
@@ -417,6 +448,17 @@ pub enum UntypedExpr {
         label: Option<String>,
     },
 
+    ErrorTerm {
+        location: Span,
+        label: Option<String>,
+    },
+
+    Trace {
+        location: Span,
+        then: Box<Self>,
+        text: String,
+    },
+
     RecordUpdate {
         location: Span,
         constructor: Box<Self>,
@@ -428,6 +470,11 @@ pub enum UntypedExpr {
         location: Span,
         value: Box<Self>,
     },
+
+    NegateInt {
+        location: Span,
+        value: Box<Self>,
+    },
 }
 
 impl UntypedExpr {
@@ -481,12 +528,13 @@ impl UntypedExpr {
 
     pub fn location(&self) -> Span {
         match self {
-            Self::Try { then, .. } => then.location(),
+            Self::Try { then, .. } | Self::Trace { then, .. } => then.location(),
             Self::PipeLine { expressions, .. } => expressions.last().location(),
             Self::Fn { location, .. }
             | Self::Var { location, .. }
             | Self::Int { location, .. }
             | Self::Todo { location, .. }
+            | Self::ErrorTerm { location, .. }
             | Self::When { location, .. }
             | Self::Call { location, .. }
             | Self::List { location, .. }
@@ -499,6 +547,7 @@ impl UntypedExpr {
             | Self::FieldAccess { location, .. }
             | Self::RecordUpdate { location, .. }
             | Self::Negate { location, .. }
+            | Self::NegateInt { location, .. }
             | Self::If { location, .. } => *location,
             Self::Sequence {
                 location,
@@ -519,7 +568,9 @@ impl UntypedExpr {
                 .map(|e| e.start_byte_index())
                 .unwrap_or(location.start),
             Self::PipeLine { expressions, .. } => expressions.first().start_byte_index(),
-            Self::Try { location, .. } | Self::Assignment { location, .. } => location.start,
+            Self::Try { location, .. }
+            | Self::Trace { location, .. }
+            | Self::Assignment { location, .. } => location.start,
             _ => self.location().start,
         }
     }
@@ -7,8 +7,8 @@ use crate::{
         Annotation, Arg, ArgName, AssignmentKind, BinOp, CallArg, ClauseGuard, Constant, DataType,
         Definition, Function, ModuleConstant, Pattern, RecordConstructor, RecordConstructorArg,
         RecordUpdateSpread, Span, TypeAlias, TypedArg, TypedConstant, UnqualifiedImport,
-        UntypedArg, UntypedClause, UntypedClauseGuard, UntypedDefinition, UntypedModule,
-        UntypedPattern, UntypedRecordUpdateArg, Use, CAPTURE_VARIABLE,
+        UntypedArg, UntypedClause, UntypedClauseGuard, UntypedDefinition, UntypedFunction,
+        UntypedModule, UntypedPattern, UntypedRecordUpdateArg, Use, Validator, CAPTURE_VARIABLE,
     },
     docvec,
     expr::UntypedExpr,
@@ -280,6 +280,13 @@ impl<'comments> Formatter<'comments> {
                 };
                 head.append(" = ").append(self.const_expr(value))
             }
+
+            Definition::Validator(Validator {
+                params,
+                handlers,
+                end_position,
+                ..
+            }) => self.definition_validator(params, handlers, *end_position),
         }
     }
 
@@ -510,6 +517,57 @@ impl<'comments> Formatter<'comments> {
             .append("}")
     }
 
+    fn definition_validator<'a>(
+        &mut self,
+        params: &'a [UntypedArg],
+        handlers: &'a [UntypedFunction],
+        end_position: usize,
+    ) -> Document<'a> {
+        let head = if params.is_empty() {
+            "validator".to_doc()
+        } else {
+            "validator"
+                .to_doc()
+                .append(wrap_args(params.iter().map(|e| (self.fn_arg(e), false))))
+                .group()
+        };
+
+        let handlers = handlers
+            .iter()
+            .map(|handler| {
+                let comments = self.pop_comments(handler.location.start);
+
+                let doc_comments = self.doc_comments(handler.location.start);
+
+                let handler = self
+                    .definition_fn(
+                        &handler.public,
+                        &handler.name,
+                        &handler.arguments,
+                        &handler.return_annotation,
+                        &handler.body,
+                        handler.end_position,
+                    )
+                    .group();
+
+                commented(doc_comments.append(handler).group(), comments)
+            })
+            .collect::<Vec<_>>();
+
+        let body = join(handlers, lines(2));
+
+        let body = match printed_comments(self.pop_comments(end_position), false) {
+            Some(comments) => body.append(line()).append(comments),
+            None => body,
+        };
+
+        head.append(" {")
+            .append(line().append(body).nest(INDENT))
+            .append(line())
+            .append("}")
+            .force_break()
+    }
+
     fn expr_fn<'a>(
         &mut self,
         args: &'a [UntypedArg],
@@ -584,7 +642,10 @@ impl<'comments> Formatter<'comments> {
             keyword.to_doc()
         }
         .append(pattern.append(annotation).group())
-        .append(" =")
+        .append(match kind {
+            Some(AssignmentKind::Assert | AssignmentKind::Check) => " is",
+            _ => " =",
+        })
         .append(self.assigned_value(value));
 
         if let Some(then) = then {
@@ -599,6 +660,17 @@ impl<'comments> Formatter<'comments> {
         }
     }
 
+    fn trace<'a>(&mut self, text: &'a String, then: &'a UntypedExpr) -> Document<'a> {
+        docvec!["trace(", self.string(text), ")"]
+            .force_break()
+            .append(if self.pop_empty_lines(then.start_byte_index()) {
+                lines(2)
+            } else {
+                line()
+            })
+            .append(self.expr(then))
+    }
+
     fn expr<'a>(&mut self, expr: &'a UntypedExpr) -> Document<'a> {
         let comments = self.pop_comments(expr.start_byte_index());
 
@@ -650,6 +722,12 @@ impl<'comments> Formatter<'comments> {
 
             UntypedExpr::Todo { label: Some(l), .. } => docvec!["todo(\"", l, "\")"],
 
+            UntypedExpr::ErrorTerm { label: None, .. } => "error".to_doc(),
+
+            UntypedExpr::ErrorTerm { label: Some(l), .. } => docvec!["error(\"", l, "\")"],
+
+            UntypedExpr::Trace { text, then, .. } => self.trace(text, then),
+
             UntypedExpr::PipeLine { expressions, .. } => self.pipeline(expressions),
 
             UntypedExpr::Int { value, .. } => value.to_doc(),
@@ -664,6 +742,8 @@ impl<'comments> Formatter<'comments> {
 
             UntypedExpr::Negate { value, .. } => self.negate(value),
 
+            UntypedExpr::NegateInt { value, .. } => self.negate_int(value),
+
             UntypedExpr::Fn {
                 is_capture: true,
                 body,
@@ -1127,7 +1207,8 @@ impl<'comments> Formatter<'comments> {
         match expr {
             UntypedExpr::Sequence { .. }
             | UntypedExpr::Assignment { .. }
-            | UntypedExpr::Try { .. } => "{"
+            | UntypedExpr::Try { .. }
+            | UntypedExpr::Trace { .. } => "{"
                 .to_doc()
                 .append(line().append(self.expr(expr)).nest(INDENT))
                 .append(line())
@@ -1159,6 +1240,7 @@ impl<'comments> Formatter<'comments> {
     fn case_clause_value<'a>(&mut self, expr: &'a UntypedExpr) -> Document<'a> {
         match expr {
             UntypedExpr::Try { .. }
+            | UntypedExpr::Trace { .. }
             | UntypedExpr::Sequence { .. }
             | UntypedExpr::Assignment { .. } => " {"
                 .to_doc()
@@ -1346,6 +1428,13 @@ impl<'comments> Formatter<'comments> {
     fn negate<'a>(&mut self, value: &'a UntypedExpr) -> Document<'a> {
         docvec!["!", self.wrap_expr(value)]
     }
+
+    fn negate_int<'a>(&mut self, value: &'a UntypedExpr) -> Document<'a> {
+        match value {
+            UntypedExpr::BinOp { .. } => docvec!["-{ ", self.expr(value), " }"],
+            _ => docvec!["-", self.wrap_expr(value)],
+        }
+    }
 }
 
 impl<'a> Documentable<'a> for &'a ArgName {
@@ -73,6 +73,7 @@ fn module_parser() -> impl Parser<Token, Vec<UntypedDefinition>, Error = ParseEr
         import_parser(),
         data_parser(),
         type_alias_parser(),
+        validator_parser(),
         fn_parser(),
     ))
     .repeated()
@@ -221,6 +222,36 @@ pub fn type_alias_parser() -> impl Parser<Token, ast::UntypedDefinition, Error =
 }
 
 pub fn fn_parser() -> impl Parser<Token, ast::UntypedDefinition, Error = ParseError> {
+    function_parser().map(ast::UntypedDefinition::Fn)
+}
+
+pub fn validator_parser() -> impl Parser<Token, ast::UntypedDefinition, Error = ParseError> {
+    just(Token::Validator)
+        .ignore_then(
+            fn_param_parser()
+                .separated_by(just(Token::Comma))
+                .allow_trailing()
+                .delimited_by(just(Token::LeftParen), just(Token::RightParen))
+                .or_not(),
+        )
+        .map_with_span(|params, span| (params.unwrap_or_default(), span))
+        .then(
+            function_parser()
+                .repeated()
+                .delimited_by(just(Token::LeftBrace), just(Token::RightBrace)),
+        )
+        .map_with_span(|((params, location), handlers), span| {
+            ast::UntypedDefinition::Validator(ast::Validator {
+                doc: None,
+                location,
+                params,
+                handlers,
+                end_position: span.end - 1,
+            })
+        })
+}
+
+fn function_parser() -> impl Parser<Token, ast::UntypedFunction, Error = ParseError> {
     pub_parser()
         .or_not()
         .then_ignore(just(Token::Fn))
@@ -240,7 +271,7 @@ pub fn fn_parser() -> impl Parser<Token, ast::UntypedDefinition, Error = ParseEr
         )
         .map_with_span(
             |((((opt_pub, name), (arguments, args_span)), return_annotation), body), span| {
-                ast::UntypedDefinition::Fn(ast::Function {
+                ast::Function {
                     arguments,
                     body: body.unwrap_or(expr::UntypedExpr::Todo {
                         kind: TodoKind::EmptyFunction,
@@ -260,7 +291,7 @@ pub fn fn_parser() -> impl Parser<Token, ast::UntypedDefinition, Error = ParseEr
                     public: opt_pub.is_some(),
                     return_annotation,
                     return_type: (),
-                })
+                }
             },
         )
 }
@@ -342,6 +373,17 @@ pub fn expr_seq_parser() -> impl Parser<Token, expr::UntypedExpr, Error = ParseE
                         annotation,
                     }
                 }),
+            just(Token::Trace)
+                .ignore_then(
+                    select! {Token::String {value} => value}
+                        .delimited_by(just(Token::LeftParen), just(Token::RightParen)),
+                )
+                .then(r.clone())
+                .map_with_span(|(text, then_), span| expr::UntypedExpr::Trace {
+                    location: span,
+                    then: Box::new(then_),
+                    text,
+                }),
             expr_parser(r.clone())
                 .then(r.repeated())
                 .foldl(|current, next| current.append_in_sequence(next)),
@@ -361,12 +403,19 @@ pub fn expr_parser(
                 }
             });
 
-        let int_parser = select! { Token::Int {value} => value}.map_with_span(|value, span| {
-            expr::UntypedExpr::Int {
+        let int_parser = select! { Token::Int {value} => value}
+            .validate(|value, span, emit| {
+                // Only the magnitude of `i128::MIN` fits when negated
+                if ast::int_value(&value).is_none() {
+                    emit(ParseError::int_out_of_range(span));
+                }
+
+                value
+            })
+            .map_with_span(|value, span| expr::UntypedExpr::Int {
                 location: span,
                 value,
-            }
-        });
+            });
 
         let var_parser = select! {
             Token::Name { name } => name,
@@ -389,6 +438,17 @@ pub fn expr_parser(
                 label,
             });
 
+        let error_parser = just(Token::ErrorTerm)
+            .ignore_then(
+                select! {Token::String {value} => value}
+                    .delimited_by(just(Token::LeftParen), just(Token::RightParen))
+                    .or_not(),
+            )
+            .map_with_span(|label, span| expr::UntypedExpr::ErrorTerm {
+                location: span,
+                label,
+            });
+
         let list_parser = just(Token::LeftSquare)
             .ignore_then(r.clone().separated_by(just(Token::Comma)))
             .then(choice((
@@ -433,36 +493,9 @@ pub fn expr_parser(
 
         let block_parser = seq_r.delimited_by(just(Token::LeftBrace), just(Token::RightBrace));
 
-        // TODO: do guards later
-        // let when_clause_guard_parser = just(Token::If);
-
-        let when_clause_parser = pattern_parser()
-            .separated_by(just(Token::Comma))
-            .at_least(1)
-            .then(
-                just(Token::Vbar)
-                    .ignore_then(
-                        pattern_parser()
-                            .separated_by(just(Token::Comma))
-                            .at_least(1),
-                    )
-                    .repeated()
-                    .or_not(),
-            )
-            // TODO: do guards later
-            // .then(when_clause_guard_parser)
-            // TODO: add hint "Did you mean to wrap a multi line clause in curly braces?"
-            .then_ignore(just(Token::RArrow))
+        let when_clause_parser = when_clause_head_parser()
             .then(r.clone())
-            .map_with_span(|((patterns, alternative_patterns_opt), then), span| {
-                ast::UntypedClause {
-                    location: span,
-                    pattern: patterns,
-                    alternative_patterns: alternative_patterns_opt.unwrap_or_default(),
-                    guard: None,
-                    then,
-                }
-            });
+            .map_with_span(|(head, then), span| (head, then, span));
 
         let when_parser = just(Token::When)
             // TODO: If subject is empty we should return ParseErrorType::ExpectedExpr,
@@ -472,10 +505,12 @@ pub fn expr_parser(
             // TODO: If clauses are empty we should return ParseErrorType::NoCaseClause
             .then(when_clause_parser.repeated())
             .then_ignore(just(Token::RightBrace))
-            .map_with_span(|(subjects, clauses), span| expr::UntypedExpr::When {
-                location: span,
-                subjects,
-                clauses,
+            .try_map(|(subjects, clauses), span| {
+                Ok(expr::UntypedExpr::When {
+                    location: span,
+                    subjects,
+                    clauses: when_clauses(clauses)?,
+                })
             });
 
         let let_parser = just(Token::Let)
@@ -562,6 +597,7 @@ pub fn expr_parser(
             int_parser,
             var_parser,
             todo_parser,
+            error_parser,
             list_parser,
             anon_fn_parser,
             block_parser,
@@ -589,12 +625,15 @@ pub fn expr_parser(
                 Box<(expr::UntypedExpr, Vec<ast::UntypedRecordUpdateArg>)>,
                 Span,
             ),
+            Record(Vec<ast::CallArg<expr::UntypedExpr>>, Span),
         }
 
         let field_access_parser = just(Token::Dot)
             .ignore_then(select! {
                 Token::Name { name } => name,
-                Token::UpName { name } => name
+                Token::UpName { name } => name,
+                // The `trace` builtin remains reachable as `builtin.trace`.
+                Token::Trace => "trace".to_string(),
             })
             .map_with_span(Chain::FieldAccess);
 
@@ -621,6 +660,21 @@ pub fn expr_parser(
                 Chain::RecordUpdate(Box::new((spread, args_opt.unwrap_or_default())), span)
             });
 
+        // `Constructor { label: value, .. }` is sugar for a labeled call.
+        let record_parser = select! { Token::Name { name } => name }
+            .then_ignore(just(Token::Colon))
+            .then(r.clone())
+            .map_with_span(|(label, value), span| ast::CallArg {
+                label: Some(label),
+                location: span,
+                value,
+            })
+            .separated_by(just(Token::Comma))
+            .at_least(1)
+            .allow_trailing()
+            .delimited_by(just(Token::LeftBrace), just(Token::RightBrace))
+            .map_with_span(Chain::Record);
+
         let call_parser = choice((
             select! { Token::Name { name } => name }
                 .then_ignore(just(Token::Colon))
@@ -647,7 +701,12 @@ pub fn expr_parser(
         .delimited_by(just(Token::LeftParen), just(Token::RightParen))
         .map_with_span(Chain::Call);
 
-        let chain = choice((field_access_parser, record_update_parser, call_parser));
+        let chain = choice((
+            field_access_parser,
+            record_update_parser,
+            record_parser,
+            call_parser,
+        ));
 
         let chained = expr_unit_parser
             .then(chain.repeated())
@@ -708,6 +767,12 @@ pub fn expr_parser(
                     container: Box::new(e),
                 },
 
+                Chain::Record(arguments, span) => expr::UntypedExpr::Call {
+                    location: e.location().union(span),
+                    fun: Box::new(e),
+                    arguments,
+                },
+
                 Chain::RecordUpdate(data, span) => {
                     let (spread, arguments) = *data;
 
@@ -728,17 +793,31 @@ pub fn expr_parser(
             });
 
         // Negate
-        let op = just(Token::Bang);
-
-        let unary = op
-            .ignored()
-            .map_with_span(|_, span| span)
-            .repeated()
-            .then(chained)
-            .foldr(|span, value| expr::UntypedExpr::Negate {
-                location: span.union(value.location()),
-                value: Box::new(value),
-            })
+        let op = choice((just(Token::Bang).to(false), just(Token::Minus).to(true)));
+
+        // A negative literal, which can be `i128::MIN`
+        let negative_int = just(Token::Minus)
+            .ignore_then(select! { Token::Int {value} => value})
+            .map_with_span(|value, span| expr::UntypedExpr::Int {
+                location: span,
+                value: format!("-{}", value),
+            });
+
+        let unary = negative_int
+            .or(op
+                .map_with_span(|is_int, span| (is_int, span))
+                .repeated()
+                .then(chained)
+                .foldr(|(is_int, span), value| {
+                    let location = span.union(value.location());
+                    let value = Box::new(value);
+
+                    if is_int {
+                        expr::UntypedExpr::NegateInt { location, value }
+                    } else {
+                        expr::UntypedExpr::Negate { location, value }
+                    }
+                }))
             .boxed();
 
         // Product
@@ -934,6 +1013,300 @@ pub fn pub_parser() -> impl Parser<Token, (), Error = ParseError> {
     just(Token::Pub).ignored()
 }
 
+/// The patterns, alternatives and guard of a `when` clause, up to its arrow.
+struct ClauseHead {
+    /// The token starting the head, and its location.
+    start: (Token, Span),
+    /// Missing when the head starts with a negative literal, as `-1 -> ..`,
+    /// which the body of the previous clause read as a subtraction.
+    first: Option<ast::UntypedPattern>,
+    patterns: Vec<ast::UntypedPattern>,
+    alternative_patterns: Vec<Vec<ast::UntypedPattern>>,
+    guard: Option<ast::UntypedClauseGuard>,
+}
+
+fn when_clause_head_parser() -> impl Parser<Token, ClauseHead, Error = ParseError> {
+    any()
+        .map_with_span(|token, span| (token, span))
+        .rewind()
+        .then(pattern_parser().or_not())
+        .then(just(Token::Comma).ignore_then(pattern_parser()).repeated())
+        .then(
+            just(Token::Vbar)
+                .ignore_then(
+                    pattern_parser()
+                        .separated_by(just(Token::Comma))
+                        .at_least(1),
+                )
+                .repeated(),
+        )
+        .then(
+            just(Token::If)
+                .ignore_then(when_clause_guard_parser())
+                .or_not(),
+        )
+        // TODO: add hint "Did you mean to wrap a multi line clause in curly braces?"
+        .then_ignore(just(Token::RArrow))
+        .map(
+            |((((start, first), patterns), alternative_patterns), guard)| ClauseHead {
+                start,
+                first,
+                patterns,
+                alternative_patterns,
+                guard,
+            },
+        )
+}
+
+/// Build the clauses of a `when`, giving back to a head without its first
+/// pattern the negative literal that ends the body of the previous clause.
+fn when_clauses(
+    clauses: Vec<(ClauseHead, expr::UntypedExpr, Span)>,
+) -> Result<Vec<ast::UntypedClause>, ParseError> {
+    let mut result: Vec<ast::UntypedClause> = Vec::with_capacity(clauses.len());
+
+    for (head, then, location) in clauses {
+        let first = match head.first {
+            Some(first) => first,
+            None => {
+                let (token, span) = head.start;
+
+                let previous = result.last_mut().ok_or_else(|| {
+                    ParseError::expected_input_found(span, None, Some(token.clone()))
+                })?;
+
+                let body = std::mem::replace(
+                    &mut previous.then,
+                    expr::UntypedExpr::Sequence {
+                        location: previous.location,
+                        expressions: vec![],
+                    },
+                );
+
+                match split_negative_literal(body) {
+                    Ok((body, literal)) => {
+                        previous.location =
+                            Span::new((), previous.location.start..body.location().end);
+                        previous.then = body;
+
+                        literal
+                    }
+                    Err(body) => {
+                        previous.then = body;
+
+                        return Err(ParseError::expected_input_found(span, None, Some(token)));
+                    }
+                }
+            }
+        };
+
+        result.push(ast::UntypedClause {
+            location: first.location().union(location),
+            pattern: std::iter::once(first).chain(head.patterns).collect(),
+            alternative_patterns: head.alternative_patterns,
+            guard: head.guard,
+            then,
+        });
+    }
+
+    Ok(result)
+}
+
+/// Take the subtraction of an integer literal ending `expr` off, as the
+/// negative literal pattern of the next clause.
+fn split_negative_literal(
+    expr: expr::UntypedExpr,
+) -> Result<(expr::UntypedExpr, ast::UntypedPattern), expr::UntypedExpr> {
+    match expr {
+        expr::UntypedExpr::BinOp {
+            name,
+            left,
+            right,
+            location,
+        } => match (name, *right) {
+            (BinOp::SubInt, expr::UntypedExpr::Int { value, location })
+                if !value.starts_with('-') =>
+            {
+                Ok((
+                    *left,
+                    ast::UntypedPattern::Int {
+                        location,
+                        value: format!("-{}", value),
+                    },
+                ))
+            }
+            // The literal ends the right operand of operators binding looser
+            (name, right) => match split_negative_literal(right) {
+                Ok((right, literal)) => Ok((
+                    expr::UntypedExpr::BinOp {
+                        location: left.location().union(right.location()),
+                        name,
+                        left,
+                        right: Box::new(right),
+                    },
+                    literal,
+                )),
+                Err(right) => Err(expr::UntypedExpr::BinOp {
+                    name,
+                    left,
+                    right: Box::new(right),
+                    location,
+                }),
+            },
+        },
+        expr => Err(expr),
+    }
+}
+
+/// An integer literal in a pattern or a guard, where it may be negative.
+fn signed_int_parser() -> impl Parser<Token, String, Error = ParseError> + Clone {
+    just(Token::Minus)
+        .or_not()
+        .then(select! {Token::Int {value} => value})
+        .map(|(minus, value)| match minus {
+            Some(_) => format!("-{}", value),
+            None => value,
+        })
+        .validate(|value, span, emit| {
+            if ast::int_value(&value).is_none() {
+                emit(ParseError::int_out_of_range(span));
+            }
+
+            value
+        })
+}
+
+pub fn when_clause_guard_parser() -> impl Parser<Token, ast::UntypedClauseGuard, Error = ParseError>
+{
+    recursive(|r| {
+        let var_parser = select! { Token::Name { name } => name }.map_with_span(|name, span| {
+            ast::ClauseGuard::Var {
+                location: span,
+                tipo: (),
+                name,
+            }
+        });
+
+        let constant_parser = choice((
+            signed_int_parser().map_with_span(|value, span| ast::Constant::Int {
+                location: span,
+                value,
+            }),
+            select! {Token::String {value} => value}.map_with_span(|value, span| {
+                ast::Constant::String {
+                    location: span,
+                    value,
+                }
+            }),
+            select! { Token::Name { name } => name }
+                .then_ignore(just(Token::Dot))
+                .or_not()
+                .then(select! { Token::UpName { name } => name })
+                .map_with_span(|(module, name), span| ast::Constant::Record {
+                    location: span,
+                    module,
+                    name,
+                    args: vec![],
+                    tag: (),
+                    tipo: (),
+                    field_map: None,
+                }),
+        ))
+        .map(ast::ClauseGuard::Constant);
+
+        let unit = choice((
+            constant_parser,
+            var_parser,
+            r.delimited_by(just(Token::LeftBrace), just(Token::RightBrace)),
+        ));
+
+        let bin_op = |left: ast::UntypedClauseGuard,
+                      (op, right): (Token, ast::UntypedClauseGuard)| {
+            let location = left.location().union(right.location());
+            let left = Box::new(left);
+            let right = Box::new(right);
+
+            match op {
+                Token::Less => ast::ClauseGuard::LtInt {
+                    location,
+                    left,
+                    right,
+                },
+                Token::LessEqual => ast::ClauseGuard::LtEqInt {
+                    location,
+                    left,
+                    right,
+                },
+                Token::Greater => ast::ClauseGuard::GtInt {
+                    location,
+                    left,
+                    right,
+                },
+                Token::GreaterEqual => ast::ClauseGuard::GtEqInt {
+                    location,
+                    left,
+                    right,
+                },
+                Token::EqualEqual => ast::ClauseGuard::Equals {
+                    location,
+                    left,
+                    right,
+                },
+                Token::NotEqual => ast::ClauseGuard::NotEquals {
+                    location,
+                    left,
+                    right,
+                },
+                Token::AmperAmper => ast::ClauseGuard::And {
+                    location,
+                    left,
+                    right,
+                },
+                _ => ast::ClauseGuard::Or {
+                    location,
+                    left,
+                    right,
+                },
+            }
+        };
+
+        let comparison = unit
+            .clone()
+            .then(
+                choice((
+                    just(Token::Less),
+                    just(Token::LessEqual),
+                    just(Token::Greater),
+                    just(Token::GreaterEqual),
+                ))
+                .then(unit)
+                .repeated(),
+            )
+            .foldl(bin_op)
+            .boxed();
+
+        let equality = comparison
+            .clone()
+            .then(
+                choice((just(Token::EqualEqual), just(Token::NotEqual)))
+                    .then(comparison)
+                    .repeated(),
+            )
+            .foldl(bin_op)
+            .boxed();
+
+        let and = equality
+            .clone()
+            .then(just(Token::AmperAmper).then(equality).repeated())
+            .foldl(bin_op)
+            .boxed();
+
+        and.clone()
+            .then(just(Token::VbarVbar).then(and).repeated())
+            .foldl(bin_op)
+    })
+}
+
 pub fn pattern_parser() -> impl Parser<Token, ast::UntypedPattern, Error = ParseError> {
     recursive(|r| {
         let record_constructor_pattern_arg_parser = choice((
@@ -1052,11 +1425,9 @@ pub fn pattern_parser() -> impl Parser<Token, ast::UntypedPattern, Error = Parse
                     value,
                 }
             }),
-            select! {Token::Int {value} => value}.map_with_span(|value, span| {
-                ast::UntypedPattern::Int {
-                    location: span,
-                    value,
-                }
+            signed_int_parser().map_with_span(|value, span| ast::UntypedPattern::Int {
+                location: span,
+                value,
             }),
             just(Token::LeftSquare)
                 .ignore_then(r.clone().separated_by(just(Token::Comma)))
@@ -24,6 +24,16 @@ impl ParseError {
         }
         self
     }
+
+    pub fn int_out_of_range(span: Span) -> Self {
+        Self {
+            kind: ErrorKind::IntOutOfRange,
+            span,
+            while_parsing: None,
+            expected: HashSet::new(),
+            label: None,
+        }
+    }
 }
 
 impl PartialEq for ParseError {
@@ -43,10 +53,13 @@ impl<T: Into<Pattern>> chumsky::Error<T> for ParseError {
         found: Option<T>,
     ) -> Self {
         Self {
-            kind: found
-                .map(Into::into)
-                .map(ErrorKind::Unexpected)
-                .unwrap_or(ErrorKind::UnexpectedEnd),
+            kind: match found.map(Into::into) {
+                // Names that became keywords, in code written before they did
+                Some(Pattern::Token(Token::Trace)) => ErrorKind::Keyword("trace"),
+                Some(Pattern::Token(Token::ErrorTerm)) => ErrorKind::Keyword("error"),
+                Some(found) => ErrorKind::Unexpected(found),
+                None => ErrorKind::UnexpectedEnd,
+            },
             span,
             while_parsing: None,
             expected: expected
@@ -83,6 +96,14 @@ pub enum ErrorKind {
     },
     #[error("no end branch")]
     NoEndBranch,
+    #[error("unexpected keyword {0}")]
+    #[diagnostic(help(
+        "`trace` and `error` are keywords: `trace(\"message\")` and `error(\"message\")` are expressions. Rename any variable called so, and use the builtin qualified, as `builtin.trace`."
+    ))]
+    Keyword(&'static str),
+    #[error("integer literal out of range")]
+    #[diagnostic(help("integer literals must fit in 128 bits"))]
+    IntOutOfRange,
 }
 
 #[derive(Debug, PartialEq, Eq, Hash, Diagnostic, thiserror::Error)]
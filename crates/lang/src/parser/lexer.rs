@@ -1,11 +1,38 @@
 use chumsky::prelude::*;
 
-use crate::ast::Span;
+use crate::ast::{int_value, Span};
 
 use super::{error::ParseError, token::Token};
 
 pub fn lexer() -> impl Parser<char, Vec<(Token, Span)>, Error = ParseError> {
-    let int = choice((text::int(10), text::int(16))).map(|value| Token::Int { value });
+    // Digits of `radix`, possibly separated by single underscores.
+    let digits = |radix: u32| {
+        filter(move |c: &char| c.is_digit(radix))
+            .repeated()
+            .at_least(1)
+            .collect::<String>()
+            .separated_by(just('_'))
+            .at_least(1)
+            .map(|groups| groups.join("_"))
+    };
+
+    let int = choice((
+        just("0x")
+            .then(digits(16))
+            .map(|(prefix, digits)| prefix.to_string() + &digits),
+        just("0b")
+            .then(digits(2))
+            .map(|(prefix, digits)| prefix.to_string() + &digits),
+        digits(10),
+    ))
+    .validate(|value, span, emit| {
+        // Only the magnitude is lexed, `-` may follow to make it `i128::MIN`
+        if int_value(&format!("-{}", value)).is_none() {
+            emit(ParseError::int_out_of_range(span));
+        }
+
+        Token::Int { value }
+    });
 
     let op = choice((
         just("==").to(Token::EqualEqual),
@@ -63,19 +90,22 @@ pub fn lexer() -> impl Parser<char, Vec<(Token, Span)>, Error = ParseError> {
     let keyword = text::ident().map(|s: String| match s.as_str() {
         "as" => Token::As,
         "assert" => Token::Assert,
-        "check" => Token::Assert,
+        "check" => Token::Check,
         "const" => Token::Const,
         "fn" => Token::Fn,
         "if" => Token::If,
         "else" => Token::Else,
+        "error" => Token::ErrorTerm,
         "is" => Token::Is,
         "let" => Token::Let,
         "opaque" => Token::Opaque,
         "pub" => Token::Pub,
         "use" => Token::Use,
         "todo" => Token::Todo,
+        "trace" => Token::Trace,
         "try" => Token::Try,
         "type" => Token::Type,
+        "validator" => Token::Validator,
         "when" => Token::When,
         _ => {
             if s.chars().next().map_or(false, |c| c.is_uppercase()) {
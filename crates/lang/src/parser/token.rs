@@ -63,14 +63,17 @@ pub enum Token {
     Fn,
     If,
     Else,
+    ErrorTerm,
     Is,
     Let,
     Opaque,
     Pub,
     Use,
     Todo,
+    Trace,
     Try,
     Type,
+    Validator,
     When,
 }
 
@@ -138,13 +141,16 @@ impl fmt::Display for Token {
             Token::Fn => "fn",
             Token::If => "if",
             Token::Else => "else",
+            Token::ErrorTerm => "error",
             Token::Use => "import",
             Token::Let => "let",
             Token::Opaque => "opaque",
             Token::Pub => "pub",
             Token::Todo => "todo",
+            Token::Trace => "trace",
             Token::Try => "try",
             Token::Type => "type",
+            Token::Validator => "validator",
         };
         write!(f, "\"{}\"", s)
     }
@@ -0,0 +1,1859 @@
+use std::collections::HashMap;
+
+use serde_json::json;
+use uplc::{
+    ast::{Constant, Name, NamedDeBruijn, Program, Term},
+    json,
+    machine::Error,
+};
+
+use crate::{
+    ast::{
+        CallArg, Constant as AikenConstant, Definition, Function, ModuleConstant, ModuleKind, Span,
+        TypedModule, UntypedConstant,
+    },
+    builtins, parser,
+    uplc::{
+        error::Error as CodeGenError, holds_functions, CodeGenerator, DataTypeKey,
+        FunctionAccessKey,
+    },
+    IdGenerator,
+};
+
+/// Type-check `modules` in order and compile the function `name` of the last
+/// one as if it were a validator. Handlers of `validator` blocks take the
+/// parameters of their block first.
+fn compile(modules: &[(&str, &str)], name: &str, keep_traces: bool) -> Program<Name> {
+    compile_with_constants(modules, &[], name, keep_traces).expect("failed to generate code")
+}
+
+/// Like `compile`, with public `constants` added to the modules they are
+/// given with, as constants have no syntax of their own yet.
+fn compile_with_constants(
+    modules: &[(&str, &str)],
+    constants: &[(&str, &str, UntypedConstant)],
+    name: &str,
+    keep_traces: bool,
+) -> Result<Program<Name>, Vec<CodeGenError>> {
+    let checked = type_check(modules, constants);
+
+    let mut functions = HashMap::new();
+    let mut data_types = HashMap::new();
+
+    for module in checked.iter() {
+        for def in module.definitions() {
+            match def {
+                Definition::Fn(func) => {
+                    functions.insert(
+                        FunctionAccessKey {
+                            module_name: module.name.clone(),
+                            function_name: func.name.clone(),
+                        },
+                        func,
+                    );
+                }
+                Definition::DataType(dt) => {
+                    data_types.insert(
+                        DataTypeKey {
+                            module_name: module.name.clone(),
+                            defined_type: dt.name.clone(),
+                        },
+                        dt,
+                    );
+                }
+                _ => {}
+            }
+        }
+    }
+
+    let validator = checked
+        .last()
+        .and_then(|module| {
+            module.definitions().find_map(|def| match def {
+                Definition::Fn(func) if func.name == name => Some(func.clone()),
+                Definition::Validator(validator) => validator
+                    .handlers
+                    .iter()
+                    .find(|handler| handler.name == name)
+                    .map(|handler| Function {
+                        arguments: validator
+                            .params
+                            .iter()
+                            .chain(&handler.arguments)
+                            .cloned()
+                            .collect(),
+                        ..handler.clone()
+                    }),
+                _ => None,
+            })
+        })
+        .expect("unknown function");
+
+    let mut generator = CodeGenerator::new(&functions, &data_types, keep_traces);
+
+    let module = &checked.last().unwrap().name;
+
+    generator.generate(module, validator.body, validator.arguments)
+}
+
+/// Type-check `modules` in order, with public `constants` added to the
+/// modules they are given with.
+fn type_check(
+    modules: &[(&str, &str)],
+    constants: &[(&str, &str, UntypedConstant)],
+) -> Vec<TypedModule> {
+    let id_gen = IdGenerator::new();
+
+    let mut module_types = HashMap::new();
+
+    module_types.insert("aiken".to_string(), builtins::prelude(&id_gen));
+    module_types.insert("aiken/builtin".to_string(), builtins::plutus(&id_gen));
+
+    let mut checked: Vec<TypedModule> = Vec::new();
+
+    for (module_name, src) in modules {
+        let (mut ast, _) = parser::module(src, ModuleKind::Lib).expect("failed to parse");
+
+        ast.name = module_name.to_string();
+
+        for (_, name, value) in constants
+            .iter()
+            .filter(|(module, ..)| module == module_name)
+        {
+            ast.definitions
+                .push(Definition::ModuleConstant(ModuleConstant {
+                    doc: None,
+                    location: Span::empty(),
+                    public: true,
+                    name: name.to_string(),
+                    annotation: None,
+                    value: Box::new(value.clone()),
+                    tipo: (),
+                }));
+        }
+
+        let ast = ast
+            .infer(&id_gen, ModuleKind::Lib, "test", &module_types, &mut vec![])
+            .expect("failed to type check");
+
+        module_types.insert(module_name.to_string(), ast.type_info.clone());
+
+        checked.push(ast);
+    }
+
+    checked
+}
+
+/// Evaluate the function `name`, passing it `args` given in the detailed JSON
+/// schema of Plutus data.
+fn eval(
+    modules: &[(&str, &str)],
+    name: &str,
+    args: Vec<serde_json::Value>,
+) -> Result<Term<NamedDeBruijn>, Error> {
+    let mut program = compile(modules, name, true);
+
+    for arg in args {
+        program = program.apply_data(json::json_to_plutus_data(&arg).unwrap());
+    }
+
+    let program: Program<NamedDeBruijn> = program.try_into().unwrap();
+
+    let (result, _, _) = program.eval();
+
+    result
+}
+
+fn assert_succeeds(modules: &[(&str, &str)], name: &str) {
+    match eval(modules, name, vec![]) {
+        Ok(Term::Constant(Constant::Unit)) => {}
+        result => panic!("{} did not evaluate to True: {:?}", name, result),
+    }
+}
+
+fn assert_fails(modules: &[(&str, &str)], name: &str) {
+    assert!(
+        eval(modules, name, vec![]).is_err(),
+        "{} did not fail",
+        name
+    );
+}
+
+const LIST: &str = r#"
+use aiken/builtin
+
+pub fn head(xs: List(a)) -> Option(a) {
+  when xs is {
+    [] -> None
+    _ -> Some(builtin.head_list(xs))
+  }
+}
+
+pub fn length(xs: List(a)) -> Int {
+  when xs is {
+    [] -> 0
+    [_, ..rest] -> 1 + length(rest)
+  }
+}
+
+pub fn take(xs: List(a), n: Int) -> List(a) {
+  if n <= 0 {
+    []
+  } else {
+    when xs is {
+      [] -> []
+      [x, ..rest] -> [x, ..take(rest, n - 1)]
+    }
+  }
+}
+
+pub fn repeat(x: a, n: Int) -> List(a) {
+  if n <= 0 {
+    []
+  } else {
+    [x, ..repeat(x, n - 1)]
+  }
+}
+
+pub fn is_elem(xs: List(a), x: a) -> Bool {
+  when xs is {
+    [] -> False
+    [y, ..rest] ->
+      if x == y {
+        True
+      } else {
+        is_elem(rest, x)
+      }
+  }
+}
+
+pub fn map(xs: List(a), f: fn(a) -> b) -> List(b) {
+  when xs is {
+    [] -> []
+    [x, ..rest] -> [f(x), ..map(rest, f)]
+  }
+}
+
+pub fn foldr(xs: List(a), f: fn(a, b) -> b, zero: b) -> b {
+  when xs is {
+    [] -> zero
+    [x, ..rest] -> f(x, foldr(rest, f, zero))
+  }
+}
+
+pub fn concat(left: List(a), right: List(a)) -> List(a) {
+  foldr(left, fn(x, xs) { [x, ..xs] }, right)
+}
+
+pub fn filter(xs: List(a), f: fn(a) -> Bool) -> List(a) {
+  foldr(
+    xs,
+    fn(x, ys) {
+      if f(x) {
+        [x, ..ys]
+      } else {
+        ys
+      }
+    },
+    [],
+  )
+}
+
+pub fn all(xs: List(a), predicate: fn(a) -> Bool) -> Bool {
+  foldr(xs, fn(x, result) { predicate(x) && result }, True)
+}
+"#;
+
+const OPTION: &str = r#"
+pub fn with_default(opt: Option(a), default: a) -> a {
+  when opt is {
+    None -> default
+    Some(a) -> a
+  }
+}
+
+pub fn map(opt: Option(a), f: fn(a) -> b) -> Option(b) {
+  when opt is {
+    None -> None
+    Some(a) -> Some(f(a))
+  }
+}
+"#;
+
+#[test]
+fn generic_functions_over_lists() {
+    let modules = [
+        ("aiken/list", LIST),
+        (
+            "test",
+            r#"
+            use aiken/builtin
+            use aiken/list
+
+            pub fn length_of_ints() {
+              list.length([1, 2, 3]) == 3
+            }
+
+            pub fn length_of_strings() {
+              list.length(["a", "b"]) == 2
+            }
+
+            pub fn length_of_empty() {
+              list.length([]) == 0
+            }
+
+            pub fn take_strings() {
+              list.take(["a", "b", "c"], 2) == ["a", "b"]
+            }
+
+            pub fn repeat_bool() {
+              list.repeat(True, 3) == [True, True, True]
+            }
+
+            pub fn is_elem_bytearray() {
+              let a = builtin.encode_utf8("a")
+              let b = builtin.encode_utf8("b")
+              list.is_elem([a, b], b) && !list.is_elem([a], b)
+            }
+
+            pub fn head_of_nested() {
+              list.head([[1, 2], [3]]) == Some([1, 2])
+            }
+
+            pub fn head_of_empty() {
+              list.head([]) == None
+            }
+            "#,
+        ),
+    ];
+
+    for name in [
+        "length_of_ints",
+        "length_of_strings",
+        "length_of_empty",
+        "take_strings",
+        "repeat_bool",
+        "is_elem_bytearray",
+        "head_of_nested",
+        "head_of_empty",
+    ] {
+        assert_succeeds(&modules, name);
+    }
+}
+
+#[test]
+fn generic_functions_over_options() {
+    let modules = [
+        ("aiken/option", OPTION),
+        (
+            "test",
+            r#"
+            use aiken/option
+
+            pub fn default_int() {
+              option.with_default(None, 14) + option.with_default(Some(28), 0) == 42
+            }
+
+            pub fn default_string() {
+              option.with_default(Some("foo"), "bar") == "foo"
+            }
+
+            pub fn default_bool() {
+              option.with_default(Some(False), True) == False
+            }
+            "#,
+        ),
+    ];
+
+    for name in ["default_int", "default_string", "default_bool"] {
+        assert_succeeds(&modules, name);
+    }
+}
+
+#[test]
+fn generic_functions_with_function_arguments() {
+    let modules = [
+        ("aiken/list", LIST),
+        ("aiken/option", OPTION),
+        (
+            "test",
+            r#"
+            use aiken/list
+            use aiken/option
+
+            fn double(n: Int) -> Int {
+              n * 2
+            }
+
+            fn is_positive(n: Int) -> Bool {
+              n > 0
+            }
+
+            pub fn map_ints() {
+              list.map([1, 2, 3], double) == [2, 4, 6]
+            }
+
+            pub fn map_changes_type() {
+              list.map([0 - 1, 2], is_positive) == [False, True]
+            }
+
+            pub fn map_option() {
+              option.map(Some(21), double) == Some(42)
+            }
+            "#,
+        ),
+    ];
+
+    for name in ["map_ints", "map_changes_type", "map_option"] {
+        assert_succeeds(&modules, name);
+    }
+}
+
+#[test]
+fn generic_custom_types() {
+    let modules = [(
+        "test",
+        r#"
+        type Pair(a, b) {
+          Pair(a, b)
+        }
+
+        type Tree(a) {
+          Leaf
+          Node { left: Tree(a), value: a, right: Tree(a) }
+        }
+
+        type Wrapper(a) {
+          inner: a,
+          count: Int,
+        }
+
+        fn first(pair: Pair(a, b)) -> a {
+          when pair is {
+            Pair(a, _) -> a
+          }
+        }
+
+        fn second(pair: Pair(a, b)) -> b {
+          when pair is {
+            Pair(_, b) -> b
+          }
+        }
+
+        fn size(tree: Tree(a)) -> Int {
+          when tree is {
+            Leaf -> 0
+            Node { left, right, .. } -> size(left) + 1 + size(right)
+          }
+        }
+
+        pub fn pair_fields() {
+          first(Pair(1, "one")) == 1 && second(Pair(1, "one")) == "one"
+        }
+
+        pub fn tree_size() {
+          let tree = Node(Node(Leaf, "a", Leaf), "b", Leaf)
+          size(tree) == 2
+        }
+
+        pub fn record_access() {
+          let wrapper = Wrapper([True], 1)
+          wrapper.inner == [True] && wrapper.count == 1
+        }
+
+        pub fn mismatch() {
+          first(Pair(1, 2)) == 2
+        }
+        "#,
+    )];
+
+    for name in ["pair_fields", "tree_size", "record_access"] {
+        assert_succeeds(&modules, name);
+    }
+
+    assert_fails(&modules, "mismatch");
+}
+
+#[test]
+fn one_definition_per_instantiation() {
+    let program = compile(
+        &[
+            ("aiken/list", LIST),
+            (
+                "test",
+                r#"
+                use aiken/list
+
+                pub fn heads() {
+                  list.head([1]) == Some(1) && list.head([2]) == Some(2) && list.head(["a"]) == Some("a")
+                }
+                "#,
+            ),
+        ],
+        "heads",
+        true,
+    )
+    .to_pretty()
+    .split_whitespace()
+    .collect::<Vec<_>>()
+    .join(" ");
+
+    assert_eq!(program.matches("(lam aiken_list_head_Int ").count(), 1);
+    assert_eq!(program.matches("(lam aiken_list_head_String ").count(), 1);
+}
+
+#[test]
+fn anonymous_functions_and_closures() {
+    let modules = [
+        ("aiken/list", LIST),
+        (
+            "test",
+            r#"
+            use aiken/list
+
+            fn add(a: Int, b: Int) -> Int {
+              a + b
+            }
+
+            fn twice(f: fn(Int) -> Int, initial: Int) -> Int {
+              f(f(initial))
+            }
+
+            fn adder(n: Int) -> fn(Int) -> Int {
+              fn(x) { x + n }
+            }
+
+            pub fn lambda_argument() {
+              list.map([1, 2, 3], fn(n) { n * 10 }) == [10, 20, 30]
+            }
+
+            pub fn capturing_lambda() {
+              let offset = 5
+              list.map([1, 2], fn(n) { n + offset }) == [6, 7]
+            }
+
+            pub fn returned_closure() {
+              let add_three = adder(3)
+              twice(add_three, 1) == 7 && adder(1)(1) == 2
+            }
+
+            pub fn lambdas_in_library() {
+              list.concat([1, 2], [3]) == [1, 2, 3] && list.filter([1, 2, 3, 4], fn(n) { n > 2 }) == [3, 4] && list.all(["a", "b"], fn(s) { s != "c" })
+            }
+
+            pub fn capture_syntax() {
+              list.map([1, 2], add(_, 10)) == [11, 12]
+            }
+
+            pub fn partial_application_with_pipes() {
+              let result =
+                1
+                |> add(2)
+                |> adder(4)
+                |> twice(adder(1), _)
+              let xs = [1, 2] |> list.map(adder(1))
+              result == 9 && xs == [2, 3]
+            }
+
+            pub fn thunk() {
+              let f = fn() { 42 }
+              f() == 42
+            }
+            "#,
+        ),
+    ];
+
+    for name in [
+        "lambda_argument",
+        "capturing_lambda",
+        "returned_closure",
+        "lambdas_in_library",
+        "capture_syntax",
+        "partial_application_with_pipes",
+        "thunk",
+    ] {
+        assert_succeeds(&modules, name);
+    }
+}
+
+#[test]
+fn functions_stored_in_records() {
+    let modules = [(
+        "test",
+        r#"
+        type Validator {
+          predicate: fn(Int) -> Bool,
+          name: String,
+        }
+
+        type Rule(a) {
+          Always
+          When(fn(a) -> Bool)
+        }
+
+        fn run(v: Validator, n: Int) -> Bool {
+          v.predicate(n)
+        }
+
+        fn apply(rule: Rule(a), value: a) -> Bool {
+          when rule is {
+            Always -> True
+            When(predicate) -> predicate(value)
+          }
+        }
+
+        pub fn record_field() {
+          let positive = Validator(fn(n) { n > 0 }, "positive")
+          run(positive, 1) && !run(positive, 0) && positive.name == "positive"
+        }
+
+        pub fn pattern_match() {
+          apply(Always, 0) && apply(When(fn(n) { n == 2 }), 2) && !apply(When(fn(n) { n == 2 }), 3)
+        }
+
+        pub fn option_of_function() {
+          let f = Some(fn(n) { n + 1 })
+          when f is {
+            Some(f) -> f(1) == 2
+            None -> False
+          }
+        }
+        "#,
+    )];
+
+    for name in ["record_field", "pattern_match", "option_of_function"] {
+        assert_succeeds(&modules, name);
+    }
+}
+
+#[test]
+fn lists_of_functions() {
+    let modules = [
+        ("aiken/list", LIST),
+        (
+            "test",
+            r#"
+            use aiken/builtin
+            use aiken/list
+
+            type Predicate {
+              run: fn(Int) -> Bool,
+            }
+
+            fn functions() -> List(fn(Int) -> Int) {
+              [fn(x: Int) { x + 1 }, fn(x) { x * 2 }]
+            }
+
+            pub fn patterns() {
+              when functions() is {
+                [f, g] -> f(1) == 2 && g(3) == 6
+                _ -> False
+              }
+            }
+
+            pub fn generic_functions() {
+              let fs = [fn(x: Int) { x - 1 }, ..functions()]
+              list.map(fs, fn(f) { f(10) }) == [9, 11, 20] && list.length(list.repeat(fs, 2)) == 2
+            }
+
+            pub fn builtins() {
+              let fs = functions()
+              when list.head(fs) is {
+                Some(f) -> f(0) == 1 && builtin.head_list(builtin.tail_list(fs))(4) == 8 && !builtin.null_list(fs)
+                None -> False
+              }
+            }
+
+            pub fn records_in_lists() {
+              let predicates = [Predicate(fn(n) { n > 0 }), Predicate { run: fn(n) { n < 10 } }]
+              list.foldr(predicates, fn(predicate, ok) { ok && predicate.run(5) }, True)
+            }
+            "#,
+        ),
+    ];
+
+    for name in [
+        "patterns",
+        "generic_functions",
+        "builtins",
+        "records_in_lists",
+    ] {
+        assert_succeeds(&modules, name);
+    }
+}
+
+#[test]
+fn types_holding_functions() {
+    let modules = [(
+        "test",
+        r#"
+        pub type Predicate {
+          run: fn(Int) -> Bool,
+        }
+
+        pub type Box(a) {
+          content: a,
+        }
+
+        pub type Tree {
+          Leaf
+          Node { left: Tree, right: Tree, value: Int }
+        }
+
+        pub fn f(
+          a: Int,
+          b: Predicate,
+          c: List(Predicate),
+          d: Option(fn(Int) -> Int),
+          e: Box(Box(fn() -> Int)),
+          f: Box(List(Int)),
+          g: Tree,
+          h: fn(Int) -> Int,
+        ) {
+          True
+        }
+        "#,
+    )];
+
+    let checked = type_check(&modules, &[]);
+
+    let module = &checked[0];
+
+    let data_types = module
+        .definitions()
+        .filter_map(|def| match def {
+            Definition::DataType(dt) => Some((
+                DataTypeKey {
+                    module_name: module.name.clone(),
+                    defined_type: dt.name.clone(),
+                },
+                dt,
+            )),
+            _ => None,
+        })
+        .collect::<HashMap<_, _>>();
+
+    let f = module
+        .definitions()
+        .find_map(|def| match def {
+            Definition::Fn(func) => Some(func),
+            _ => None,
+        })
+        .unwrap();
+
+    assert_eq!(
+        f.arguments
+            .iter()
+            .map(|arg| holds_functions(&arg.tipo, &data_types))
+            .collect::<Vec<_>>(),
+        [false, true, true, true, true, false, false, true]
+    );
+}
+
+#[test]
+fn try_expressions() {
+    let modules = [(
+        "test",
+        r#"
+        pub fn unwrap() {
+          try x = todo
+          todo
+        }
+        "#,
+    )];
+
+    let errors =
+        compile_with_constants(&modules, &[], "unwrap", false).expect_err("try can't be compiled");
+
+    assert!(matches!(
+        errors.as_slice(),
+        [CodeGenError::Try { module, location }]
+            if module == "test" && &modules[0].1[location.start..location.end] == "try x = todo\n          todo"
+    ));
+}
+
+#[test]
+fn values_holding_functions_as_data() {
+    let modules = [
+        ("aiken/list", LIST),
+        (
+            "test",
+            r#"
+            use aiken/list
+
+            pub type Predicate {
+              run: fn(Int) -> Bool,
+            }
+
+            pub fn comparison() {
+              let f = fn(x: Int) { x }
+              [f] == [f]
+            }
+
+            pub fn generic_comparison() {
+              let f = fn(x: Int) { x }
+              list.is_elem([f], f)
+            }
+
+            pub fn cast(data: Data) {
+              assert predicate: Predicate is data
+              predicate.run(1)
+            }
+
+            pub fn argument(predicate: Predicate) {
+              predicate.run(1)
+            }
+            "#,
+        ),
+    ];
+
+    let errors = |name| {
+        compile_with_constants(&modules, &[], name, false)
+            .expect_err("values holding functions can't be data")
+            .into_iter()
+            .map(|error| (error.module().to_string(), error.to_string()))
+            .collect::<Vec<_>>()
+    };
+
+    assert_eq!(
+        errors("comparison"),
+        [(
+            "test".to_string(),
+            "values of type List(fn(Int) -> Int) can't be compared".to_string()
+        )]
+    );
+    assert_eq!(
+        errors("generic_comparison"),
+        [(
+            "aiken/list".to_string(),
+            "values of type fn(Int) -> Int can't be compared".to_string()
+        )]
+    );
+    assert_eq!(
+        errors("cast"),
+        [(
+            "test".to_string(),
+            "data can't be cast into Predicate".to_string()
+        )]
+    );
+    assert_eq!(
+        errors("argument"),
+        [(
+            "test".to_string(),
+            "data can't be cast into Predicate".to_string()
+        )]
+    );
+}
+
+#[test]
+fn recursive_functions() {
+    let modules = [
+        ("aiken/list", LIST),
+        (
+            "test",
+            r#"
+            use aiken/list
+
+            fn foldl(xs: List(a), f: fn(a, b) -> b, acc: b) -> b {
+              when xs is {
+                [] -> acc
+                [x, ..rest] -> foldl(rest, f, f(x, acc))
+              }
+            }
+
+            fn sum(n: Int) -> Int {
+              if n == 0 {
+                0
+              } else {
+                n + sum(n - 1)
+              }
+            }
+
+            pub fn folds() {
+              let total = list.foldr([1, 2, 3], fn(x, acc) { x + acc }, 0)
+              let reversed = foldl([1, 2, 3], fn(x, acc) { [x, ..acc] }, [])
+              total == 6 && reversed == [3, 2, 1]
+            }
+
+            pub fn lengths() {
+              list.length(list.repeat("a", 10)) == 10 && list.length([[1], []]) == 2
+            }
+
+            pub fn counting_down() {
+              sum(100) == 5050
+            }
+            "#,
+        ),
+    ];
+
+    for name in ["folds", "lengths", "counting_down"] {
+        assert_succeeds(&modules, name);
+    }
+}
+
+#[test]
+fn mutually_recursive_functions() {
+    let modules = [
+        (
+            "aiken/parity",
+            r#"
+            pub fn is_even(n: Int) -> Bool {
+              if n == 0 {
+                True
+              } else {
+                is_odd(n - 1)
+              }
+            }
+
+            pub fn is_odd(n: Int) -> Bool {
+              if n == 0 {
+                False
+              } else {
+                is_even(n - 1)
+              }
+            }
+            "#,
+        ),
+        (
+            "test",
+            r#"
+            use aiken/parity
+
+            type Tree(a) {
+              Tree { value: a, children: List(Tree(a)) }
+            }
+
+            fn size(tree: Tree(a)) -> Int {
+              when tree is {
+                Tree { children, .. } -> 1 + forest_size(children)
+              }
+            }
+
+            fn forest_size(forest: List(Tree(a))) -> Int {
+              when forest is {
+                [] -> 0
+                [tree, ..rest] -> size(tree) + forest_size(rest)
+              }
+            }
+
+            pub fn even_and_odd() {
+              parity.is_even(10) && parity.is_odd(7) && !parity.is_even(3) && !parity.is_odd(0)
+            }
+
+            pub fn odd_is_not_even() {
+              parity.is_even(11)
+            }
+
+            pub fn tree_sizes() {
+              let leaf = Tree(1, [])
+              let strings = Tree("a", [Tree("b", []), Tree("c", [])])
+              size(Tree(0, [leaf, Tree(2, [leaf])])) == 4 && size(strings) == 3
+            }
+            "#,
+        ),
+    ];
+
+    for name in ["even_and_odd", "tree_sizes"] {
+        assert_succeeds(&modules, name);
+    }
+
+    assert_fails(&modules, "odd_is_not_even");
+}
+
+#[test]
+fn assert_assignments() {
+    let modules = [(
+        "test",
+        r#"
+        type Datum {
+          Locked { owner: String, amount: Int }
+          Unlocked
+        }
+
+        fn amount(datum: Datum) -> Int {
+          assert Locked { amount, .. } is datum
+          amount
+        }
+
+        fn first_of_pair(xs: List(Int)) -> Int {
+          assert [a, _] is xs
+          a
+        }
+
+        pub fn assert_matches() {
+          amount(Locked("alice", 42)) == 42 && first_of_pair([1, 2]) == 1
+        }
+
+        pub fn assert_mismatch() {
+          amount(Unlocked) == 0
+        }
+
+        pub fn assert_list_mismatch() {
+          first_of_pair([1, 2, 3]) == 1
+        }
+
+        pub fn trailing_assert() {
+          let ok = 1 < 2
+          assert True is ok
+        }
+        "#,
+    )];
+
+    for name in ["assert_matches", "trailing_assert"] {
+        assert_succeeds(&modules, name);
+    }
+
+    for name in ["assert_mismatch", "assert_list_mismatch"] {
+        assert_fails(&modules, name);
+    }
+}
+
+#[test]
+fn check_assignments_validate_data() {
+    let modules = [(
+        "test",
+        r#"
+        pub type Tag {
+          Tag(Int)
+        }
+
+        pub type Datum {
+          Locked { owner: ByteArray, amount: Int, tags: List(Tag) }
+          Unlocked
+        }
+
+        pub type Tree {
+          Leaf(Bool)
+          Node(Tree, Tree)
+        }
+
+        pub fn checked(datum: Datum) {
+          check Locked { amount, .. } is datum
+          amount > 0
+        }
+
+        pub fn asserted(datum: Datum) {
+          assert Locked { amount, .. } is datum
+          amount > 0
+        }
+
+        pub fn checked_tree(tree: Tree) {
+          check _ is tree
+          True
+        }
+        "#,
+    )];
+
+    let locked = |owner, amount, tags| json!({ "constructor": 0, "fields": [owner, amount, { "list": tags }] });
+
+    let tag = json!({ "constructor": 0, "fields": [{ "int": 1 }] });
+
+    let valid = locked(json!({ "bytes": "00" }), json!({ "int": 1 }), json!([tag]));
+
+    let malformed = [
+        // Unknown constructor.
+        json!({ "constructor": 2, "fields": [] }),
+        // Missing field.
+        json!({ "constructor": 0, "fields": [{ "bytes": "00" }, { "int": 1 }] }),
+        // Extra field.
+        json!({ "constructor": 1, "fields": [{ "int": 1 }] }),
+        // Field of the wrong type.
+        locked(json!({ "int": 0 }), json!({ "int": 1 }), json!([])),
+        // Malformed element of a nested list.
+        locked(
+            json!({ "bytes": "00" }),
+            json!({ "int": 1 }),
+            json!([tag, { "int": 1 }]),
+        ),
+    ];
+
+    for name in ["checked", "asserted"] {
+        assert!(matches!(
+            eval(&modules, name, vec![valid.clone()]),
+            Ok(Term::Constant(Constant::Unit))
+        ));
+    }
+
+    for datum in malformed.iter() {
+        assert!(
+            eval(&modules, "checked", vec![datum.clone()]).is_err(),
+            "accepted {}",
+            datum
+        );
+    }
+
+    // A plain `assert` only looks at what its pattern needs.
+    assert!(eval(&modules, "asserted", vec![malformed[1].clone()]).is_ok());
+    assert!(eval(&modules, "asserted", vec![malformed[4].clone()]).is_ok());
+
+    let leaf =
+        |value| json!({ "constructor": 0, "fields": [{ "constructor": value, "fields": [] }] });
+    let node = |left, right| json!({ "constructor": 1, "fields": [left, right] });
+
+    let well_formed = node(leaf(1), node(leaf(0), leaf(1)));
+    let bad_leaf = node(leaf(1), node(leaf(2), leaf(1)));
+
+    assert!(eval(&modules, "checked_tree", vec![well_formed]).is_ok());
+    assert!(eval(&modules, "checked_tree", vec![bad_leaf]).is_err());
+}
+
+#[test]
+fn casting_data_into_custom_types() {
+    let modules = [(
+        "test",
+        r#"
+        pub type Datum {
+          owner: ByteArray,
+          amounts: List(Int),
+          deadline: Option(Int),
+        }
+
+        fn sum(xs: List(Int)) -> Int {
+          when xs is {
+            [] -> 0
+            [x, ..rest] -> x + sum(rest)
+          }
+        }
+
+        pub fn cast_record(raw: Data) {
+          assert datum: Datum is raw
+          sum(datum.amounts) == 3 && datum.deadline == Some(10)
+        }
+
+        pub fn cast_list(raw: Data) {
+          assert amounts: List(Int) is raw
+          sum(amounts) == 3
+        }
+
+        pub fn unchecked_cast(raw: Data) {
+          let datum: Datum = raw
+          datum.owner == datum.owner
+        }
+        "#,
+    )];
+
+    let datum = |owner, amounts, deadline| json!({ "constructor": 0, "fields": [owner, { "list": amounts }, deadline] });
+
+    let deadline = json!({ "constructor": 0, "fields": [{ "int": 10 }] });
+    let amounts = json!([{ "int": 1 }, { "int": 2 }]);
+
+    let valid = datum(json!({ "bytes": "00" }), amounts.clone(), deadline.clone());
+
+    assert!(matches!(
+        eval(&modules, "cast_record", vec![valid]),
+        Ok(Term::Constant(Constant::Unit))
+    ));
+
+    assert!(matches!(
+        eval(&modules, "cast_list", vec![json!({ "list": amounts })]),
+        Ok(Term::Constant(Constant::Unit))
+    ));
+
+    let malformed = [
+        // Malformed element of a nested list.
+        datum(
+            json!({ "bytes": "00" }),
+            json!([{ "int": 1 }, { "bytes": "02" }]),
+            deadline.clone(),
+        ),
+        // Malformed option.
+        datum(
+            json!({ "bytes": "00" }),
+            json!([]),
+            json!({ "constructor": 1, "fields": [{ "int": 10 }] }),
+        ),
+        // Not a record at all.
+        json!({ "int": 1 }),
+    ];
+
+    for raw in malformed.iter() {
+        let program: Program<NamedDeBruijn> = compile(&modules, "cast_record", true)
+            .apply_data(json::json_to_plutus_data(raw).unwrap())
+            .try_into()
+            .unwrap();
+
+        let (result, _, logs) = program.eval();
+
+        assert!(result.is_err(), "accepted {}", raw);
+        assert_eq!(logs, vec!["datum is not a valid Datum".to_string()]);
+    }
+
+    assert!(eval(&modules, "cast_list", vec![json!({ "int": 1 })]).is_err());
+
+    // A `let` converts without validating.
+    assert!(eval(&modules, "unchecked_cast", vec![malformed[1].clone()]).is_ok());
+}
+
+#[test]
+fn same_type_names_in_different_modules() {
+    let modules = [
+        (
+            "a/x",
+            r#"
+            pub type Thing {
+              n: Int,
+            }
+            "#,
+        ),
+        (
+            "a/y",
+            r#"
+            pub type Thing {
+              label: ByteArray,
+              m: Int,
+            }
+            "#,
+        ),
+        (
+            "a/z",
+            r#"
+            pub type Thing {
+              f: fn(Int) -> Int,
+            }
+
+            pub fn new() -> Thing {
+              Thing { f: fn(n) { n + 1 } }
+            }
+            "#,
+        ),
+        (
+            "test",
+            r#"
+            use aiken/builtin
+            use a/x
+            use a/y
+            use a/z
+
+            fn first(xs: List(a)) -> a {
+              builtin.head_list(xs)
+            }
+
+            pub fn casts(datum: Data, redeemer: Data) {
+              assert a: x.Thing is datum
+              assert b: y.Thing is redeemer
+              a.n == 1 && b.m == 2
+            }
+
+            pub fn specialisations() {
+              let a = first([x.Thing { n: 1 }])
+              let b = first([z.new()])
+              a.n == 1 && b.f(1) == 2
+            }
+            "#,
+        ),
+    ];
+
+    assert!(matches!(
+        eval(
+            &modules,
+            "casts",
+            vec![
+                json!({ "constructor": 0, "fields": [{ "int": 1 }] }),
+                json!({ "constructor": 0, "fields": [{ "bytes": "00" }, { "int": 2 }] }),
+            ]
+        ),
+        Ok(Term::Constant(Constant::Unit))
+    ));
+
+    assert_succeeds(&modules, "specialisations");
+}
+
+#[test]
+fn traces_and_errors() {
+    let modules = [(
+        "test",
+        r#"
+        fn positive(n: Int) -> Int {
+          if n > 0 {
+            n
+          } else {
+            error("not positive")
+          }
+        }
+
+        pub fn traced() {
+          trace("start")
+          let n = positive(1)
+          trace("checked")
+          n == 1
+        }
+
+        pub fn failing() {
+          trace("start")
+          positive(0) == 0
+        }
+
+        pub fn silent_error() {
+          let n = when Some(1) is {
+            Some(n) -> n
+            None -> error
+          }
+          n == 1 && error == 0
+        }
+        "#,
+    )];
+
+    let run = |name, keep_traces| {
+        let program: Program<NamedDeBruijn> =
+            compile(&modules, name, keep_traces).try_into().unwrap();
+
+        let (result, _, logs) = program.eval();
+
+        (result.is_ok(), logs)
+    };
+
+    assert_eq!(
+        run("traced", true),
+        (true, vec!["start".to_string(), "checked".to_string()])
+    );
+    assert_eq!(
+        run("failing", true),
+        (false, vec!["start".to_string(), "not positive".to_string()])
+    );
+    assert_eq!(run("silent_error", true), (false, vec![]));
+
+    assert_eq!(run("traced", false), (true, vec![]));
+    assert_eq!(run("failing", false), (false, vec![]));
+
+    assert!(!compile(&modules, "failing", false)
+        .to_pretty()
+        .contains("trace"));
+}
+
+#[test]
+fn labeled_arguments() {
+    let modules = [(
+        "test",
+        r#"
+        pub type Interval {
+          lower: Int,
+          upper: Option(Int),
+          closed: Bool,
+        }
+
+        fn sub(from x: Int, amount y: Int) -> Int {
+          x - y
+        }
+
+        fn width(interval: Interval) -> Int {
+          let Interval { upper, lower, .. } = interval
+          when upper is {
+            Some(upper) -> sub(amount: lower, from: upper)
+            None -> 0
+          }
+        }
+
+        pub fn reordered_fields() {
+          Interval { closed: True, upper: Some(5), lower: 2 } == Interval(2, Some(5), True)
+        }
+
+        pub fn reordered_call() {
+          sub(amount: 1, from: 3) == 2 && sub(3, amount: 1) == 2
+        }
+
+        pub fn reordered_pattern() {
+          width(Interval { upper: Some(5), closed: False, lower: 2 }) == 3
+        }
+
+        pub fn layout(raw: Data) {
+          let expected: Interval = raw
+          Interval { upper: None, closed: False, lower: 7 } == expected
+        }
+        "#,
+    )];
+
+    assert_succeeds(&modules, "reordered_fields");
+    assert_succeeds(&modules, "reordered_call");
+    assert_succeeds(&modules, "reordered_pattern");
+
+    // Fields are laid out in declaration order, whatever the order at the call site.
+    let raw = json!({
+        "constructor": 0,
+        "fields": [
+            { "int": 7 },
+            { "constructor": 1, "fields": [] },
+            { "constructor": 0, "fields": [] },
+        ]
+    });
+
+    assert!(matches!(
+        eval(&modules, "layout", vec![raw]),
+        Ok(Term::Constant(Constant::Unit))
+    ));
+}
+
+#[test]
+fn record_updates() {
+    let modules = [(
+        "test",
+        r#"
+        pub type Datum {
+          owner: Int,
+          counter: Int,
+          deadline: Option(Int),
+          closed: Bool,
+        }
+
+        pub type Handler {
+          run: fn(Int) -> Int,
+          calls: Int,
+        }
+
+        pub fn update_one() {
+          let old = Datum { owner: 1, counter: 1, deadline: None, closed: False }
+          let new = Datum { ..old, counter: old.counter + 1 }
+          new == Datum { owner: 1, counter: 2, deadline: None, closed: False }
+        }
+
+        pub fn update_many() {
+          let old = Datum { owner: 1, counter: 1, deadline: None, closed: False }
+          let new = Datum { ..old, closed: True, owner: 2 }
+          new == Datum { owner: 2, counter: 1, deadline: None, closed: True }
+        }
+
+        pub fn update_scott() {
+          let old = Handler { run: fn(n) { n + 1 }, calls: 0 }
+          let new = Handler { ..old, calls: old.calls + 1 }
+          new.run(new.calls) == 2
+        }
+
+        pub fn update_data(raw: Data) {
+          let old: Datum = raw
+          let new = Datum { ..old, counter: old.counter + 1 }
+          new.counter == 2 && new.closed == old.closed
+        }
+        "#,
+    )];
+
+    assert_succeeds(&modules, "update_one");
+    assert_succeeds(&modules, "update_many");
+    assert_succeeds(&modules, "update_scott");
+
+    // Untouched fields are copied without being decoded: the owner here isn't
+    // even an integer.
+    let raw = json!({
+        "constructor": 0,
+        "fields": [
+            { "list": [] },
+            { "int": 1 },
+            { "constructor": 1, "fields": [] },
+            { "constructor": 1, "fields": [] },
+        ]
+    });
+
+    assert!(matches!(
+        eval(&modules, "update_data", vec![raw]),
+        Ok(Term::Constant(Constant::Unit))
+    ));
+}
+
+#[test]
+fn when_patterns_and_guards() {
+    let modules = [(
+        "test",
+        r#"
+        pub type Action {
+          Mint(Int)
+          Burn { amount: Int, reason: Option(String) }
+          Close
+        }
+
+        fn describe(action: Action, limit: Int) -> Int {
+          when action is {
+            Mint(0) -> 0
+            Mint(n) if n > limit -> 1
+            Mint(_) -> 2
+            Burn { reason: Some("expired"), .. } -> 3
+            Burn { amount, reason: Some(_) as reason } if reason != None && amount <= limit -> 4
+            Burn { .. } | Close -> 5
+          }
+        }
+
+        fn name(n: Int) -> String {
+          when n is {
+            1 -> "one"
+            2 -> "two"
+            _ -> "many"
+          }
+        }
+
+        fn shape(xs: List(Int)) -> Int {
+          when xs is {
+            [] -> 0
+            [1, ..] -> 1
+            [x] if x == 2 -> 2
+            [_, y, ..rest] as all if y > 0 -> sum(all) + sum(rest)
+            _ -> 99
+          }
+        }
+
+        fn sum(xs: List(Int)) -> Int {
+          when xs is {
+            [] -> 0
+            [x, ..rest] -> x + sum(rest)
+          }
+        }
+
+        fn both(a: Option(Int), b: Option(Int)) -> Int {
+          when a, b is {
+            Some(x), Some(y) if x == y -> 0
+            Some(x), Some(_) if { x > 10 || x < 0 } && True -> 1
+            Some(_), _ -> 2
+            None, Some(_) -> 3
+            None, None -> 4
+          }
+        }
+
+        pub fn constructors() {
+          describe(Mint(0), 5) == 0
+            && describe(Mint(6), 5) == 1
+            && describe(Mint(5), 5) == 2
+            && describe(Burn { amount: 1, reason: Some("expired") }, 5) == 3
+            && describe(Burn { amount: 1, reason: Some("lost") }, 5) == 4
+            && describe(Burn { amount: 9, reason: Some("lost") }, 5) == 5
+            && describe(Burn { amount: 1, reason: None }, 5) == 5
+            && describe(Close, 5) == 5
+        }
+
+        pub fn literals() {
+          name(1) == "one" && name(2) == "two" && name(3) == "many"
+        }
+
+        pub fn lists() {
+          shape([]) == 0
+            && shape([1, 2]) == 1
+            && shape([2]) == 2
+            && shape([3, 4, 5]) == 17
+            && shape([3, 0]) == 99
+            && shape([3]) == 99
+        }
+
+        pub fn multiple_subjects() {
+          both(Some(1), Some(1)) == 0
+            && both(Some(11), Some(1)) == 1
+            && both(Some(0 - 1), Some(1)) == 1
+            && both(Some(1), Some(2)) == 2
+            && both(Some(1), None) == 2
+            && both(None, Some(1)) == 3
+            && both(None, None) == 4
+        }
+        "#,
+    )];
+
+    assert_succeeds(&modules, "constructors");
+    assert_succeeds(&modules, "literals");
+    assert_succeeds(&modules, "lists");
+    assert_succeeds(&modules, "multiple_subjects");
+}
+
+#[test]
+fn alternative_patterns_share_their_body() {
+    let modules = [(
+        "test",
+        r#"
+        pub type Shape {
+          Square(Int)
+          Rect(Int, Int)
+          Circle(Int)
+        }
+
+        fn size(shape: Shape) -> String {
+          when shape is {
+            Square(n) | Rect(n, _) | Circle(n) ->
+              if n > 10 {
+                "large"
+              } else {
+                "small"
+              }
+          }
+        }
+
+        pub fn sizes() {
+          size(Square(11)) == "large" && size(Rect(1, 20)) == "small" && size(Circle(20)) == "large"
+        }
+        "#,
+    )];
+
+    assert_succeeds(&modules, "sizes");
+
+    let program = compile(&modules, "sizes", false).to_pretty();
+
+    // Once in the body of `size`, twice in `sizes`.
+    assert_eq!(program.matches("\"large\"").count(), 3);
+}
+
+#[test]
+fn int_literals_and_negation() {
+    let modules = [(
+        "test",
+        r#"
+        fn sign(n: Int) -> Int {
+          when n is {
+            0 -> 0
+            -1 -> -1
+            n if n < -1 -> -2
+            _ -> 1
+          }
+        }
+
+        pub fn literals() {
+          1_000_000 == 1000000 && 0xff == 255 && 0b1010 == 10
+        }
+
+        pub fn negation() {
+          let n = 5
+          0 - 5 == -n && - -n == n && -{ n + 1 } == -6 && 3 - -2 == 5
+        }
+
+        fn quadrant(x: Int, y: Int) -> Int {
+          when x, y is {
+            0, 0 -> 0
+            -1, -1 | 1, 1 -> 1
+            -1, 1 -> 2
+            _, _ -> 3
+          }
+        }
+
+        pub fn negative_patterns() {
+          sign(0) == 0 && sign(-1) == -1 && sign(-0x10) == -2 && sign(1_000) == 1
+        }
+
+        pub fn negative_tuple_patterns() {
+          quadrant(-1, -1) == 1 && quadrant(1, 1) == 1 && quadrant(-1, 1) == 2 && quadrant(1, -1) == 3
+        }
+
+        pub fn subtraction_on_next_line() {
+          let r = 5
+          let total = r
+            -1
+          total == 4
+        }
+
+        fn below(n: Int, limit: Int) -> Bool {
+          when n is {
+            0 -> n < limit - 1
+            -1 -> True
+            _ -> False
+          }
+        }
+
+        pub fn negative_pattern_after_comparison() {
+          below(0, 2) && !below(0, 1) && below(-1, 0) && !below(1, 5)
+        }
+
+        fn minimum(n: Int) -> Bool {
+          when n is {
+            -170141183460469231731687303715884105728 -> True
+            _ -> False
+          }
+        }
+
+        pub fn smallest_literal() {
+          let min = -170141183460469231731687303715884105728
+          min == -170141183460469231731687303715884105727 - 1 && minimum(min) && !minimum(min + 1)
+        }
+        "#,
+    )];
+
+    assert_succeeds(&modules, "negative_pattern_after_comparison");
+    assert_succeeds(&modules, "smallest_literal");
+    assert_succeeds(&modules, "literals");
+    assert_succeeds(&modules, "negation");
+    assert_succeeds(&modules, "negative_patterns");
+    assert_succeeds(&modules, "negative_tuple_patterns");
+    assert_succeeds(&modules, "subtraction_on_next_line");
+}
+
+#[test]
+fn module_constants() {
+    let int = |value: &str| AikenConstant::Int {
+        location: Span::empty(),
+        value: value.to_string(),
+    };
+
+    let reference = |module: Option<&str>, name: &str| AikenConstant::Var {
+        location: Span::empty(),
+        module: module.map(str::to_string),
+        name: name.to_string(),
+        constructor: None,
+        tipo: (),
+    };
+
+    let point = |args: Vec<(Option<&str>, UntypedConstant)>| AikenConstant::Record {
+        location: Span::empty(),
+        module: None,
+        name: "Point".to_string(),
+        args: args
+            .into_iter()
+            .map(|(label, value)| CallArg {
+                label: label.map(str::to_string),
+                location: Span::empty(),
+                value,
+            })
+            .collect(),
+        tag: (),
+        tipo: (),
+        field_map: None,
+    };
+
+    let list = |elements| AikenConstant::List {
+        location: Span::empty(),
+        elements,
+        tipo: (),
+    };
+
+    let modules = [
+        (
+            "shapes",
+            r#"
+            pub type Point {
+              x: Int,
+              y: Int,
+            }
+            "#,
+        ),
+        (
+            "test",
+            r#"
+            use shapes.{Point}
+
+            fn double(n: Int) -> Int {
+              n * 2
+            }
+
+            pub fn lists() {
+              when first_prime is {
+                [2, 3, ..rest] -> rest == [5, 7]
+                _ -> False
+              }
+            }
+
+            pub fn records() {
+              when corners is {
+                [a, b] -> a.x == 0 && a.y == 0 && b == Point { x: 1, y: 1 }
+                _ -> False
+              }
+            }
+
+            pub fn references() {
+              twice(21) == 42 && nothing == None
+            }
+            "#,
+        ),
+    ];
+
+    let constants = [
+        (
+            "shapes",
+            "origin",
+            point(vec![(Some("y"), int("0")), (Some("x"), int("0"))]),
+        ),
+        (
+            "test",
+            "primes",
+            list(vec![int("2"), int("3"), int("5"), int("7")]),
+        ),
+        (
+            "test",
+            "corners",
+            list(vec![
+                reference(Some("shapes"), "origin"),
+                point(vec![(None, int("1")), (None, int("1"))]),
+            ]),
+        ),
+        ("test", "first_prime", reference(None, "primes")),
+        ("test", "twice", reference(None, "double")),
+        (
+            "test",
+            "nothing",
+            AikenConstant::Record {
+                location: Span::empty(),
+                module: None,
+                name: "None".to_string(),
+                args: vec![],
+                tag: (),
+                tipo: (),
+                field_map: None,
+            },
+        ),
+    ];
+
+    for name in ["lists", "records", "references"] {
+        let program = compile_with_constants(&modules, &constants, name, true).unwrap();
+
+        let program: Program<NamedDeBruijn> = program.try_into().unwrap();
+
+        match program.eval().0 {
+            Ok(Term::Constant(Constant::Unit)) => {}
+            result => panic!("{} did not evaluate to True: {:?}", name, result),
+        }
+    }
+}
+
+#[test]
+fn validator_parameters() {
+    let modules = [(
+        "test",
+        r#"
+        pub type Datum {
+          owner: Int,
+          amount: Int,
+        }
+
+        validator(owner: Int, minimum: Int) {
+          fn spend(datum: Datum, redeemer: Int, _ctx: Data) -> Bool {
+            datum.owner == owner && datum.amount + redeemer >= minimum
+          }
+
+          fn mint(redeemer: Int, _ctx: Data) {
+            redeemer < minimum
+          }
+        }
+        "#,
+    )];
+
+    let datum = json!({ "constructor": 0, "fields": [{ "int": 1 }, { "int": 40 }] });
+
+    let ctx = json!({ "constructor": 0, "fields": [] });
+
+    // Parameters come first, then the datum, the redeemer and the context.
+    let spend = |owner: i64, minimum: i64, redeemer: i64| {
+        eval(
+            &modules,
+            "spend",
+            vec![
+                json!({ "int": owner }),
+                json!({ "int": minimum }),
+                datum.clone(),
+                json!({ "int": redeemer }),
+                ctx.clone(),
+            ],
+        )
+    };
+
+    assert!(matches!(
+        spend(1, 50, 10),
+        Ok(Term::Constant(Constant::Unit))
+    ));
+    assert!(spend(1, 50, 9).is_err());
+    assert!(spend(2, 50, 10).is_err());
+
+    let mint = |owner: i64, minimum: i64, redeemer: i64| {
+        eval(
+            &modules,
+            "mint",
+            vec![
+                json!({ "int": owner }),
+                json!({ "int": minimum }),
+                json!({ "int": redeemer }),
+                ctx.clone(),
+            ],
+        )
+    };
+
+    assert!(matches!(
+        mint(1, 50, 49),
+        Ok(Term::Constant(Constant::Unit))
+    ));
+    assert!(mint(1, 50, 50).is_err());
+}
@@ -34,3 +34,41 @@ fn tokens() {
         ]),
     );
 }
+
+#[test]
+fn int_literals() {
+    let code = "1_000_000 0xff_ff 0b1010 -42";
+    let len = code.chars().count();
+
+    let span = |i| Span::new((), i..i + 1);
+
+    let int = |value: &str| Token::Int {
+        value: value.to_string(),
+    };
+
+    assert_eq!(
+        lexer::lexer()
+            .parse(chumsky::Stream::from_iter(
+                span(len),
+                code.chars().enumerate().map(|(i, c)| (c, span(i))),
+            ))
+            .map(|tokens| tokens.into_iter().map(|(tok, _)| tok).collect::<Vec<_>>()),
+        Ok(vec![
+            int("1_000_000"),
+            int("0xff_ff"),
+            int("0b1010"),
+            Token::Minus,
+            int("42"),
+        ]),
+    );
+
+    let code = "0x1_0000_0000_0000_0000_0000_0000_0000_0000";
+    let len = code.chars().count();
+
+    assert!(lexer::lexer()
+        .parse(chumsky::Stream::from_iter(
+            span(len),
+            code.chars().enumerate().map(|(i, c)| (c, span(i))),
+        ))
+        .is_err());
+}
@@ -1,2 +1,3 @@
+mod codegen;
 mod lexer;
 mod parser;
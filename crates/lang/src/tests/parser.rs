@@ -1059,3 +1059,71 @@ fn module() {
         },
     );
 }
+
+#[test]
+fn trace_and_error_are_keywords() {
+    fn keyword(code: &str) -> Vec<(parser::error::ErrorKind, &str)> {
+        parser::module(code, ast::ModuleKind::Lib)
+            .unwrap_err()
+            .into_iter()
+            .map(|error| (error.kind, &code[error.span.start..error.span.end]))
+            .collect()
+    }
+
+    assert_eq!(
+        keyword("use aiken/builtin.{trace}"),
+        [(parser::error::ErrorKind::Keyword("trace"), "trace")]
+    );
+
+    assert_eq!(
+        keyword("fn f(error: Int) {\n  error\n}"),
+        [(parser::error::ErrorKind::Keyword("error"), "error")]
+    );
+
+    assert_eq!(
+        keyword("fn f() {\n  let trace = 1\n  trace\n}"),
+        [(parser::error::ErrorKind::Keyword("trace"), "trace")]
+    );
+
+    // The builtin remains reachable through its module
+    assert!(parser::module(
+        "use aiken/builtin\n\nfn f() {\n  builtin.trace(\"hi\", 1)\n}",
+        ast::ModuleKind::Lib
+    )
+    .is_ok());
+}
+
+#[test]
+fn int_literals_out_of_range() {
+    let out_of_range = |code: &str| {
+        parser::module(code, ast::ModuleKind::Lib)
+            .unwrap_err()
+            .into_iter()
+            .map(|error| error.kind)
+            .collect::<Vec<_>>()
+    };
+
+    // `i128::MIN` can only be written negated
+    assert!(parser::module(
+        "fn f() {\n  -170141183460469231731687303715884105728\n}",
+        ast::ModuleKind::Lib
+    )
+    .is_ok());
+
+    assert_eq!(
+        out_of_range("fn f() {\n  170141183460469231731687303715884105728\n}"),
+        [parser::error::ErrorKind::IntOutOfRange]
+    );
+
+    assert_eq!(
+        out_of_range("fn f() {\n  - -170141183460469231731687303715884105729\n}"),
+        [parser::error::ErrorKind::IntOutOfRange]
+    );
+
+    assert_eq!(
+        out_of_range(
+            "fn f(n) {\n  when n is {\n    170141183460469231731687303715884105728 -> 1\n  }\n}"
+        ),
+        [parser::error::ErrorKind::IntOutOfRange]
+    );
+}
@@ -1,5 +1,6 @@
 use std::{cell::RefCell, collections::HashMap, ops::Deref, sync::Arc};
 
+use serde::{Deserialize, Serialize};
 use uplc::builtins::DefaultFunction;
 
 use crate::{
@@ -19,7 +20,7 @@ mod pattern;
 mod pipe;
 pub mod pretty;
 
-#[derive(Debug, Clone, PartialEq)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub enum Type {
     /// A nominal (named) type such as `Int`, `Float`, or a programmer defined
     /// custom type such as `Person`. The type can take other types as
@@ -123,6 +124,22 @@ impl Type {
         }
     }
 
+    pub fn is_data(&self) -> bool {
+        match self {
+            Self::App { module, name, .. } if "Data" == name && module.is_empty() => true,
+            Self::Var { tipo } => tipo.borrow().is_data(),
+            _ => false,
+        }
+    }
+
+    pub fn is_function(&self) -> bool {
+        match self {
+            Self::Fn { .. } => true,
+            Self::Var { tipo } => tipo.borrow().is_function(),
+            _ => false,
+        }
+    }
+
     /// Get the args for the type if the type is a specific `Type::App`.
     /// Returns None if the type is not a `Type::App` or is an incorrect `Type:App`
     ///
@@ -220,7 +237,7 @@ impl Type {
     }
 }
 
-#[derive(Debug, Clone, PartialEq)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub enum TypeVar {
     /// Unbound is an unbound variable. It is one specific type but we don't
     /// know what yet in the inference process. It has a unique id which can be used to
@@ -286,9 +303,23 @@ impl TypeVar {
             _ => false,
         }
     }
+
+    pub fn is_data(&self) -> bool {
+        match self {
+            Self::Link { tipo } => tipo.is_data(),
+            _ => false,
+        }
+    }
+
+    pub fn is_function(&self) -> bool {
+        match self {
+            Self::Link { tipo } => tipo.is_function(),
+            _ => false,
+        }
+    }
 }
 
-#[derive(Debug, Clone, PartialEq)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct ValueConstructor {
     pub public: bool,
     pub variant: ValueConstructorVariant,
@@ -337,7 +368,7 @@ impl ValueConstructor {
     }
 }
 
-#[derive(Debug, Clone, PartialEq)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub enum ValueConstructorVariant {
     /// A locally defined variable or function parameter
     LocalVariable { location: Span },
@@ -434,7 +465,7 @@ impl ValueConstructorVariant {
     }
 }
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct TypeInfo {
     pub name: String,
     pub kind: ModuleKind,
@@ -445,7 +476,7 @@ pub struct TypeInfo {
     pub accessors: HashMap<String, AccessorsMap>,
 }
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct TypeConstructor {
     pub public: bool,
     pub location: Span,
@@ -454,14 +485,14 @@ pub struct TypeConstructor {
     pub tipo: Arc<Type>,
 }
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct AccessorsMap {
     pub public: bool,
     pub tipo: Arc<Type>,
     pub accessors: HashMap<String, RecordAccessor>,
 }
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct RecordAccessor {
     // TODO: smaller int. Doesn't need to be this big
     pub index: u64,
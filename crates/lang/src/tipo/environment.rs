@@ -255,7 +255,8 @@ impl<'a> Environment<'a> {
             definition @ (Definition::TypeAlias { .. }
             | Definition::DataType { .. }
             | Definition::Use { .. }
-            | Definition::ModuleConstant { .. }) => definition,
+            | Definition::ModuleConstant { .. }
+            | Definition::Validator { .. }) => definition,
         }
     }
 
@@ -910,7 +911,10 @@ impl<'a> Environment<'a> {
                 }
             }
 
-            Definition::Fn { .. } | Definition::Use { .. } | Definition::ModuleConstant { .. } => {}
+            Definition::Fn { .. }
+            | Definition::Use { .. }
+            | Definition::ModuleConstant { .. }
+            | Definition::Validator { .. } => {}
         }
 
         Ok(())
@@ -1092,7 +1096,10 @@ impl<'a> Environment<'a> {
                 assert_unique_const_name(names, name, location)?;
             }
 
-            Definition::Use { .. } | Definition::TypeAlias { .. } => {}
+            // Handlers of validators are not values, they can't be referred to.
+            Definition::Use { .. }
+            | Definition::TypeAlias { .. }
+            | Definition::Validator { .. } => {}
         }
         Ok(())
     }
@@ -234,6 +234,16 @@ impl<'a, 'b> ExprTyper<'a, 'b> {
                 ..
             } => Ok(self.infer_todo(location, kind, label)),
 
+            UntypedExpr::ErrorTerm { location, label } => {
+                Ok(self.infer_error_term(location, label))
+            }
+
+            UntypedExpr::Trace {
+                location,
+                then,
+                text,
+            } => self.infer_trace(*then, location, text),
+
             UntypedExpr::Var { location, name, .. } => self.infer_var(name, location),
 
             UntypedExpr::Int {
@@ -341,6 +351,8 @@ impl<'a, 'b> ExprTyper<'a, 'b> {
             } => self.infer_record_update(*constructor, spread, args, location),
 
             UntypedExpr::Negate { location, value } => self.infer_negate(location, value),
+
+            UntypedExpr::NegateInt { location, value } => self.infer_negate_int(location, value),
         }
     }
 
@@ -544,6 +556,21 @@ impl<'a, 'b> ExprTyper<'a, 'b> {
         })
     }
 
+    fn infer_negate_int(
+        &mut self,
+        location: Span,
+        value: Box<UntypedExpr>,
+    ) -> Result<TypedExpr, Error> {
+        let value = self.infer(*value)?;
+
+        self.unify(int(), value.tipo(), value.location())?;
+
+        Ok(TypedExpr::NegateInt {
+            location,
+            value: Box::new(value),
+        })
+    }
+
     fn infer_field_access(
         &mut self,
         container: UntypedExpr,
@@ -779,11 +806,15 @@ impl<'a, 'b> ExprTyper<'a, 'b> {
                 .type_from_annotation(ann)
                 .map(|t| self.instantiate(t, &mut HashMap::new()))?;
 
-            self.unify(
-                ann_typ.clone(),
-                value_typ.clone(),
-                value.type_defining_location(),
-            )?;
+            // `assert` and `check` may cast `Data` into any type, the value
+            // is validated against the annotation when the script runs.
+            if kind == AssignmentKind::Let || !value_typ.is_data() {
+                self.unify(
+                    ann_typ.clone(),
+                    value_typ.clone(),
+                    value.type_defining_location(),
+                )?;
+            }
 
             value_typ = ann_typ.clone();
 
@@ -804,8 +835,9 @@ impl<'a, 'b> ExprTyper<'a, 'b> {
 
         // We currently only do limited exhaustiveness checking of custom types
         // at the top level of patterns.
-        // Do not perform exhaustiveness checking if user explicitly used `assert`.
-        if kind != AssignmentKind::Assert {
+        // Do not perform exhaustiveness checking if user explicitly used `assert`
+        // or `check`, both are refutable.
+        if kind == AssignmentKind::Let {
             if let Err(unmatched) = self.environment.check_exhaustiveness(
                 vec![pattern.clone()],
                 collapse_links(value_typ.clone()),
@@ -1665,6 +1697,32 @@ impl<'a, 'b> ExprTyper<'a, 'b> {
         }
     }
 
+    fn infer_error_term(&mut self, location: Span, label: Option<String>) -> TypedExpr {
+        let tipo = self.new_unbound_var();
+
+        TypedExpr::ErrorTerm {
+            location,
+            label,
+            tipo,
+        }
+    }
+
+    fn infer_trace(
+        &mut self,
+        then: UntypedExpr,
+        location: Span,
+        text: String,
+    ) -> Result<TypedExpr, Error> {
+        let then = self.infer(then)?;
+
+        Ok(TypedExpr::Trace {
+            location,
+            tipo: then.tipo(),
+            then: Box::new(then),
+            text,
+        })
+    }
+
     fn infer_todo(&mut self, location: Span, kind: TodoKind, label: Option<String>) -> TypedExpr {
         let tipo = self.new_unbound_var();
 
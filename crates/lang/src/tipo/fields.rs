@@ -1,11 +1,12 @@
 use std::collections::{HashMap, HashSet};
 
 use itertools::Itertools;
+use serde::{Deserialize, Serialize};
 
 use super::error::Error;
 use crate::ast::{CallArg, Span};
 
-#[derive(Debug, Clone, PartialEq, Eq)]
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
 pub struct FieldMap {
     pub arity: usize,
     pub fields: HashMap<String, usize>,
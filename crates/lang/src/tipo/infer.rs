@@ -3,8 +3,8 @@ use std::collections::HashMap;
 use crate::{
     ast::{
         DataType, Definition, Function, Layer, ModuleConstant, ModuleKind, RecordConstructor,
-        RecordConstructorArg, TypeAlias, TypedDefinition, TypedModule, UntypedDefinition,
-        UntypedModule, Use,
+        RecordConstructorArg, TypeAlias, TypedDefinition, TypedFunction, TypedModule,
+        UntypedDefinition, UntypedFunction, UntypedModule, Use, Validator,
     },
     builtins::function,
     parser::token::Token,
@@ -70,7 +70,8 @@ impl UntypedModule {
                 Definition::Fn { .. }
                 | Definition::TypeAlias { .. }
                 | Definition::DataType { .. }
-                | Definition::Use { .. } => not_consts.push(def),
+                | Definition::Use { .. }
+                | Definition::Validator { .. } => not_consts.push(def),
             }
         }
 
@@ -233,6 +234,61 @@ fn infer_definition(
             }))
         }
 
+        Definition::Validator(Validator {
+            doc,
+            location,
+            params,
+            handlers,
+            end_position,
+        }) => {
+            let mut hydrator = Hydrator::new();
+
+            hydrator.permit_holes(true);
+
+            // The parameters are in scope of every handler, and only reported
+            // as unused when none of them refers to it.
+            environment.in_new_scope(|environment| {
+                let params = params
+                    .into_iter()
+                    .map(|param| {
+                        let tipo =
+                            hydrator.type_from_option_annotation(&param.annotation, environment)?;
+
+                        if let Some(name) = param.arg_name.get_variable_name() {
+                            environment.insert_variable(
+                                name.to_string(),
+                                ValueConstructorVariant::LocalVariable {
+                                    location: param.location,
+                                },
+                                tipo.clone(),
+                            );
+
+                            environment.init_usage(
+                                name.to_string(),
+                                EntityKind::Variable,
+                                param.location,
+                            );
+                        }
+
+                        Ok(param.set_type(tipo))
+                    })
+                    .collect::<Result<_, Error>>()?;
+
+                let handlers = handlers
+                    .into_iter()
+                    .map(|handler| infer_handler(handler, environment))
+                    .collect::<Result<_, Error>>()?;
+
+                Ok(Definition::Validator(Validator {
+                    doc,
+                    location,
+                    params,
+                    handlers,
+                    end_position,
+                }))
+            })
+        }
+
         Definition::TypeAlias(TypeAlias {
             doc,
             location,
@@ -454,13 +510,67 @@ fn str_to_keyword(word: &str) -> Option<Token> {
         "const" => Some(Token::Const),
         "fn" => Some(Token::Fn),
         "if" => Some(Token::If),
+        "error" => Some(Token::ErrorTerm),
         "use" => Some(Token::Use),
         "let" => Some(Token::Let),
         "opaque" => Some(Token::Opaque),
         "pub" => Some(Token::Pub),
         "todo" => Some(Token::Todo),
+        "trace" => Some(Token::Trace),
         "try" => Some(Token::Try),
         "type" => Some(Token::Type),
         _ => None,
     }
 }
+
+fn infer_handler(
+    handler: UntypedFunction,
+    environment: &mut Environment<'_>,
+) -> Result<TypedFunction, Error> {
+    let Function {
+        doc,
+        location,
+        name,
+        public,
+        arguments: args,
+        body,
+        return_annotation,
+        end_position,
+        ..
+    } = handler;
+
+    let mut hydrator = Hydrator::new();
+
+    hydrator.permit_holes(true);
+
+    let args = args
+        .into_iter()
+        .map(|arg| {
+            let tipo = hydrator.type_from_option_annotation(&arg.annotation, environment)?;
+
+            Ok(arg.set_type(tipo))
+        })
+        .collect::<Result<_, Error>>()?;
+
+    let return_type = hydrator.type_from_option_annotation(&return_annotation, environment)?;
+
+    let (args, body) = environment.in_new_scope(|environment| {
+        let mut expr_typer = ExprTyper::new(environment);
+
+        expr_typer.hydrator = hydrator;
+
+        expr_typer.infer_fn_with_known_types(args, body, Some(return_type))
+    })?;
+
+    Ok(Function {
+        doc,
+        location,
+        name,
+        public,
+        arguments: args,
+        return_annotation,
+        return_type: body.tipo(),
+        body,
+        end_position,
+    })
+}
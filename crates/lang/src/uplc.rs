@@ -1,93 +1,37 @@
-use std::{cmp::Ordering, collections::HashMap, rc::Rc, sync::Arc};
+use std::{
+    collections::{BTreeSet, HashMap},
+    rc::Rc,
+    sync::Arc,
+};
 
 use indexmap::IndexMap;
+use petgraph::{algo::tarjan_scc, graph::NodeIndex, Graph};
+use strum::IntoEnumIterator;
 
 use uplc::{
     ast::{Constant, Name, Program, Term, Type as UplcType, Unique},
     builtins::DefaultFunction,
     parser::interner::Interner,
-    BigInt, PlutusData,
 };
 
 use crate::{
-    ast::{AssignmentKind, BinOp, DataType, Function, Pattern, Span, TypedArg, TypedPattern},
+    ast::{
+        int_value, AssignmentKind, BinOp, CallArg, ClauseGuard, Constant as AikenConstant,
+        DataType, Function, Span, TypedArg, TypedClauseGuard, TypedRecordUpdateArg,
+    },
+    builtins::function,
     expr::TypedExpr,
-    tipo::{self, ModuleValueConstructor, Type, ValueConstructor, ValueConstructorVariant},
+    tipo::{
+        self, ModuleValueConstructor, PatternConstructor, Type, TypeVar, ValueConstructor,
+        ValueConstructorVariant,
+    },
 };
 
-#[derive(Debug, Clone, PartialEq, Eq, Hash)]
-pub struct ScopeLevels {
-    scope_tracker: Vec<i32>,
-    field_depth: i32,
-}
-
-impl ScopeLevels {
-    pub fn new() -> Self {
-        ScopeLevels {
-            scope_tracker: vec![0],
-            field_depth: 0,
-        }
-    }
-
-    pub fn is_less_than(&self, other: &ScopeLevels, include_depth: bool) -> bool {
-        if self.scope_tracker.is_empty() && !other.scope_tracker.is_empty() {
-            return true;
-        } else if other.scope_tracker.is_empty() {
-            return false;
-        }
-
-        let mut result = self.scope_tracker.len() < other.scope_tracker.len()
-            || (self.scope_tracker.len() == other.scope_tracker.len()
-                && include_depth
-                && self.field_depth < other.field_depth);
-
-        for (scope_self, scope_other) in self.scope_tracker.iter().zip(other.scope_tracker.iter()) {
-            match scope_self.cmp(scope_other) {
-                std::cmp::Ordering::Less => {
-                    result = true;
-                    break;
-                }
-                std::cmp::Ordering::Equal => {}
-                std::cmp::Ordering::Greater => {
-                    result = false;
-                    break;
-                }
-            }
-        }
-        result
-    }
-
-    pub fn scope_increment_sequence(&self, inc: i32) -> ScopeLevels {
-        let mut new_scope = self.clone();
-        *new_scope.scope_tracker.last_mut().unwrap() += inc;
-        new_scope.scope_tracker.push(0);
-        new_scope
-    }
-
-    pub fn scope_increment(&self, inc: i32) -> ScopeLevels {
-        let mut new_scope = self.clone();
-        *new_scope.scope_tracker.last_mut().unwrap() += inc;
-        new_scope
-    }
-
-    pub fn depth_increment(&self, inc: i32) -> ScopeLevels {
-        let mut new_scope = self.clone();
-        new_scope.field_depth += inc;
-        new_scope
-    }
-}
+pub mod error;
 
-impl Default for ScopeLevels {
-    fn default() -> Self {
-        Self::new()
-    }
-}
+use error::Error;
 
-#[derive(Clone, Eq, PartialEq, Hash)]
-pub struct ConstrFieldKey {
-    pub local_var: String,
-    pub field_name: String,
-}
+type TypedPattern = crate::ast::Pattern<PatternConstructor, Arc<Type>>;
 
 #[derive(Clone, Debug, Eq, PartialEq, Hash)]
 pub struct DataTypeKey {
@@ -95,148 +39,154 @@ pub struct DataTypeKey {
     pub defined_type: String,
 }
 
-pub type ConstrUsageKey = String;
-
 #[derive(Clone, Debug, Eq, PartialEq, Hash)]
 pub struct FunctionAccessKey {
     pub module_name: String,
     pub function_name: String,
 }
 
-#[derive(Clone)]
-pub struct ConstrConversionInfo {
-    local_var: String,
-    field: Option<String>,
-    scope: ScopeLevels,
-    index: Option<u64>,
-    returning_type: String,
+/// How the values of an Aiken type are laid out in UPLC.
+///
+/// Prelude scalars map onto their builtin constants, lists are builtin lists
+/// of `Data` and everything else (custom types, `Option`, `Data` and type
+/// variables left unresolved after monomorphisation) is `Data`. Values are
+/// converted to and from `Data` when they are stored in, or read from, a list
+/// or a record field.
+///
+/// Values holding functions can't be converted to `Data`, so the types
+/// holding them, lists included, are Scott encoded instead.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Repr {
+    Integer,
+    ByteString,
+    String,
+    Bool,
+    Unit,
+    List,
+    Data,
+    Function,
+    /// Lists and custom types holding functions, as functions taking one
+    /// handler per constructor.
+    Scott,
 }
 
-#[derive(Clone)]
-pub struct ScopedExpr {
-    scope: ScopeLevels,
-    expr: TypedExpr,
+type TypedFunction = Function<Arc<tipo::Type>, TypedExpr>;
+
+/// What each type variable of a generic function is instantiated to.
+type TypeMapping = HashMap<u64, Arc<Type>>;
+
+/// A group of mutually recursive functions being compiled together.
+///
+/// The knot is a single function taking itself and the index of a member, so
+/// members refer to one another as `knot knot index`.
+struct Knot<'a> {
+    name: String,
+    group: usize,
+    /// Compiled bodies by specialised name, `None` until compiled. The
+    /// position of an entry is the index of that member.
+    members: IndexMap<String, Option<Term<Name>>>,
+    pending: Vec<(String, String, &'a TypedFunction, TypeMapping)>,
 }
 
+/// Compiles a validator and every module function it reaches into a single
+/// UPLC program.
+///
+/// Generic functions are monomorphised: each distinct instantiation of a
+/// function's type variables gets its own specialised definition, so the body
+/// is always compiled against concrete types.
 pub struct CodeGenerator<'a> {
-    uplc_function_holder: Vec<(String, Term<Name>)>,
-    uplc_function_holder_lookup: IndexMap<FunctionAccessKey, ScopeLevels>,
-    uplc_data_holder_lookup: IndexMap<ConstrFieldKey, ScopedExpr>,
-    uplc_data_constr_lookup: IndexMap<DataTypeKey, ScopeLevels>,
-    uplc_data_usage_holder_lookup: IndexMap<ConstrUsageKey, ScopeLevels>,
-    function_recurse_lookup: IndexMap<FunctionAccessKey, usize>,
-    functions: &'a HashMap<FunctionAccessKey, &'a Function<Arc<tipo::Type>, TypedExpr>>,
-    // type_aliases: &'a HashMap<(String, String), &'a TypeAlias<Arc<tipo::Type>>>,
+    functions: &'a HashMap<FunctionAccessKey, &'a TypedFunction>,
     data_types: &'a HashMap<DataTypeKey, &'a DataType<Arc<tipo::Type>>>,
-    // imports: &'a HashMap<(String, String), &'a Use<String>>,
-    // constants: &'a HashMap<(String, String), &'a ModuleConstant<Arc<tipo::Type>, String>>,
+    /// Mutually recursive group of each function that belongs to one, computed
+    /// on first use.
+    recursion_groups: Option<HashMap<FunctionAccessKey, usize>>,
+    /// Mutually recursive groups being compiled, innermost last.
+    knots: Vec<Knot<'a>>,
+    /// Specialised module functions, in the order they must be bound.
+    definitions: IndexMap<String, Term<Name>>,
+    /// Functions whose body is being compiled, innermost last.
+    in_progress: Vec<String>,
+    /// Functions that referred to themselves while being compiled.
+    recursive: Vec<String>,
+    /// Instantiation of the type variables of the function being compiled.
+    type_mapping: HashMap<u64, Arc<Type>>,
+    /// Whether `trace` and the messages of `error` survive compilation.
+    keep_traces: bool,
+    /// The module of the code being compiled.
+    module: String,
+    errors: Vec<Error>,
+    id: u64,
 }
 
 impl<'a> CodeGenerator<'a> {
     pub fn new(
-        functions: &'a HashMap<FunctionAccessKey, &'a Function<Arc<tipo::Type>, TypedExpr>>,
+        functions: &'a HashMap<FunctionAccessKey, &'a TypedFunction>,
         // type_aliases: &'a HashMap<(String, String), &'a TypeAlias<Arc<tipo::Type>>>,
         data_types: &'a HashMap<DataTypeKey, &'a DataType<Arc<tipo::Type>>>,
         // imports: &'a HashMap<(String, String), &'a Use<String>>,
         // constants: &'a HashMap<(String, String), &'a ModuleConstant<Arc<tipo::Type>, String>>,
+        keep_traces: bool,
     ) -> Self {
         CodeGenerator {
-            uplc_function_holder: Vec::new(),
-            uplc_function_holder_lookup: IndexMap::new(),
-            uplc_data_holder_lookup: IndexMap::new(),
-            uplc_data_constr_lookup: IndexMap::new(),
-            uplc_data_usage_holder_lookup: IndexMap::new(),
-            function_recurse_lookup: IndexMap::new(),
             functions,
-            // type_aliases,
             data_types,
-            // imports,
-            // constants,
+            recursion_groups: None,
+            knots: Vec::new(),
+            definitions: IndexMap::new(),
+            in_progress: Vec::new(),
+            recursive: Vec::new(),
+            type_mapping: HashMap::new(),
+            keep_traces,
+            module: String::new(),
+            errors: Vec::new(),
+            id: 0,
         }
     }
 
-    pub fn generate(&mut self, body: TypedExpr, arguments: Vec<TypedArg>) -> Program<Name> {
-        self.recurse_scope_level(&body, ScopeLevels::new());
+    /// Compile a validator of `module`, made of `body` and its `arguments`.
+    pub fn generate(
+        &mut self,
+        module: &str,
+        body: TypedExpr,
+        arguments: Vec<TypedArg>,
+    ) -> Result<Program<Name>, Vec<Error>> {
+        self.module = module.to_string();
 
-        self.uplc_function_holder_lookup
-            .sort_by(|_key1, value1, _key2, value2| {
-                if value1.is_less_than(value2, true) {
-                    Ordering::Less
-                } else if value2.is_less_than(value1, true) {
-                    Ordering::Greater
-                } else {
-                    Ordering::Equal
-                }
-            });
+        let mut term = self.build(&body);
 
-        let mut term = self.recurse_code_gen(&body, ScopeLevels::new());
-
-        // Apply constr exposer to top level.
-        term = Term::Apply {
-            function: Term::Lambda {
-                parameter_name: Name {
-                    text: "constr_fields_exposer".to_string(),
-                    unique: 0.into(),
-                },
-                body: term.into(),
-            }
-            .into(),
-            argument: Term::Lambda {
-                parameter_name: Name {
-                    text: "constr_var".to_string(),
-                    unique: 0.into(),
-                },
-                body: Term::Apply {
-                    function: Term::Builtin(DefaultFunction::UnListData).into(),
-                    argument: Term::Apply {
-                        function: Term::Force(
-                            Term::Force(Term::Builtin(DefaultFunction::SndPair).into()).into(),
-                        )
-                        .into(),
-                        argument: Term::Apply {
-                            function: Term::Builtin(DefaultFunction::UnConstrData).into(),
-                            argument: Term::Var(Name {
-                                text: "constr_var".to_string(),
-                                unique: 0.into(),
-                            })
-                            .into(),
-                        }
-                        .into(),
-                    }
-                    .into(),
-                }
-                .into(),
-            }
-            .into(),
-        };
+        term = if_then_else(term, Term::Constant(Constant::Unit), Term::Error);
 
-        term = self.add_arg_getter(term);
+        // Scripts receive their arguments as data.
+        for arg in arguments.iter().rev() {
+            if let Some(name) = arg.arg_name.get_variable_name() {
+                match self.repr(&arg.tipo) {
+                    Repr::Data => {}
+                    Repr::Function | Repr::Scott => {
+                        self.errors.push(Error::FunctionFromData {
+                            module: self.module.clone(),
+                            location: arg.location,
+                            tipo: self.resolve(&arg.tipo),
+                        });
+                    }
+                    _ => {
+                        let value = self.unwrap_data(var(name), &arg.tipo);
 
-        term = Term::Force(
-            Term::Apply {
-                function: Term::Apply {
-                    function: Term::Apply {
-                        function: Term::Force(Term::Builtin(DefaultFunction::IfThenElse).into())
-                            .into(),
-                        argument: term.into(),
+                        term = apply(lambda(name, term), value);
                     }
-                    .into(),
-                    argument: Term::Delay(Term::Constant(Constant::Unit).into()).into(),
                 }
-                .into(),
-                argument: Term::Delay(Term::Error.into()).into(),
             }
-            .into(),
-        );
+        }
+
+        if !self.errors.is_empty() {
+            return Err(std::mem::take(&mut self.errors));
+        }
+
+        for (name, definition) in self.definitions.iter().rev() {
+            term = apply(lambda(name, term), definition.clone());
+        }
 
         for arg in arguments.iter().rev() {
-            term = Term::Lambda {
-                parameter_name: uplc::ast::Name {
-                    text: arg.arg_name.get_variable_name().unwrap_or("_").to_string(),
-                    unique: Unique::new(0),
-                },
-                body: Rc::new(term),
-            }
+            term = lambda(arg.arg_name.get_variable_name().unwrap_or("_"), term);
         }
 
         let mut program = Program {
@@ -248,2753 +198,2289 @@ impl<'a> CodeGenerator<'a> {
 
         interner.program(&mut program);
 
-        program
+        Ok(program)
     }
 
-    pub(crate) fn recurse_scope_level(&mut self, body: &TypedExpr, scope_level: ScopeLevels) {
+    fn build(&mut self, body: &TypedExpr) -> Term<Name> {
         match body {
-            TypedExpr::Int { .. } => {}
-            TypedExpr::String { .. } => {}
-            TypedExpr::ByteArray { .. } => {}
+            TypedExpr::Int { value, .. } => build_int(value),
+            TypedExpr::String { value, .. } => Term::Constant(Constant::String(value.clone())),
+            TypedExpr::ByteArray { bytes, .. } => {
+                Term::Constant(Constant::ByteString(bytes.clone()))
+            }
             TypedExpr::Sequence { expressions, .. } | TypedExpr::Pipeline { expressions, .. } => {
-                // let mut terms = Vec::new();
-                for (i, exp) in expressions.iter().enumerate().rev() {
-                    self.recurse_scope_level(
-                        exp,
-                        scope_level.scope_increment_sequence(i as i32 + 1),
-                    );
-                }
+                self.build_sequence(expressions)
             }
+            TypedExpr::Var {
+                constructor, name, ..
+            } => self.build_var(name, constructor),
+            TypedExpr::Fn { args, body, .. } => {
+                let body = self.build(body);
 
-            TypedExpr::Var { constructor, .. } => {
-                match constructor.variant.clone() {
-                    ValueConstructorVariant::LocalVariable { .. } => {}
-                    ValueConstructorVariant::ModuleConstant { .. } => todo!(),
-                    ValueConstructorVariant::ModuleFn { name, module, .. } => {
-                        if self
-                            .uplc_function_holder_lookup
-                            .get(&FunctionAccessKey {
-                                module_name: module.to_string(),
-                                function_name: name.to_string(),
-                            })
-                            .is_none()
-                        {
-                            let func_def = self
-                                .functions
-                                .get(&FunctionAccessKey {
-                                    module_name: module.to_string(),
-                                    function_name: name.to_string(),
-                                })
-                                .unwrap();
-
-                            self.uplc_function_holder_lookup.insert(
-                                FunctionAccessKey {
-                                    module_name: module,
-                                    function_name: name,
-                                },
-                                scope_level.clone(),
-                            );
-
-                            self.recurse_scope_level(&func_def.body, scope_level);
-                        } else if scope_level.is_less_than(
-                            self.uplc_function_holder_lookup
-                                .get(&FunctionAccessKey {
-                                    module_name: module.to_string(),
-                                    function_name: name.to_string(),
-                                })
-                                .unwrap(),
-                            false,
-                        ) {
-                            self.uplc_function_holder_lookup.insert(
-                                FunctionAccessKey {
-                                    module_name: module,
-                                    function_name: name,
-                                },
-                                scope_level,
-                            );
-                        }
-                    }
-                    ValueConstructorVariant::Record { .. } => {
-                        match &*constructor.tipo {
-                            Type::App { .. } => {}
-                            Type::Fn { .. } | Type::Var { .. } => {}
-                        };
-                    }
-                };
-            }
-            TypedExpr::Fn { .. } => todo!(),
-            TypedExpr::List { elements, tail, .. } => {
-                for element in elements {
-                    self.recurse_scope_level(element, scope_level.clone());
-                }
-                if let Some(tail_element) = tail {
-                    self.recurse_scope_level(tail_element, scope_level)
-                }
+                function_lambda(args, body)
             }
-            TypedExpr::Call { fun, args, .. } => {
-                self.recurse_scope_level(fun, scope_level.scope_increment(1));
+            TypedExpr::List {
+                elements,
+                tail,
+                tipo,
+                ..
+            } => {
+                let elements = elements
+                    .iter()
+                    .map(|element| (self.build(element), element.tipo()))
+                    .collect();
 
-                for (index, arg) in args.iter().enumerate() {
-                    self.recurse_scope_level(
-                        &arg.value,
-                        scope_level.scope_increment(index as i32 + 2),
-                    );
-                }
-            }
-            TypedExpr::BinOp { left, right, .. } => {
-                self.recurse_scope_level(left, scope_level.clone());
-                self.recurse_scope_level(right, scope_level);
+                let tail = tail.as_ref().map(|tail| self.build(tail));
+
+                self.build_list(elements, tail, tipo)
             }
-            TypedExpr::Assignment { value, pattern, .. } => self.recurse_scope_level_pattern(
-                pattern,
+            TypedExpr::Call {
+                fun, args, tipo, ..
+            } => self.build_call(fun, args, tipo),
+            TypedExpr::BinOp {
+                name,
+                left,
+                right,
+                location,
+                ..
+            } => self.build_binop(name, left, right, *location),
+            TypedExpr::Assignment {
                 value,
-                scope_level.scope_increment(1),
-                &[],
-            ),
-            TypedExpr::Try { .. } => todo!(),
+                pattern,
+                kind,
+                tipo,
+                location,
+            } => self.build_assignment(value, pattern, (*kind, *location), tipo, None),
+            TypedExpr::Try { location, .. } => {
+                self.errors.push(Error::Try {
+                    module: self.module.clone(),
+                    location: *location,
+                });
+
+                Term::Error
+            }
             TypedExpr::When {
                 subjects, clauses, ..
             } => {
-                for clause in clauses {
-                    for pattern in clause.pattern.iter() {
-                        self.recurse_scope_level_pattern(
-                            pattern,
-                            &clause.then,
-                            scope_level.scope_increment_sequence(1),
-                            subjects,
-                        );
+                let subject_names = subjects
+                    .iter()
+                    .map(|_| self.fresh("subject"))
+                    .collect::<Vec<_>>();
+
+                let mut term = Term::Error;
+
+                for clause in clauses.iter().rev() {
+                    let alternatives = std::iter::once(&clause.pattern)
+                        .chain(clause.alternative_patterns.iter())
+                        .collect::<Vec<_>>();
+
+                    let then = self.build(&clause.then);
+
+                    // Alternatives share the body of their clause, bound once
+                    // as a function of the variables every alternative binds.
+                    let shared = if alternatives.len() > 1 {
+                        let mut variables = BTreeSet::new();
+
+                        for pattern in &clause.pattern {
+                            pattern_variables(pattern, &mut variables);
+                        }
+
+                        Some((self.fresh("body"), variables))
+                    } else {
+                        None
+                    };
+
+                    for patterns in alternatives.into_iter().rev() {
+                        let next = self.fresh("clause");
+                        let failure = Term::Force(var(&next).into());
+
+                        let mut body = match &shared {
+                            Some((body_name, variables)) if variables.is_empty() => {
+                                Term::Force(var(body_name).into())
+                            }
+                            Some((body_name, variables)) => variables
+                                .iter()
+                                .fold(var(body_name), |body, variable| apply(body, var(variable))),
+                            None => then.clone(),
+                        };
+
+                        // A failing guard falls through to the next clause,
+                        // like a pattern that doesn't match.
+                        if let Some(guard) = &clause.guard {
+                            let guard = self.build_guard(guard);
+
+                            body = if_then_else(guard, body, failure.clone());
+                        }
+
+                        for ((pattern, subject), subject_name) in
+                            patterns.iter().zip(subjects).zip(&subject_names).rev()
+                        {
+                            body = self.build_pattern(
+                                pattern,
+                                subject_name,
+                                &subject.tipo(),
+                                body,
+                                &failure,
+                            );
+                        }
+
+                        term = apply(lambda(&next, body), Term::Delay(term.into()));
+                    }
+
+                    if let Some((body_name, variables)) = shared {
+                        let body = if variables.is_empty() {
+                            Term::Delay(then.into())
+                        } else {
+                            variables
+                                .iter()
+                                .rev()
+                                .fold(then, |body, variable| lambda(variable, body))
+                        };
+
+                        term = apply(lambda(&body_name, term), body);
                     }
                 }
-                for subject in subjects {
-                    self.recurse_scope_level(subject, scope_level.clone());
+
+                for (subject, subject_name) in subjects.iter().zip(&subject_names).rev() {
+                    let value = self.build(subject);
+
+                    term = apply(lambda(subject_name, term), value);
                 }
+
+                term
             }
-            // if statements increase scope due to branching.
             TypedExpr::If {
                 branches,
                 final_else,
                 ..
             } => {
-                self.recurse_scope_level(final_else, scope_level.scope_increment_sequence(1));
-
-                for branch in branches {
-                    // Need some scoping count to potentially replace condition with var since we should assume a condition
-                    // may be repeated 3 + times or be large enough series of binops to warrant var replacement
-                    self.recurse_scope_level(
-                        &branch.condition,
-                        scope_level.scope_increment_sequence(1), // Since this happens before branching. Maybe not increase scope level
-                    );
+                let mut term = self.build(final_else);
 
-                    self.recurse_scope_level(&branch.body, scope_level.scope_increment_sequence(1));
+                for branch in branches.iter().rev() {
+                    let condition = self.build(&branch.condition);
+                    let body = self.build(&branch.body);
+
+                    term = if_then_else(condition, body, term);
                 }
+
+                term
             }
-            expr @ TypedExpr::RecordAccess { label, record, .. } => {
-                self.recurse_scope_level(record, scope_level.clone());
-                let mut is_var = false;
-                let mut current_var_name = String::new();
-                let mut current_record = *record.clone();
-                let mut current_scope = scope_level;
-                while !is_var {
-                    match current_record.clone() {
-                        TypedExpr::Var {
-                            constructor, name, ..
-                        } => match (
-                            constructor.clone().variant.clone(),
-                            (*constructor.tipo).clone(),
-                        ) {
-                            (ValueConstructorVariant::LocalVariable { .. }, Type::App { .. }) => {
-                                current_var_name = if current_var_name.is_empty() {
-                                    name
-                                } else {
-                                    format!("{name}_field_{current_var_name}")
-                                };
-                                is_var = true;
-                            }
-                            _ => todo!(),
-                        },
-                        TypedExpr::RecordAccess { label, record, .. } => {
-                            current_var_name = if current_var_name.is_empty() {
-                                label.to_string()
-                            } else {
-                                format!("{label}_field_{current_var_name}")
-                            };
-                            current_record = *record.clone();
-                            current_scope = current_scope.depth_increment(1);
-                        }
-                        _ => {}
-                    }
-                }
+            TypedExpr::RecordAccess {
+                tipo,
+                index,
+                record,
+                ..
+            } => {
+                let record_tipo = record.tipo();
+                let record = self.build(record);
 
-                if let Some(val) = self.uplc_data_holder_lookup.get(&ConstrFieldKey {
-                    local_var: current_var_name.clone(),
-                    field_name: label.clone(),
-                }) {
-                    if current_scope.is_less_than(&val.scope, false) {
-                        self.uplc_data_holder_lookup.insert(
-                            ConstrFieldKey {
-                                local_var: current_var_name.clone(),
-                                field_name: label.clone(),
-                            },
-                            ScopedExpr {
-                                scope: current_scope.clone(),
-                                expr: expr.clone(),
-                            },
-                        );
+                self.build_field(record, &record_tipo, 0, *index as usize, tipo)
+            }
+            TypedExpr::ModuleSelect {
+                tipo,
+                module_name,
+                constructor,
+                ..
+            } => match constructor {
+                ModuleValueConstructor::Record {
+                    name,
+                    arity,
+                    tipo: constructor_tipo,
+                    ..
+                } => self.build_record(name, *arity, constructor_tipo),
+                ModuleValueConstructor::Fn { module, name, .. } => {
+                    if module_name == "aiken/builtin" {
+                        self.build_builtin(builtin_by_name(name), tipo)
+                    } else {
+                        self.build_function(module, name, tipo)
                     }
-                } else {
-                    self.uplc_data_holder_lookup.insert(
-                        ConstrFieldKey {
-                            local_var: current_var_name.clone(),
-                            field_name: label.clone(),
-                        },
-                        ScopedExpr {
-                            scope: current_scope.clone(),
-                            expr: expr.clone(),
-                        },
-                    );
                 }
+                ModuleValueConstructor::Constant { literal, .. } => self.build_constant(literal),
+            },
+            TypedExpr::Todo { .. } => Term::Error,
+            TypedExpr::ErrorTerm { label, .. } => match label {
+                Some(label) => self.build_error(label),
+                None => Term::Error,
+            },
+            TypedExpr::Trace { then, text, .. } => {
+                let then = self.build(then);
 
-                if let Some(val) = self
-                    .uplc_data_usage_holder_lookup
-                    .get(&current_var_name.clone())
-                {
-                    if current_scope.is_less_than(val, false) {
-                        self.uplc_data_usage_holder_lookup
-                            .insert(current_var_name, current_scope);
-                    }
+                if self.keep_traces {
+                    trace(text, then)
                 } else {
-                    self.uplc_data_usage_holder_lookup
-                        .insert(current_var_name, current_scope);
+                    then
                 }
             }
-            TypedExpr::ModuleSelect { constructor, .. } => match constructor {
-                ModuleValueConstructor::Record { .. } => {}
-                ModuleValueConstructor::Fn { module, name, .. } => {
-                    if self
-                        .uplc_function_holder_lookup
-                        .get(&FunctionAccessKey {
-                            module_name: module.to_string(),
-                            function_name: name.to_string(),
-                        })
-                        .is_none()
-                    {
-                        let func_def = self
-                            .functions
-                            .get(&FunctionAccessKey {
-                                module_name: module.to_string(),
-                                function_name: name.to_string(),
-                            })
-                            .unwrap();
-
-                        self.recurse_scope_level(
-                            &func_def.body,
-                            scope_level
-                                .scope_increment_sequence(func_def.arguments.len() as i32 + 1),
-                        );
+            TypedExpr::RecordUpdate {
+                tipo, spread, args, ..
+            } => self.build_record_update(spread, args, tipo),
+            TypedExpr::Negate { value, .. } => {
+                let value = self.build(value);
+
+                if_then_else(
+                    value,
+                    Term::Constant(Constant::Bool(false)),
+                    Term::Constant(Constant::Bool(true)),
+                )
+            }
+            TypedExpr::NegateInt { value, .. } => builtin_call(
+                DefaultFunction::SubtractInteger,
+                vec![Term::Constant(Constant::Integer(0)), self.build(value)],
+            ),
+        }
+    }
 
-                        self.uplc_function_holder_lookup.insert(
-                            FunctionAccessKey {
-                                module_name: module.to_string(),
-                                function_name: name.to_string(),
-                            },
-                            scope_level,
-                        );
-                    } else if scope_level.is_less_than(
-                        self.uplc_function_holder_lookup
-                            .get(&FunctionAccessKey {
-                                module_name: module.to_string(),
-                                function_name: name.to_string(),
-                            })
-                            .unwrap(),
-                        false,
-                    ) {
-                        let func_def = self
-                            .functions
-                            .get(&FunctionAccessKey {
-                                module_name: module.to_string(),
-                                function_name: name.to_string(),
-                            })
-                            .unwrap();
-
-                        self.uplc_function_holder_lookup.insert(
-                            FunctionAccessKey {
-                                module_name: module.to_string(),
-                                function_name: name.to_string(),
-                            },
-                            scope_level
-                                .scope_increment_sequence(func_def.arguments.len() as i32 + 1),
-                        );
-                    }
-                }
-                ModuleValueConstructor::Constant { .. } => todo!(),
-            },
-            TypedExpr::Todo { .. } => todo!(),
-            TypedExpr::RecordUpdate { .. } => todo!(),
-            TypedExpr::Negate { .. } => todo!(),
+    /// Prepend `elements`, given along with their types, to `tail` or to the
+    /// empty list of type `tipo`.
+    fn build_list(
+        &mut self,
+        elements: Vec<(Term<Name>, Arc<Type>)>,
+        tail: Option<Term<Name>>,
+        tipo: &Arc<Type>,
+    ) -> Term<Name> {
+        if self.repr(tipo) == Repr::Scott {
+            let mut term = match tail {
+                Some(tail) => tail,
+                None => self.build_scott_value(0, tipo, vec![]),
+            };
+
+            for (element, _) in elements.into_iter().rev() {
+                term = self.build_scott_value(1, tipo, vec![element, term]);
+            }
+
+            return term;
+        }
+
+        let mut term =
+            tail.unwrap_or_else(|| Term::Constant(Constant::ProtoList(UplcType::Data, vec![])));
+
+        for (element, element_tipo) in elements.into_iter().rev() {
+            let value = self.wrap_data(element, &element_tipo);
+
+            term = builtin_call(DefaultFunction::MkCons, vec![value, term]);
+        }
+
+        term
+    }
+
+    fn build_sequence(&mut self, expressions: &[TypedExpr]) -> Term<Name> {
+        let (last, rest) = expressions.split_last().expect("sequences are never empty");
+
+        let mut term = self.build(last);
+
+        for expression in rest.iter().rev() {
+            term = match expression {
+                TypedExpr::Assignment {
+                    value,
+                    pattern,
+                    kind,
+                    tipo,
+                    location,
+                } => self.build_assignment(value, pattern, (*kind, *location), tipo, Some(term)),
+                _ => apply(lambda("_", term), self.build(expression)),
+            };
         }
+
+        term
     }
 
-    fn recurse_scope_level_pattern(
+    /// Bind `value` to `pattern` and continue with `then`, or with the value
+    /// itself when the assignment ends its block.
+    ///
+    /// Refutable patterns fail the script when they don't match. A `check`
+    /// also validates the whole structure of the value beforehand, which
+    /// matters for values decoded from untrusted data. So does an `assert`
+    /// whose annotation casts `Data` into another type.
+    fn build_assignment(
         &mut self,
-        pattern: &TypedPattern,
         value: &TypedExpr,
-        scope_level: ScopeLevels,
-        vars: &[TypedExpr],
-    ) {
-        match pattern {
-            Pattern::Int { .. }
-            | Pattern::String { .. }
-            | Pattern::Var { .. }
-            | Pattern::List { .. }
-            | Pattern::Discard { .. } => {
-                self.recurse_scope_level(value, scope_level);
-            }
-
-            Pattern::VarUsage { .. } => todo!(),
-            Pattern::Assign { .. } => todo!(),
-            Pattern::Constructor {
-                name: constructor_name,
+        pattern: &TypedPattern,
+        (kind, location): (AssignmentKind, Span),
+        tipo: &Arc<Type>,
+        then: Option<Term<Name>>,
+    ) -> Term<Name> {
+        let tipo = self.resolve(tipo);
+        let cast = self.resolve(&value.tipo()).is_data() && !tipo.is_data();
+
+        if cast && matches!(self.repr(&tipo), Repr::Function | Repr::Scott) {
+            self.errors.push(Error::FunctionFromData {
+                module: self.module.clone(),
+                location,
                 tipo,
-                arguments,
-                constructor,
-                module,
-                ..
-            } => {
-                self.recurse_scope_level(value, scope_level.scope_increment_sequence(1));
-
-                match &**tipo {
-                    Type::App { module, name, .. } => {
-                        if let Some(val) = self.uplc_data_constr_lookup.get(&DataTypeKey {
-                            module_name: module.to_string(),
-                            defined_type: name.clone(),
-                        }) {
-                            if scope_level.is_less_than(val, false) {
-                                self.uplc_data_constr_lookup.insert(
-                                    DataTypeKey {
-                                        module_name: module.to_string(),
-                                        defined_type: name.clone(),
-                                    },
-                                    scope_level,
-                                );
-                            }
-                        } else {
-                            self.uplc_data_constr_lookup.insert(
-                                DataTypeKey {
-                                    module_name: module.to_string(),
-                                    defined_type: name.clone(),
-                                },
-                                scope_level,
-                            );
-                        }
-                    }
-                    Type::Fn { .. } => {
-                        let mut mapping_index: IndexMap<String, usize> = IndexMap::new();
-
-                        match constructor {
-                            tipo::PatternConstructor::Record { field_map, .. } => {
-                                if let Some(fields_mapping) = field_map {
-                                    mapping_index.extend(fields_mapping.fields.clone());
-                                    mapping_index
-                                        .sort_by(|_, value1, _, value2| value1.cmp(value2));
-                                    mapping_index.reverse();
-                                }
-                            }
-                        };
+            });
 
-                        let module = module.clone().unwrap();
-                        // TODO: support multiple subjects
-                        let (var_name, tipo) = match &vars[0] {
-                            TypedExpr::Var {
-                                name, constructor, ..
-                            } => (name, constructor.tipo.clone()),
-                            rest => todo!("implement: {:#?}", rest),
-                        };
+            return Term::Error;
+        }
 
-                        let mut type_name = String::new();
-                        let mut is_app = false;
-                        let current_tipo = &*tipo;
-                        while !is_app {
-                            match current_tipo {
-                                Type::App { name, .. } => {
-                                    type_name = name.to_string();
-                                    is_app = true;
-                                }
-                                _ => todo!(),
-                            };
-                        }
+        let value = self.build(value);
 
-                        for (ind, arg) in arguments.iter().rev().enumerate() {
-                            let (label, index) = if let Some(arg_label) = &arg.label {
-                                (
-                                    arg_label.to_string(),
-                                    mapping_index.remove(arg_label).unwrap() as u64,
-                                )
-                            } else {
-                                let arg_field =
-                                    mapping_index.pop().unwrap_or((format!("{ind}"), ind));
-                                (arg_field.0, arg_field.1 as u64)
-                            };
-
-                            match &arg.value {
-                                Pattern::Var {
-                                    name: field_name, ..
-                                } => {
-                                    let record_access = TypedExpr::Assignment {
-                                        location: Span::empty(),
-                                        tipo: Type::App {
-                                            public: true,
-                                            module: module.clone(),
-                                            name: constructor_name.to_string(),
-                                            args: vec![],
-                                        }
-                                        .into(),
-                                        value: TypedExpr::RecordAccess {
-                                            location: Span::empty(),
-                                            tipo: Type::App {
-                                                public: true,
-                                                module: module.clone(),
-                                                name: constructor_name.to_string(),
-                                                args: vec![],
-                                            }
-                                            .into(),
-                                            label: label.clone(),
-                                            index,
-                                            record: TypedExpr::Var {
-                                                location: Span::empty(),
-                                                constructor: tipo::ValueConstructor {
-                                                    public: false,
-                                                    variant:
-                                                        ValueConstructorVariant::LocalVariable {
-                                                            location: Span::empty(),
-                                                        },
-                                                    tipo: Type::App {
-                                                        public: true,
-                                                        module: module.clone(),
-                                                        name: type_name.clone(),
-                                                        args: vec![],
-                                                    }
-                                                    .into(),
-                                                },
-                                                name: var_name.clone(),
-                                            }
-                                            .into(),
-                                        }
-                                        .into(),
-                                        pattern: TypedPattern::Var {
-                                            location: Span::empty(),
-                                            name: field_name.clone(),
-                                        },
-                                        kind: AssignmentKind::Let,
-                                    };
-
-                                    if let Some(val) =
-                                        self.uplc_data_holder_lookup.get(&ConstrFieldKey {
-                                            local_var: var_name.clone(),
-                                            field_name: label.clone(),
-                                        })
-                                    {
-                                        if scope_level.is_less_than(&val.scope, false) {
-                                            self.uplc_data_holder_lookup.insert(
-                                                ConstrFieldKey {
-                                                    local_var: var_name.clone(),
-                                                    field_name: label.clone(),
-                                                },
-                                                ScopedExpr {
-                                                    scope: scope_level.scope_increment(1),
-                                                    expr: record_access.clone(),
-                                                },
-                                            );
-                                        }
-                                    } else {
-                                        self.uplc_data_holder_lookup.insert(
-                                            ConstrFieldKey {
-                                                local_var: var_name.clone(),
-                                                field_name: label.clone(),
-                                            },
-                                            ScopedExpr {
-                                                scope: scope_level.scope_increment(1),
-                                                expr: record_access.clone(),
-                                            },
-                                        );
-                                    }
-
-                                    if let Some(val) =
-                                        self.uplc_data_usage_holder_lookup.get(&var_name.clone())
-                                    {
-                                        if scope_level.is_less_than(val, false) {
-                                            self.uplc_data_usage_holder_lookup
-                                                .insert(var_name.clone(), scope_level.clone());
-                                        }
-                                    } else {
-                                        self.uplc_data_usage_holder_lookup
-                                            .insert(var_name.clone(), scope_level.clone());
-                                    }
-
-                                    if let Some(val) =
-                                        self.uplc_data_constr_lookup.get(&DataTypeKey {
-                                            module_name: module.to_string(),
-                                            defined_type: type_name.clone(),
-                                        })
-                                    {
-                                        if scope_level.is_less_than(val, false) {
-                                            self.uplc_data_constr_lookup.insert(
-                                                DataTypeKey {
-                                                    module_name: module.to_string(),
-                                                    defined_type: type_name.clone(),
-                                                },
-                                                scope_level.clone(),
-                                            );
-                                        }
-                                    } else {
-                                        self.uplc_data_constr_lookup.insert(
-                                            DataTypeKey {
-                                                module_name: module.to_string(),
-                                                defined_type: type_name.clone(),
-                                            },
-                                            scope_level.clone(),
-                                        );
-                                    }
-                                }
-                                Pattern::Discard { .. } => {}
-                                _ => todo!(),
-                            };
-                        }
-                    }
-                    _ => todo!(),
-                };
+        let then = match (pattern, kind, then) {
+            (crate::ast::Pattern::Var { name, .. }, AssignmentKind::Let, Some(then)) if !cast => {
+                return apply(lambda(name, then), value)
             }
+            (_, _, then) => then,
+        };
+
+        let subject = self.fresh("subject");
+
+        let then = then.unwrap_or_else(|| var(&subject));
+
+        let mut body = self.build_pattern(pattern, &subject, &tipo, then, &Term::Error);
+
+        // The variable the value is bound to, and its data when it has some.
+        let (binder, data) = if cast {
+            let data = self.fresh("data");
+
+            body = apply(lambda(&subject, body), self.unwrap_data(var(&data), &tipo));
+
+            (data.clone(), Some(var(&data)))
+        } else if matches!(self.repr(&tipo), Repr::Data | Repr::List) {
+            let data = self.wrap_data(var(&subject), &tipo);
+
+            (subject, Some(data))
+        } else {
+            (subject, None)
+        };
+
+        let validate = kind == AssignmentKind::Check || (kind == AssignmentKind::Assert && cast);
+
+        if let (true, Some(data)) = (validate, data) {
+            let validator = self.build_validator(&tipo);
+
+            let message = match pattern {
+                crate::ast::Pattern::Var { name, .. } => {
+                    format!("{} is not a valid {}", name, tipo.to_pretty(0))
+                }
+                _ => format!("not a valid {}", tipo.to_pretty(0)),
+            };
+
+            body = if_then_else(apply(validator, data), body, self.build_error(&message));
         }
+
+        apply(lambda(&binder, body), value)
     }
 
-    fn recurse_code_gen(&mut self, body: &TypedExpr, scope_level: ScopeLevels) -> Term<Name> {
-        match body {
-            TypedExpr::Int { value, .. } => {
-                Term::Constant(Constant::Integer(value.parse::<i128>().unwrap()))
-            }
-            TypedExpr::String { value, .. } => Term::Constant(Constant::String(value.clone())),
-            TypedExpr::ByteArray { bytes, .. } => {
+    /// Fail the script, tracing `message` first when traces are kept.
+    fn build_error(&self, message: &str) -> Term<Name> {
+        if self.keep_traces {
+            trace(message, Term::Error)
+        } else {
+            Term::Error
+        }
+    }
+
+    fn build_constant(&mut self, literal: &AikenConstant<Arc<Type>, String>) -> Term<Name> {
+        match literal {
+            AikenConstant::Int { value, .. } => build_int(value),
+            AikenConstant::String { value, .. } => Term::Constant(Constant::String(value.clone())),
+            AikenConstant::ByteArray { bytes, .. } => {
                 Term::Constant(Constant::ByteString(bytes.clone()))
             }
-            TypedExpr::Sequence { expressions, .. } | TypedExpr::Pipeline { expressions, .. } => {
-                for (i, exp) in expressions.iter().enumerate().rev() {
-                    let mut term = self
-                        .recurse_code_gen(exp, scope_level.scope_increment_sequence(i as i32 + 1));
-
-                    term = self
-                        .maybe_insert_def(term, scope_level.scope_increment_sequence(i as i32 + 1));
-
-                    self.uplc_function_holder
-                        .push((String::new(), term.clone()));
-                }
+            AikenConstant::List { elements, tipo, .. } => {
+                let elements = elements
+                    .iter()
+                    .map(|element| (self.build_constant(element), element.tipo()))
+                    .collect();
 
-                self.maybe_insert_def(
-                    self.uplc_function_holder.clone().pop().unwrap().1,
-                    scope_level,
-                )
+                self.build_list(elements, None, tipo)
             }
-            TypedExpr::Var {
-                constructor, name, ..
+            AikenConstant::Record {
+                name, args, tipo, ..
             } => {
-                if name == "True" || name == "False" {
-                    Term::Constant(Constant::Bool(name == "True"))
+                let fields = args
+                    .iter()
+                    .map(|arg| (self.build_constant(&arg.value), arg.value.tipo()))
+                    .collect::<Vec<_>>();
+
+                if fields.is_empty() {
+                    self.build_record(name, 0, tipo)
                 } else {
-                    match constructor.variant.clone() {
-                        ValueConstructorVariant::LocalVariable { .. } => Term::Var(Name {
-                            text: name.to_string(),
-                            unique: 0.into(),
-                        }),
-                        ValueConstructorVariant::ModuleConstant { .. } => todo!(),
-                        ValueConstructorVariant::ModuleFn { module, name, .. } => Term::Var(Name {
-                            text: format!("{module}_{name}"),
-                            unique: 0.into(),
-                        }),
-                        ValueConstructorVariant::Record {
-                            name: constr_name, ..
-                        } => {
-                            let data_type_key = match &*constructor.tipo {
-                                Type::App { module, name, .. } => DataTypeKey {
-                                    module_name: module.to_string(),
-                                    defined_type: name.to_string(),
-                                },
-                                Type::Fn { .. } => todo!(),
-                                Type::Var { .. } => todo!(),
-                            };
-
-                            if let Some(data_type) = self.data_types.get(&data_type_key) {
-                                let (constr_index, _constr) = data_type
-                                    .constructors
-                                    .iter()
-                                    .enumerate()
-                                    .find(|(_, x)| x.name == *constr_name)
-                                    .unwrap();
-
-                                Term::Apply {
-                                    function: Term::Builtin(DefaultFunction::ConstrData).into(),
-                                    argument: Term::Apply {
-                                        function: Term::Apply {
-                                            function: Term::Builtin(DefaultFunction::MkPairData)
-                                                .into(),
-                                            argument: Term::Constant(Constant::Data(
-                                                PlutusData::BigInt(BigInt::Int(
-                                                    (constr_index as i128).try_into().unwrap(),
-                                                )),
-                                            ))
-                                            .into(),
-                                        }
-                                        .into(),
-                                        argument: Term::Constant(Constant::Data(
-                                            PlutusData::Array(vec![]),
-                                        ))
-                                        .into(),
-                                    }
-                                    .into(),
-                                }
-                            } else {
-                                todo!()
-                            }
-                        }
-                    }
+                    self.build_constr(name, tipo, fields)
                 }
             }
-            TypedExpr::Fn { .. } => todo!(),
-            TypedExpr::List {
-                elements,
-                tail,
-                tipo,
-                ..
+            AikenConstant::Var {
+                name, constructor, ..
             } => {
-                let mut type_list = vec![];
-                let mut is_final_type = false;
-                // TODO use lifetimes instead of clone
-                // Skip first type since we know we have a list
-                let mut current_tipo = match (**tipo).clone() {
-                    Type::App { args, .. } => (*args[0]).clone(),
-                    Type::Fn { .. } => todo!(),
-                    Type::Var { .. } => todo!(),
-                };
-                while !is_final_type {
-                    match current_tipo.clone() {
-                        Type::App { name, args, .. } => {
-                            if args.is_empty() {
-                                type_list.push(name);
-                                is_final_type = true;
-                            } else {
-                                type_list.push(name);
-                                current_tipo = (*args[0]).clone();
-                            }
-                        }
-                        Type::Fn { .. } => todo!(),
-                        Type::Var { tipo } => match (*tipo).borrow().clone() {
-                            tipo::TypeVar::Unbound { .. } => todo!(),
-                            tipo::TypeVar::Link { tipo } => {
-                                current_tipo = (*tipo).clone();
-                            }
-                            tipo::TypeVar::Generic { .. } => todo!(),
-                        },
-                    };
-                }
-
-                let mut list_term = if let Some(tail_list) = tail {
-                    // Get list of tail items
-                    self.recurse_code_gen(tail_list, scope_level.clone())
-                } else {
-                    // Or get empty list of correct type
-                    let mut current_type = vec![];
-                    for type_name in type_list.into_iter().rev() {
-                        match type_name.as_str() {
-                            "ByteArray" => current_type.push(UplcType::ByteString),
-                            "Int" => current_type.push(UplcType::Integer),
-                            "String" => current_type.push(UplcType::String),
-                            "Bool" => current_type.push(UplcType::Bool),
-                            "List" => {
-                                if let Some(prev_type) = current_type.pop() {
-                                    current_type.push(UplcType::List(prev_type.into()));
-                                } else {
-                                    unreachable!()
-                                }
-                            }
-                            "Pair" => todo!(),
-                            _ => current_type.push(UplcType::Data),
-                        };
-                    }
-                    Term::Constant(Constant::ProtoList(current_type.pop().unwrap(), vec![]))
-                };
+                let constructor = constructor
+                    .as_ref()
+                    .expect("constants are type checked before code generation");
 
-                // use mkCons to prepend all elements in reverse
-                for element in elements.iter().rev() {
-                    let element_term = self.recurse_code_gen(element, scope_level.clone());
+                self.build_var(name, constructor)
+            }
+        }
+    }
 
-                    list_term = Term::Apply {
-                        function: Term::Apply {
-                            function: Term::Force(Term::Builtin(DefaultFunction::MkCons).into())
-                                .into(),
-                            argument: element_term.into(),
-                        }
-                        .into(),
-                        argument: list_term.into(),
-                    }
-                }
-                list_term
+    fn build_var(&mut self, name: &str, constructor: &ValueConstructor) -> Term<Name> {
+        match &constructor.variant {
+            ValueConstructorVariant::LocalVariable { .. } => var(name),
+            ValueConstructorVariant::ModuleConstant { literal, .. } => self.build_constant(literal),
+            ValueConstructorVariant::ModuleFn {
+                builtin: Some(builtin),
+                ..
+            } => self.build_builtin(*builtin, &constructor.tipo),
+            ValueConstructorVariant::ModuleFn {
+                name,
+                module,
+                builtin: None,
+                ..
+            } => self.build_function(module, name, &constructor.tipo),
+            ValueConstructorVariant::Record { name, arity, .. } => {
+                self.build_record(name, *arity, &constructor.tipo)
             }
-            TypedExpr::Call {
-                fun, args, tipo, ..
-            } => {
-                match (&**tipo, &**fun) {
-                    (
-                        Type::App {
-                            name: tipo_name, ..
-                        },
-                        TypedExpr::Var {
-                            constructor: ValueConstructor { variant, .. },
-                            ..
-                        },
-                    ) => match variant {
-                        ValueConstructorVariant::LocalVariable { .. } => todo!(),
-                        ValueConstructorVariant::ModuleConstant { .. } => todo!(),
-                        ValueConstructorVariant::ModuleFn { name, module, .. } => {
-                            let func_key = FunctionAccessKey {
-                                module_name: module.to_string(),
-                                function_name: name.to_string(),
-                            };
-                            if let Some(val) = self.function_recurse_lookup.get(&func_key) {
-                                self.function_recurse_lookup.insert(func_key, *val + 1);
-                            } else {
-                                self.function_recurse_lookup.insert(func_key, 1);
-                            }
-                            let mut term =
-                                self.recurse_code_gen(fun, scope_level.scope_increment(1));
-
-                            for (i, arg) in args.iter().enumerate() {
-                                term = Term::Apply {
-                                    function: term.into(),
-                                    argument: self
-                                        .recurse_code_gen(
-                                            &arg.value,
-                                            scope_level.scope_increment(i as i32 + 2),
-                                        )
-                                        .into(),
-                                };
-                            }
-                            term
-                        }
-                        ValueConstructorVariant::Record {
-                            name: constr_name,
-                            module,
-                            ..
-                        } => {
-                            let mut term: Term<Name> =
-                                Term::Constant(Constant::ProtoList(uplc::ast::Type::Data, vec![]));
-
-                            if let Some(data_type) = self.data_types.get(&DataTypeKey {
-                                module_name: module.to_string(),
-                                defined_type: tipo_name.to_string(),
-                            }) {
-                                let (constr_index, constr) = data_type
-                                    .constructors
-                                    .iter()
-                                    .enumerate()
-                                    .find(|(_, x)| x.name == *constr_name)
-                                    .unwrap();
-
-                                // TODO: order arguments by data type field map
-                                let arg_to_data: Vec<(bool, Term<Name>)> = constr
-                                    .arguments
-                                    .iter()
-                                    .map(|x| {
-                                        if let Type::App { name, .. } = &*x.tipo {
-                                            if name == "ByteArray" {
-                                                (true, Term::Builtin(DefaultFunction::BData))
-                                            } else if name == "Int" {
-                                                (true, Term::Builtin(DefaultFunction::IData))
-                                            } else {
-                                                (false, Term::Constant(Constant::Unit))
-                                            }
-                                        } else {
-                                            unreachable!()
-                                        }
-                                    })
-                                    .collect();
-
-                                for (i, arg) in args.iter().enumerate().rev() {
-                                    let arg_term = self.recurse_code_gen(
-                                        &arg.value,
-                                        scope_level.scope_increment(i as i32 + 1),
-                                    );
-
-                                    term = Term::Apply {
-                                        function: Term::Apply {
-                                            function: Term::Force(
-                                                Term::Builtin(DefaultFunction::MkCons).into(),
-                                            )
-                                            .into(),
-                                            argument: if arg_to_data[i].0 {
-                                                Term::Apply {
-                                                    function: arg_to_data[i].1.clone().into(),
-                                                    argument: arg_term.into(),
-                                                }
-                                                .into()
-                                            } else {
-                                                arg_term.into()
-                                            },
-                                        }
-                                        .into(),
-                                        argument: term.into(),
-                                    };
-                                }
-
-                                term = Term::Apply {
-                                    function: Term::Builtin(DefaultFunction::ConstrData).into(),
-                                    argument: Term::Apply {
-                                        function: Term::Apply {
-                                            function: Term::Builtin(DefaultFunction::MkPairData)
-                                                .into(),
-                                            argument: Term::Constant(Constant::Data(
-                                                PlutusData::BigInt(BigInt::Int(
-                                                    (constr_index as i128).try_into().unwrap(),
-                                                )),
-                                            ))
-                                            .into(),
-                                        }
-                                        .into(),
-                                        argument: Term::Apply {
-                                            function: Term::Builtin(DefaultFunction::ListData)
-                                                .into(),
-                                            argument: term.into(),
-                                        }
-                                        .into(),
-                                    }
-                                    .into(),
-                                };
-
-                                term
-                            } else {
-                                let mut term =
-                                    self.recurse_code_gen(fun, scope_level.scope_increment(1));
-
-                                for (i, arg) in args.iter().enumerate() {
-                                    term = Term::Apply {
-                                        function: term.into(),
-                                        argument: self
-                                            .recurse_code_gen(
-                                                &arg.value,
-                                                scope_level.scope_increment(i as i32 + 2),
-                                            )
-                                            .into(),
-                                    };
-                                }
-                                term
-                            }
-                        }
-                    },
+        }
+    }
 
-                    (
-                        Type::App {
-                            name: tipo_name, ..
-                        },
-                        TypedExpr::ModuleSelect {
-                            constructor,
-                            module_name: module,
-                            ..
-                        },
-                    ) => {
-                        match constructor {
-                            ModuleValueConstructor::Constant { .. } => todo!(),
-                            ModuleValueConstructor::Fn { name, module, .. } => {
-                                let func_key = FunctionAccessKey {
-                                    module_name: module.to_string(),
-                                    function_name: name.to_string(),
-                                };
-                                if let Some(val) = self.function_recurse_lookup.get(&func_key) {
-                                    self.function_recurse_lookup.insert(func_key, *val + 1);
-                                } else {
-                                    self.function_recurse_lookup.insert(func_key, 1);
-                                }
-                                let mut term =
-                                    self.recurse_code_gen(fun, scope_level.scope_increment(1));
-
-                                for (i, arg) in args.iter().enumerate() {
-                                    term = Term::Apply {
-                                        function: term.into(),
-                                        argument: self
-                                            .recurse_code_gen(
-                                                &arg.value,
-                                                scope_level.scope_increment(i as i32 + 2),
-                                            )
-                                            .into(),
-                                    };
-                                }
-                                term
-                            }
-                            ModuleValueConstructor::Record {
-                                name: constr_name, ..
-                            } => {
-                                let mut term: Term<Name> = Term::Constant(Constant::ProtoList(
-                                    uplc::ast::Type::Data,
-                                    vec![],
-                                ));
-
-                                if let Some(data_type) = self.data_types.get(&DataTypeKey {
-                                    module_name: module.to_string(),
-                                    defined_type: tipo_name.to_string(),
-                                }) {
-                                    let (constr_index, constr) = data_type
-                                        .constructors
-                                        .iter()
-                                        .enumerate()
-                                        .find(|(_, x)| x.name == *constr_name)
-                                        .unwrap();
-
-                                    // TODO: order arguments by data type field map
-                                    let arg_to_data: Vec<(bool, Term<Name>)> = constr
-                                        .arguments
-                                        .iter()
-                                        .map(|x| {
-                                            if let Type::App { name, .. } = &*x.tipo {
-                                                if name == "ByteArray" {
-                                                    (true, Term::Builtin(DefaultFunction::BData))
-                                                } else if name == "Int" {
-                                                    (true, Term::Builtin(DefaultFunction::IData))
-                                                } else {
-                                                    (false, Term::Constant(Constant::Unit))
-                                                }
-                                            } else {
-                                                unreachable!()
-                                            }
-                                        })
-                                        .collect();
-
-                                    for (i, arg) in args.iter().enumerate().rev() {
-                                        let arg_term = self.recurse_code_gen(
-                                            &arg.value,
-                                            scope_level.scope_increment(i as i32 + 1),
-                                        );
-
-                                        term = Term::Apply {
-                                            function: Term::Apply {
-                                                function: Term::Force(
-                                                    Term::Builtin(DefaultFunction::MkCons).into(),
-                                                )
-                                                .into(),
-                                                argument: if arg_to_data[i].0 {
-                                                    Term::Apply {
-                                                        function: arg_to_data[i].1.clone().into(),
-                                                        argument: arg_term.into(),
-                                                    }
-                                                    .into()
-                                                } else {
-                                                    arg_term.into()
-                                                },
-                                            }
-                                            .into(),
-                                            argument: term.into(),
-                                        };
-                                    }
-
-                                    term = Term::Apply {
-                                        function: Term::Builtin(DefaultFunction::ConstrData).into(),
-                                        argument: Term::Apply {
-                                            function: Term::Apply {
-                                                function: Term::Builtin(
-                                                    DefaultFunction::MkPairData,
-                                                )
-                                                .into(),
-                                                argument: Term::Constant(Constant::Data(
-                                                    PlutusData::BigInt(BigInt::Int(
-                                                        (constr_index as i128).try_into().unwrap(),
-                                                    )),
-                                                ))
-                                                .into(),
-                                            }
-                                            .into(),
-                                            argument: Term::Apply {
-                                                function: Term::Builtin(DefaultFunction::ListData)
-                                                    .into(),
-                                                argument: term.into(),
-                                            }
-                                            .into(),
-                                        }
-                                        .into(),
-                                    };
-
-                                    term
-                                } else {
-                                    let mut term =
-                                        self.recurse_code_gen(fun, scope_level.scope_increment(1));
-
-                                    for (i, arg) in args.iter().enumerate() {
-                                        term = Term::Apply {
-                                            function: term.into(),
-                                            argument: self
-                                                .recurse_code_gen(
-                                                    &arg.value,
-                                                    scope_level.scope_increment(i as i32 + 2),
-                                                )
-                                                .into(),
-                                        };
-                                    }
-                                    term
-                                }
-                            }
-                        }
-                    }
-                    _ => todo!(),
-                }
+    fn build_call(
+        &mut self,
+        fun: &TypedExpr,
+        args: &[CallArg<TypedExpr>],
+        tipo: &Arc<Type>,
+    ) -> Term<Name> {
+        match fun {
+            TypedExpr::Var {
+                constructor:
+                    ValueConstructor {
+                        variant: ValueConstructorVariant::Record { name, .. },
+                        ..
+                    },
+                ..
             }
-            TypedExpr::BinOp {
-                name, left, right, ..
+            | TypedExpr::ModuleSelect {
+                constructor: ModuleValueConstructor::Record { name, .. },
+                ..
             } => {
-                let left_term = self.recurse_code_gen(left, scope_level.clone());
-
-                let right_term = self.recurse_code_gen(right, scope_level);
-
-                match name {
-                    BinOp::Eq => match &*left.tipo() {
-                        Type::App { name, .. } => match name.as_str() {
-                            "Int" => Term::Apply {
-                                function: Term::Apply {
-                                    function: Term::Builtin(DefaultFunction::EqualsInteger).into(),
-                                    argument: left_term.into(),
-                                }
-                                .into(),
-                                argument: right_term.into(),
-                            },
+                let fields = args
+                    .iter()
+                    .map(|arg| (self.build(&arg.value), arg.value.tipo()))
+                    .collect();
 
-                            "String" => Term::Apply {
-                                function: Term::Apply {
-                                    function: Term::Builtin(DefaultFunction::EqualsString).into(),
-                                    argument: left_term.into(),
-                                }
-                                .into(),
-                                argument: right_term.into(),
+                self.build_constr(name, tipo, fields)
+            }
+            TypedExpr::Var {
+                constructor:
+                    ValueConstructor {
+                        variant:
+                            ValueConstructorVariant::ModuleFn {
+                                builtin: Some(builtin),
+                                ..
                             },
+                        ..
+                    },
+                ..
+            } if builtin.arity() == args.len() => self.build_builtin_call(*builtin, args, tipo),
+            TypedExpr::ModuleSelect {
+                module_name,
+                constructor: ModuleValueConstructor::Fn { name, .. },
+                ..
+            } if module_name == "aiken/builtin" && builtin_by_name(name).arity() == args.len() => {
+                self.build_builtin_call(builtin_by_name(name), args, tipo)
+            }
+            _ => {
+                let mut term = self.build(fun);
 
-                            "ByteArray" => Term::Apply {
-                                function: Term::Apply {
-                                    function: Term::Builtin(DefaultFunction::EqualsByteString)
-                                        .into(),
-                                    argument: left_term.into(),
-                                }
-                                .into(),
-                                argument: right_term.into(),
-                            },
+                if args.is_empty() {
+                    term = apply(term, Term::Constant(Constant::Unit));
+                }
 
-                            _ => todo!(),
-                        },
-                        Type::Fn { .. } => todo!(),
-                        Type::Var { .. } => todo!(),
-                    },
-                    BinOp::And => Term::Force(
-                        Term::Apply {
-                            function: Term::Apply {
-                                function: Term::Apply {
-                                    function: Term::Force(
-                                        Term::Builtin(DefaultFunction::IfThenElse).into(),
-                                    )
-                                    .into(),
-                                    argument: left_term.into(),
-                                }
-                                .into(),
-                                argument: Term::Delay(
-                                    Term::Apply {
-                                        function: Term::Apply {
-                                            function: Term::Apply {
-                                                function: Term::Force(
-                                                    Term::Builtin(DefaultFunction::IfThenElse)
-                                                        .into(),
-                                                )
-                                                .into(),
-                                                argument: right_term.into(),
-                                            }
-                                            .into(),
-                                            argument: Term::Constant(Constant::Bool(true)).into(),
-                                        }
-                                        .into(),
-                                        argument: Term::Constant(Constant::Bool(false)).into(),
-                                    }
-                                    .into(),
-                                )
-                                .into(),
-                            }
-                            .into(),
-                            argument: Term::Delay(Term::Constant(Constant::Bool(false)).into())
-                                .into(),
-                        }
-                        .into(),
-                    ),
-                    BinOp::Or => Term::Force(
-                        Term::Apply {
-                            function: Term::Apply {
-                                function: Term::Apply {
-                                    function: Term::Force(
-                                        Term::Builtin(DefaultFunction::IfThenElse).into(),
-                                    )
-                                    .into(),
-                                    argument: left_term.into(),
-                                }
-                                .into(),
-                                argument: Term::Delay(Term::Constant(Constant::Bool(true)).into())
-                                    .into(),
-                            }
-                            .into(),
-                            argument: Term::Delay(
-                                Term::Apply {
-                                    function: Term::Apply {
-                                        function: Term::Apply {
-                                            function: Term::Force(
-                                                Term::Builtin(DefaultFunction::IfThenElse).into(),
-                                            )
-                                            .into(),
-                                            argument: right_term.into(),
-                                        }
-                                        .into(),
-                                        argument: Term::Constant(Constant::Bool(true)).into(),
-                                    }
-                                    .into(),
-                                    argument: Term::Constant(Constant::Bool(false)).into(),
-                                }
-                                .into(),
-                            )
-                            .into(),
-                        }
-                        .into(),
-                    ),
-                    BinOp::NotEq => match &*left.tipo() {
-                        Type::App { name, .. } => {
-                            let equality = match name.as_str() {
-                                "Int" => Term::Apply {
-                                    function: Term::Apply {
-                                        function: Term::Builtin(DefaultFunction::EqualsInteger)
-                                            .into(),
-                                        argument: left_term.into(),
-                                    }
-                                    .into(),
-                                    argument: right_term.into(),
-                                },
-
-                                "String" => Term::Apply {
-                                    function: Term::Apply {
-                                        function: Term::Builtin(DefaultFunction::EqualsString)
-                                            .into(),
-                                        argument: left_term.into(),
-                                    }
-                                    .into(),
-                                    argument: right_term.into(),
-                                },
-
-                                "ByteArray" => Term::Apply {
-                                    function: Term::Apply {
-                                        function: Term::Builtin(DefaultFunction::EqualsByteString)
-                                            .into(),
-                                        argument: left_term.into(),
-                                    }
-                                    .into(),
-                                    argument: right_term.into(),
-                                },
-
-                                _ => todo!(),
-                            };
-                            Term::Apply {
-                                function: Term::Apply {
-                                    function: Term::Apply {
-                                        function: Term::Force(
-                                            Term::Builtin(DefaultFunction::IfThenElse).into(),
-                                        )
-                                        .into(),
-                                        argument: equality.into(),
-                                    }
-                                    .into(),
-                                    argument: Term::Constant(Constant::Bool(false)).into(),
-                                }
-                                .into(),
-                                argument: Term::Constant(Constant::Bool(true)).into(),
-                            }
-                        }
-                        Type::Fn { .. } => todo!(),
-                        Type::Var { .. } => todo!(),
-                    },
-                    BinOp::LtInt => Term::Apply {
-                        function: Term::Apply {
-                            function: Term::Builtin(DefaultFunction::LessThanInteger).into(),
-                            argument: left_term.into(),
-                        }
-                        .into(),
-                        argument: right_term.into(),
-                    },
-                    BinOp::LtEqInt => Term::Apply {
-                        function: Term::Apply {
-                            function: Term::Builtin(DefaultFunction::LessThanEqualsInteger).into(),
-                            argument: left_term.into(),
-                        }
-                        .into(),
-                        argument: right_term.into(),
-                    },
-                    BinOp::GtEqInt => Term::Apply {
-                        function: Term::Apply {
-                            function: Term::Builtin(DefaultFunction::LessThanEqualsInteger).into(),
-                            argument: right_term.into(),
-                        }
-                        .into(),
-                        argument: left_term.into(),
-                    },
-                    BinOp::GtInt => Term::Apply {
-                        function: Term::Apply {
-                            function: Term::Builtin(DefaultFunction::LessThanInteger).into(),
-                            argument: right_term.into(),
-                        }
-                        .into(),
-                        argument: left_term.into(),
-                    },
-                    BinOp::AddInt => Term::Apply {
-                        function: Term::Apply {
-                            function: Term::Builtin(DefaultFunction::AddInteger).into(),
-                            argument: left_term.into(),
-                        }
-                        .into(),
-                        argument: right_term.into(),
-                    },
-                    BinOp::SubInt => Term::Apply {
-                        function: Term::Apply {
-                            function: Term::Builtin(DefaultFunction::SubtractInteger).into(),
-                            argument: left_term.into(),
-                        }
-                        .into(),
-                        argument: right_term.into(),
-                    },
-                    BinOp::MultInt => Term::Apply {
-                        function: Term::Apply {
-                            function: Term::Builtin(DefaultFunction::MultiplyInteger).into(),
-                            argument: left_term.into(),
-                        }
-                        .into(),
-                        argument: right_term.into(),
-                    },
-                    BinOp::DivInt => Term::Apply {
-                        function: Term::Apply {
-                            function: Term::Builtin(DefaultFunction::DivideInteger).into(),
-                            argument: left_term.into(),
-                        }
-                        .into(),
-                        argument: right_term.into(),
-                    },
-                    BinOp::ModInt => Term::Apply {
-                        function: Term::Apply {
-                            function: Term::Builtin(DefaultFunction::ModInteger).into(),
-                            argument: left_term.into(),
-                        }
-                        .into(),
-                        argument: right_term.into(),
-                    },
+                for arg in args {
+                    term = apply(term, self.build(&arg.value));
                 }
+
+                term
             }
-            TypedExpr::Assignment { value, pattern, .. } => match pattern {
-                Pattern::Int { .. } => todo!(),
-                Pattern::String { .. } => todo!(),
-                Pattern::Var { name, .. } => Term::Apply {
-                    function: Term::Lambda {
-                        parameter_name: Name {
-                            text: name.to_string(),
-                            unique: 0.into(),
-                        },
-                        body: self.uplc_function_holder.pop().unwrap().1.into(),
-                    }
-                    .into(),
-                    argument: self
-                        .recurse_code_gen(value, scope_level.scope_increment(1))
-                        .into(),
-                },
-
-                Pattern::VarUsage { .. } => todo!(),
-                Pattern::Assign { .. } => todo!(),
-                Pattern::Discard { .. } => todo!(),
-                Pattern::List { .. } => todo!(),
-                Pattern::Constructor { .. } => todo!(),
-            },
-            TypedExpr::Try { .. } => todo!(),
-            TypedExpr::When {
-                subjects, clauses, ..
-            } => {
-                let subject = &subjects[0];
-
-                let mut is_var = false;
-
-                let mut current_var_name = String::new();
-
-                let mut current_subject = subject.clone();
-
-                while !is_var {
-                    match current_subject.clone() {
-                        TypedExpr::Var {
-                            constructor, name, ..
-                        } => match (
-                            constructor.clone().variant.clone(),
-                            (*constructor.tipo).clone(),
-                        ) {
-                            (ValueConstructorVariant::LocalVariable { .. }, Type::App { .. }) => {
-                                current_var_name = if current_var_name.is_empty() {
-                                    name
-                                } else {
-                                    format!("{name}_field_{current_var_name}")
-                                };
-                                is_var = true;
-                            }
-                            _ => todo!(),
-                        },
-                        TypedExpr::RecordAccess { label, record, .. } => {
-                            current_var_name = if current_var_name.is_empty() {
-                                label.to_string()
-                            } else {
-                                format!("{label}_field_{current_var_name}")
-                            };
-                            current_subject = *record.clone();
-                        }
-                        _ => {}
-                    }
-                }
+        }
+    }
 
-                let current_clauses = clauses.clone();
+    fn build_binop(
+        &mut self,
+        name: &BinOp,
+        left: &TypedExpr,
+        right: &TypedExpr,
+        location: Span,
+    ) -> Term<Name> {
+        let left_term = self.build(left);
+        let right_term = self.build(right);
 
-                let mut current_module = String::new();
+        match name {
+            BinOp::And => {
+                if_then_else(left_term, right_term, Term::Constant(Constant::Bool(false)))
+            }
+            BinOp::Or => if_then_else(left_term, Term::Constant(Constant::Bool(true)), right_term),
+            BinOp::Eq => self.build_equality(left_term, right_term, &left.tipo(), location),
+            BinOp::NotEq => if_then_else(
+                self.build_equality(left_term, right_term, &left.tipo(), location),
+                Term::Constant(Constant::Bool(false)),
+                Term::Constant(Constant::Bool(true)),
+            ),
+            BinOp::LtInt => builtin_call(
+                DefaultFunction::LessThanInteger,
+                vec![left_term, right_term],
+            ),
+            BinOp::LtEqInt => builtin_call(
+                DefaultFunction::LessThanEqualsInteger,
+                vec![left_term, right_term],
+            ),
+            BinOp::GtEqInt => builtin_call(
+                DefaultFunction::LessThanEqualsInteger,
+                vec![right_term, left_term],
+            ),
+            BinOp::GtInt => builtin_call(
+                DefaultFunction::LessThanInteger,
+                vec![right_term, left_term],
+            ),
+            BinOp::AddInt => builtin_call(DefaultFunction::AddInteger, vec![left_term, right_term]),
+            BinOp::SubInt => builtin_call(
+                DefaultFunction::SubtractInteger,
+                vec![left_term, right_term],
+            ),
+            BinOp::MultInt => builtin_call(
+                DefaultFunction::MultiplyInteger,
+                vec![left_term, right_term],
+            ),
+            BinOp::DivInt => {
+                builtin_call(DefaultFunction::DivideInteger, vec![left_term, right_term])
+            }
+            BinOp::ModInt => builtin_call(DefaultFunction::ModInteger, vec![left_term, right_term]),
+        }
+    }
 
-                let mut total_constr_length = 0;
-                let pattern = &clauses[0].pattern[0];
+    fn build_equality(
+        &mut self,
+        left: Term<Name>,
+        right: Term<Name>,
+        tipo: &Arc<Type>,
+        location: Span,
+    ) -> Term<Name> {
+        match self.repr(tipo) {
+            Repr::Integer => builtin_call(DefaultFunction::EqualsInteger, vec![left, right]),
+            Repr::ByteString => builtin_call(DefaultFunction::EqualsByteString, vec![left, right]),
+            Repr::String => builtin_call(DefaultFunction::EqualsString, vec![left, right]),
+            Repr::Bool | Repr::Unit | Repr::List | Repr::Data => {
+                let left = self.wrap_data(left, tipo);
+                let right = self.wrap_data(right, tipo);
+
+                builtin_call(DefaultFunction::EqualsData, vec![left, right])
+            }
+            Repr::Function | Repr::Scott => {
+                self.errors.push(Error::FunctionComparison {
+                    module: self.module.clone(),
+                    location,
+                    tipo: self.resolve(tipo),
+                });
 
-                let key = match pattern {
-                    Pattern::Constructor { tipo, .. } => {
-                        let mut is_app = false;
-                        let mut tipo = &**tipo;
-                        let mut key = DataTypeKey {
-                            module_name: String::new(),
-                            defined_type: String::new(),
-                        };
-                        while !is_app {
-                            match tipo {
-                                Type::App { module, name, .. } => {
-                                    is_app = true;
-                                    key.module_name = module.clone();
-                                    key.defined_type = name.clone();
-                                }
-                                Type::Fn { ret, .. } => {
-                                    tipo = ret;
-                                }
-                                _ => todo!(),
-                            };
-                        }
+                Term::Error
+            }
+        }
+    }
 
-                        Some(key)
-                    }
-                    Pattern::List { .. } => None,
-                    Pattern::Discard { .. } => None,
-                    _ => todo!(),
-                };
+    /// A reference to a module function, compiling the specialisation that
+    /// matches `tipo` on first use.
+    fn build_function(&mut self, module: &str, name: &str, tipo: &Arc<Type>) -> Term<Name> {
+        let key = FunctionAccessKey {
+            module_name: module.to_string(),
+            function_name: name.to_string(),
+        };
 
-                if let Some(key) = key {
-                    let dt = self.data_types.get(&key).unwrap();
-                    let data_type = &dt.name;
-                    let mut new_current_clauses: Vec<(usize, Term<Name>)> = current_clauses
-                        .iter()
-                        .map(|clause| {
-                            let pattern = &clause.pattern[0];
-                            let pair = match pattern {
-                                Pattern::Constructor { name, module, .. } => {
-                                    let index =
-                                        dt.constructors.iter().position(|c| name.clone() == c.name);
-                                    let mut current_term = self.recurse_code_gen(
-                                        &clause.then,
-                                        scope_level.scope_increment_sequence(1),
-                                    );
-                                    if let Some(ind) = index {
-                                        for (index, field) in
-                                            dt.constructors[ind].arguments.iter().enumerate()
-                                        {
-                                            let label =
-                                                field.clone().label.unwrap_or(format!("{index}"));
-
-                                            if let Some(ScopedExpr {
-                                                expr: TypedExpr::Assignment { pattern, .. },
-                                                ..
-                                            }) =
-                                                self.uplc_data_holder_lookup.get(&ConstrFieldKey {
-                                                    local_var: current_var_name.to_string(),
-                                                    field_name: label.clone(),
-                                                })
-                                            {
-                                                let var_name = match pattern {
-                                                    Pattern::Var { name, .. } => name,
-                                                    _ => todo!(),
-                                                };
-
-                                                current_term = Term::Apply {
-                                                    function: Term::Lambda {
-                                                        parameter_name: Name {
-                                                            text: var_name.to_string(),
-                                                            unique: 0.into(),
-                                                        },
-                                                        body: current_term.into(),
-                                                    }
-                                                    .into(),
-                                                    argument: Term::Var(Name {
-                                                        text: format!(
-                                                            "{current_var_name}_field_{label}"
-                                                        ),
-                                                        unique: 0.into(),
-                                                    })
-                                                    .into(),
-                                                };
-                                            }
-                                        }
-                                    }
-
-                                    current_module = module.clone().unwrap_or_default();
-                                    total_constr_length = dt.constructors.len();
-
-                                    (index.unwrap_or(dt.constructors.len()), current_term)
-                                }
-                                _ => todo!(),
-                            };
-                            pair
-                        })
-                        .collect();
-
-                    new_current_clauses.sort_by(|a, b| a.0.cmp(&b.0));
-
-                    let mut term = Term::Apply {
-                        function: Term::Var(Name {
-                            text: format!("choose_{current_module}_{data_type}_constr"),
-                            unique: 0.into(),
-                        })
-                        .into(),
-                        argument: Term::Var(Name {
-                            text: current_var_name,
-                            unique: 0.into(),
-                        })
-                        .into(),
-                    };
-                    let need_lam = total_constr_length - new_current_clauses.len() > 0;
+        let func = *self
+            .functions
+            .get(&key)
+            .unwrap_or_else(|| panic!("unknown function {}.{}", module, name));
 
-                    let (last, new_current_clauses) = new_current_clauses.split_last().unwrap();
+        let declared = function(
+            func.arguments.iter().map(|arg| arg.tipo.clone()).collect(),
+            func.return_type.clone(),
+        );
 
-                    let mut new_current_clauses = new_current_clauses.to_vec();
-                    new_current_clauses.reverse();
-                    let last_term = last.1.clone();
+        let mut mapping = HashMap::new();
 
-                    let mut current: Option<(usize, Term<Name>)> = None;
-                    for index in 0..total_constr_length - 1 {
-                        if current.is_none() {
-                            current = new_current_clauses.pop();
-                        }
-                        if let Some(val) = current.clone() {
-                            if val.0 == index {
-                                let branch_term = val.1;
-
-                                term = Term::Apply {
-                                    function: term.into(),
-                                    argument: Term::Delay(branch_term.into()).into(),
-                                };
-                                current = None;
-                            } else {
-                                term = Term::Apply {
-                                    function: term.into(),
-                                    argument: Term::Var(Name {
-                                        text: "last_constr_then".to_string(),
-                                        unique: 0.into(),
-                                    })
-                                    .into(),
-                                }
-                            }
-                        } else {
-                            term = Term::Apply {
-                                function: term.into(),
-                                argument: Term::Var(Name {
-                                    text: "last_constr_then".to_string(),
-                                    unique: 0.into(),
-                                })
-                                .into(),
-                            }
-                        }
-                    }
-                    if need_lam {
-                        term = Term::Apply {
-                            function: Term::Lambda {
-                                parameter_name: Name {
-                                    text: "last_constr_then".to_string(),
-                                    unique: 0.into(),
-                                },
-                                body: Term::Apply {
-                                    function: term.into(),
-                                    argument: Term::Var(Name {
-                                        text: "last_constr_then".to_string(),
-                                        unique: 0.into(),
-                                    })
-                                    .into(),
-                                }
-                                .into(),
-                            }
-                            .into(),
-                            argument: Term::Delay(last_term.into()).into(),
-                        }
-                    } else {
-                        term = Term::Apply {
-                            function: term.into(),
-                            argument: Term::Delay(last_term.into()).into(),
-                        };
-                    }
+        match_generics(&declared, &self.resolve(tipo), &mut mapping);
 
-                    term
-                } else {
-                    let mut type_list = vec![];
-                    let mut is_final_type = false;
-                    // TODO use lifetimes instead of clone
-                    // Skip first type since we know we have a list
-                    let tipo = match subject {
-                        TypedExpr::Var { constructor, .. } => &constructor.tipo,
-                        _ => todo!(),
-                    };
-                    let mut current_tipo = match (**tipo).clone() {
-                        Type::App { args, .. } => (*args[0]).clone(),
-                        Type::Fn { .. } => todo!(),
-                        Type::Var { .. } => todo!(),
-                    };
+        let specialised_name = specialised_name(module, name, &mapping);
 
-                    while !is_final_type {
-                        match current_tipo.clone() {
-                            Type::App { name, args, .. } => {
-                                if args.is_empty() {
-                                    type_list.push(name);
-                                    is_final_type = true;
-                                } else {
-                                    type_list.push(name);
-                                    current_tipo = (*args[0]).clone();
-                                }
-                            }
-                            Type::Fn { .. } => todo!(),
-                            Type::Var { tipo } => match (*tipo).borrow().clone() {
-                                tipo::TypeVar::Unbound { .. } => todo!(),
-                                tipo::TypeVar::Link { tipo } => {
-                                    current_tipo = (*tipo).clone();
-                                }
-                                tipo::TypeVar::Generic { .. } => todo!(),
-                            },
-                        };
-                    }
+        if self.definitions.contains_key(&specialised_name) {
+            return var(&specialised_name);
+        }
 
-                    let mut new_current_clauses: Vec<(Option<usize>, bool, Term<Name>)> =
-                        current_clauses
-                            .iter()
-                            .map(|clause| {
-                                let pattern = &clause.pattern[0];
-                                let mut current_term = self.recurse_code_gen(
-                                    &clause.then,
-                                    scope_level.scope_increment_sequence(1),
-                                );
-                                let triplet = match pattern {
-                                    Pattern::List { elements, tail, .. } => {
-                                        let element_names: Vec<String> = elements
-                                            .clone()
-                                            .iter()
-                                            .map(|element| match element {
-                                                Pattern::Var { name, .. } => name.to_string(),
-                                                _ => todo!(),
-                                            })
-                                            .collect();
-
-                                        let tail_name: Option<String> = if let Some(tail) = tail {
-                                            match &**tail {
-                                                Pattern::Var { name, .. } => Some(name.to_string()),
-                                                _ => todo!(),
-                                            }
-                                        } else {
-                                            None
-                                        };
-
-                                        for (index, var_name) in element_names.iter().enumerate() {
-                                            current_term = Term::Apply {
-                                                function: Term::Lambda {
-                                                    parameter_name: Name {
-                                                        text: var_name.to_string(),
-                                                        unique: 0.into(),
-                                                    },
-                                                    body: current_term.into(),
-                                                }
-                                                .into(),
-                                                argument: Term::Var(Name {
-                                                    text: format!(
-                                                        "{current_var_name}_item_{index}"
-                                                    ),
-                                                    unique: 0.into(),
-                                                })
-                                                .into(),
-                                            };
-                                        }
-
-                                        if let Some(tail_name) = tail_name {
-                                            current_term = Term::Apply {
-                                                function: Term::Lambda {
-                                                    parameter_name: Name {
-                                                        text: tail_name,
-                                                        unique: 0.into(),
-                                                    },
-                                                    body: current_term.into(),
-                                                }
-                                                .into(),
-                                                argument: Term::Var(Name {
-                                                    text: format!("{current_var_name}_rest"),
-                                                    unique: 0.into(),
-                                                })
-                                                .into(),
-                                            };
-                                        }
-
-                                        (Some(elements.len()), tail.is_some(), current_term)
-                                    }
-                                    Pattern::Discard { .. } => (None, false, current_term),
-                                    _ => todo!(),
-                                };
-                                triplet
-                            })
-                            .collect();
-
-                    new_current_clauses.sort_by(|item1, item2| {
-                        if item1.0.is_none() && item2.0.is_some() {
-                            Ordering::Greater
-                        } else if item2.0.is_none() && item1.0.is_some() {
-                            Ordering::Less
-                        } else {
-                            match item1.0.cmp(&item2.0) {
-                                Ordering::Less => Ordering::Less,
-                                Ordering::Equal => item1.1.cmp(&item2.1),
-                                Ordering::Greater => Ordering::Greater,
-                            }
-                        }
-                    });
+        if let Some(group) = self.recursion_group(&key) {
+            return self.build_knot_member(group, specialised_name, (module, func), mapping);
+        }
 
-                    let (last, new_current_clauses) = new_current_clauses.split_last().unwrap();
+        if self.in_progress.contains(&specialised_name) {
+            if !self.recursive.contains(&specialised_name) {
+                self.recursive.push(specialised_name.clone());
+            }
 
-                    let new_current_clauses = new_current_clauses.to_vec();
+            return apply(var(&specialised_name), var(&specialised_name));
+        }
 
-                    let mut current_term: Term<Name> = last.2.clone();
-                    let last_term = last.2.clone();
+        self.in_progress.push(specialised_name.clone());
 
-                    //if last clause had a tail then we need the lambda to expose rest
-                    if last.1 && last.0.is_some() {
-                        let last_index = last.0.unwrap() - 1;
-                        current_term = Term::Apply {
-                            function: Term::Lambda {
-                                parameter_name: Name {
-                                    text: format!("{current_var_name}_rest"),
-                                    unique: 0.into(),
-                                },
-                                body: current_term.into(),
-                            }
-                            .into(),
-                            argument: Term::Var(Name {
-                                text: format!("{current_var_name}_tail_{last_index}"),
-                                unique: 0.into(),
-                            })
-                            .into(),
-                        };
-                    }
+        let mut term = self.build_function_body(module, func, mapping);
 
-                    for (index, (array_length, has_tail, then)) in
-                        new_current_clauses.iter().enumerate().rev()
-                    {
-                        let prev_length: Option<usize> = if index == 0 {
-                            None
-                        } else {
-                            new_current_clauses
-                                .get(index - 1)
-                                .and_then(|(index_opt, _, _)| *index_opt)
-                        };
+        self.in_progress.pop();
 
-                        match (*array_length, prev_length) {
-                            (Some(length), Some(prev_length)) => {
-                                let check_length = if prev_length == length {
-                                    length + 2
-                                } else {
-                                    length + 1
-                                };
-                                // 0, 3, 3, None
-                                // Go index by index to create cases for each possible len
-                                for expose_index in (prev_length + 1..check_length).rev() {
-                                    let prev_exposed = expose_index - 1;
-                                    let list_var_name =
-                                        format!("{current_var_name}_tail_{prev_exposed}");
-
-                                    if prev_length != length {
-                                        // Just expose head list and tail list. Check for empty list happens above
-                                        current_term = Term::Apply {
-                                                function: Term::Lambda {
-                                                    parameter_name: Name {
-                                                        text: format!(
-                                                            "{current_var_name}_item_{expose_index}"
-                                                        ),
-                                                        unique: 0.into(),
-                                                    },
-                                                    body: Term::Apply {
-                                                        function: Term::Lambda {
-                                                            parameter_name: Name {
-                                                                text: format!(
-                                                                    "{current_var_name}_tail_{expose_index}"
-                                                                ),
-                                                                unique: 0.into(),
-                                                            },
-                                                            body: current_term.into(),
-                                                        }
-                                                        .into(),
-                                                        argument: Term::Apply {
-                                                            function: Term::Force(
-                                                                Term::Builtin(
-                                                                    DefaultFunction::TailList,
-                                                                )
-                                                                .into(),
-                                                            )
-                                                            .into(),
-                                                            argument: Term::Var(Name {
-                                                                text: list_var_name.to_string(),
-                                                                unique: 0.into(),
-                                                            })
-                                                            .into(),
-                                                        }
-                                                        .into(),
-                                                    }
-                                                    .into(),
-                                                }
-                                                .into(),
-                                                argument: Term::Apply {
-                                                    function: Term::Force(
-                                                        Term::Builtin(DefaultFunction::HeadList).into(),
-                                                    )
-                                                    .into(),
-                                                    argument: Term::Var(Name {
-                                                        text: list_var_name.to_string(),
-                                                        unique: 0.into(),
-                                                    })
-                                                    .into(),
-                                                }
-                                                .into(),
-                                            };
-                                    }
-
-                                    // For a given list length if we encounter a tail and we are checking a clause length = current index
-                                    // then expose a var for tail and run clause then
-                                    current_term = if *has_tail
-                                        && (expose_index == check_length - 1
-                                            || prev_length == length)
-                                    {
-                                        Term::Apply {
-                                            function: Term::Lambda {
-                                                parameter_name: Name {
-                                                    text: format!("{current_var_name}_rest"),
-                                                    unique: 0.into(),
-                                                },
-                                                body: then.clone().into(),
-                                            }
-                                            .into(),
-                                            argument: Term::Var(Name {
-                                                text: format!(
-                                                    "{current_var_name}_tail_{prev_exposed}"
-                                                ),
-                                                unique: 0.into(),
-                                            })
-                                            .into(),
-                                        }
-                                    // we are checking a clause length = current index so check empty tail list and run clause then if tail list is empty
-                                    } else if expose_index == check_length - 1 {
-                                        Term::Force(
-                                            Term::Apply {
-                                                function: Term::Apply {
-                                                    function: Term::Apply {
-                                                        function: Term::Force(
-                                                            Term::Force(
-                                                                Term::Builtin(
-                                                                    DefaultFunction::ChooseList,
-                                                                )
-                                                                .into(),
-                                                            )
-                                                            .into(),
-                                                        )
-                                                        .into(),
-                                                        argument: Term::Var(Name {
-                                                            text: format!(
-                                                                "{current_var_name}_tail_{prev_exposed}"
-                                                            ),
-                                                            unique: 0.into(),
-                                                        })
-                                                        .into(),
-                                                    }
-                                                    .into(),
-                                                    argument: Term::Delay(then.clone().into())
-                                                        .into(),
-                                                }
-                                                .into(),
-                                                argument: Term::Delay(current_term.into()).into(),
-                                            }
-                                            .into(),
-                                        )
-                                    // We are not checking for a list of this length, so fallback to last clause then if tail list is empty
-                                    } else {
-                                        Term::Force(
-                                            Term::Apply {
-                                                function: Term::Apply {
-                                                    function: Term::Apply {
-                                                        function: Term::Force(
-                                                            Term::Force(
-                                                                Term::Builtin(
-                                                                    DefaultFunction::ChooseList,
-                                                                )
-                                                                .into(),
-                                                            )
-                                                            .into(),
-                                                        )
-                                                        .into(),
-                                                        argument: Term::Var(Name {
-                                                            text: format!(
-                                                                "{current_var_name}_tail_{prev_exposed}"
-                                                            ),
-                                                            unique: 0.into(),
-                                                        })
-                                                        .into(),
-                                                    }
-                                                    .into(),
-                                                    argument: Term::Delay(last_term.clone().into())
-                                                        .into(),
-                                                }
-                                                .into(),
-                                                argument: Term::Delay(current_term.into()).into(),
-                                            }
-                                            .into(),
-                                        )
-                                    };
-                                }
-                            }
-                            (Some(length), None) => {
-                                for expose_index in 0..length + 1 {
-                                    let list_var_name = if expose_index == 0 {
-                                        current_var_name.clone()
-                                    } else {
-                                        let prev_exposed = expose_index - 1;
-                                        format!("{current_var_name}_tail_{prev_exposed}")
-                                    };
-
-                                    // Just expose head list and tail list. Check for empty list happens above
-                                    current_term = Term::Apply {
-                                        function: Term::Lambda {
-                                            parameter_name: Name {
-                                                text: format!(
-                                                    "{current_var_name}_item_{expose_index}"
-                                                ),
-                                                unique: 0.into(),
-                                            },
-                                            body: Term::Apply {
-                                                function: Term::Lambda {
-                                                    parameter_name: Name {
-                                                        text: format!(
-                                                            "{current_var_name}_tail_{expose_index}"
-                                                        ),
-                                                        unique: 0.into(),
-                                                    },
-                                                    body: current_term.into(),
-                                                }
-                                                .into(),
-                                                argument: Term::Apply {
-                                                    function: Term::Force(
-                                                        Term::Builtin(DefaultFunction::TailList)
-                                                            .into(),
-                                                    )
-                                                    .into(),
-                                                    argument: Term::Var(Name {
-                                                        text: list_var_name.to_string(),
-                                                        unique: 0.into(),
-                                                    })
-                                                    .into(),
-                                                }
-                                                .into(),
-                                            }
-                                            .into(),
-                                        }
-                                        .into(),
-                                        argument: Term::Apply {
-                                            function: Term::Force(
-                                                Term::Builtin(DefaultFunction::HeadList).into(),
-                                            )
-                                            .into(),
-                                            argument: Term::Var(Name {
-                                                text: list_var_name.to_string(),
-                                                unique: 0.into(),
-                                            })
-                                            .into(),
-                                        }
-                                        .into(),
-                                    };
-
-                                    // For a given list length if we encounter a tail and we are checking a clause length = current index
-                                    // then expose a var for tail and run clause then
-                                    current_term = if *has_tail && expose_index == length {
-                                        Term::Apply {
-                                            function: Term::Lambda {
-                                                parameter_name: Name {
-                                                    text: format!("{current_var_name}_rest"),
-                                                    unique: 0.into(),
-                                                },
-                                                body: then.clone().into(),
-                                            }
-                                            .into(),
-                                            argument: Term::Var(Name {
-                                                text: list_var_name.clone(),
-                                                unique: 0.into(),
-                                            })
-                                            .into(),
-                                        }
-                                    // we are checking a clause length = current index so check empty tail list and run clause then if tail list is empty
-                                    } else if expose_index == length {
-                                        Term::Force(
-                                            Term::Apply {
-                                                function: Term::Apply {
-                                                    function: Term::Apply {
-                                                        function: Term::Force(
-                                                            Term::Force(
-                                                                Term::Builtin(
-                                                                    DefaultFunction::ChooseList,
-                                                                )
-                                                                .into(),
-                                                            )
-                                                            .into(),
-                                                        )
-                                                        .into(),
-                                                        argument: Term::Var(Name {
-                                                            text: list_var_name.clone(),
-                                                            unique: 0.into(),
-                                                        })
-                                                        .into(),
-                                                    }
-                                                    .into(),
-                                                    argument: Term::Delay(then.clone().into())
-                                                        .into(),
-                                                }
-                                                .into(),
-                                                argument: Term::Delay(current_term.into()).into(),
-                                            }
-                                            .into(),
-                                        )
-                                    // We are not checking for a list of this length, so fallback to last clause then if tail list is empty
-                                    } else {
-                                        Term::Force(
-                                            Term::Apply {
-                                                function: Term::Apply {
-                                                    function: Term::Apply {
-                                                        function: Term::Force(
-                                                            Term::Force(
-                                                                Term::Builtin(
-                                                                    DefaultFunction::ChooseList,
-                                                                )
-                                                                .into(),
-                                                            )
-                                                            .into(),
-                                                        )
-                                                        .into(),
-                                                        argument: Term::Var(Name {
-                                                            text: list_var_name.clone(),
-                                                            unique: 0.into(),
-                                                        })
-                                                        .into(),
-                                                    }
-                                                    .into(),
-                                                    argument: Term::Delay(last_term.clone().into())
-                                                        .into(),
-                                                }
-                                                .into(),
-                                                argument: Term::Delay(current_term.into()).into(),
-                                            }
-                                            .into(),
-                                        )
-                                    };
-                                }
-                            }
-                            (None, None) => todo!(),
-                            (None, Some(_)) => todo!(),
-                        }
-                    }
-                    current_term
-                }
-            }
-            // if statements increase scope due to branching.
-            TypedExpr::If {
-                branches,
-                final_else,
-                ..
-            } => {
-                let mut final_if_term =
-                    self.recurse_code_gen(final_else, scope_level.scope_increment_sequence(1));
+        if let Some(index) = self
+            .recursive
+            .iter()
+            .position(|recursive| recursive == &specialised_name)
+        {
+            self.recursive.remove(index);
 
-                if branches.len() == 1 {
-                    let condition_term = self.recurse_code_gen(
-                        &branches[0].condition,
-                        scope_level.scope_increment_sequence(1),
-                    );
+            // Self-application: the body calls itself as `f f`.
+            term = apply(
+                lambda("__recurse", apply(var("__recurse"), var("__recurse"))),
+                lambda(&specialised_name, term),
+            );
+        }
 
-                    let branch_term = self.recurse_code_gen(
-                        &branches[0].body,
-                        scope_level.scope_increment_sequence(1),
-                    );
+        self.definitions.insert(specialised_name.clone(), term);
 
-                    match (final_if_term.clone(), branch_term.clone()) {
-                        (
-                            Term::Var(..) | Term::Constant(..),
-                            Term::Var(..) | Term::Constant(..),
-                        ) => {
-                            final_if_term = Term::Apply {
-                                function: Rc::new(Term::Apply {
-                                    function: Rc::new(Term::Apply {
-                                        function: Rc::new(Term::Force(Rc::new(Term::Builtin(
-                                            DefaultFunction::IfThenElse,
-                                        )))),
-                                        argument: Rc::new(condition_term),
-                                    }),
-                                    //If this is just a var then don't include delay
-                                    argument: Rc::new(branch_term),
-                                }),
-                                //If this is just a var then don't include delay
-                                argument: Rc::new(final_if_term.clone()),
-                            };
-                        }
-                        _ => {
-                            final_if_term = Term::Force(
-                                Term::Apply {
-                                    function: Rc::new(Term::Apply {
-                                        function: Rc::new(Term::Apply {
-                                            function: Rc::new(Term::Force(Rc::new(Term::Builtin(
-                                                DefaultFunction::IfThenElse,
-                                            )))),
-                                            argument: Rc::new(condition_term),
-                                        }),
-                                        argument: Rc::new(Term::Delay(Rc::new(branch_term))),
-                                    }),
-                                    argument: Rc::new(Term::Delay(Rc::new(final_if_term.clone()))),
-                                }
-                                .into(),
-                            );
-                        }
-                    }
-                } else {
-                    // TODO: for multi branch if statements we can insert function definitions between branches
-                    for branch in branches {
-                        let condition_term = self.recurse_code_gen(
-                            &branch.condition,
-                            scope_level.scope_increment_sequence(1),
-                        );
+        var(&specialised_name)
+    }
 
-                        let branch_term = self.recurse_code_gen(
-                            &branch.body,
-                            scope_level.scope_increment_sequence(1),
-                        );
+    fn build_function_body(
+        &mut self,
+        module: &str,
+        func: &TypedFunction,
+        mapping: TypeMapping,
+    ) -> Term<Name> {
+        let outer_mapping = std::mem::replace(&mut self.type_mapping, mapping);
+        let outer_module = std::mem::replace(&mut self.module, module.to_string());
 
-                        final_if_term = Term::Force(
-                            Term::Apply {
-                                function: Rc::new(Term::Apply {
-                                    function: Rc::new(Term::Apply {
-                                        function: Rc::new(Term::Force(Rc::new(Term::Builtin(
-                                            DefaultFunction::IfThenElse,
-                                        )))),
-                                        argument: Rc::new(condition_term),
-                                    }),
-                                    argument: Rc::new(Term::Delay(Rc::new(branch_term))),
-                                }),
-                                argument: Rc::new(Term::Delay(Rc::new(final_if_term.clone()))),
-                            }
-                            .into(),
-                        );
-                    }
-                }
+        let body = self.build(&func.body);
 
-                self.maybe_insert_def(final_if_term, scope_level)
-            }
-            TypedExpr::RecordAccess { label, record, .. } => {
-                let mut is_var = false;
-                let mut current_var_name = String::new();
-                let mut current_record = *record.clone();
-                while !is_var {
-                    match current_record.clone() {
-                        TypedExpr::Var {
-                            constructor, name, ..
-                        } => match (
-                            constructor.clone().variant.clone(),
-                            (*constructor.tipo).clone(),
-                        ) {
-                            (ValueConstructorVariant::LocalVariable { .. }, Type::App { .. }) => {
-                                current_var_name = if current_var_name.is_empty() {
-                                    name
-                                } else {
-                                    format!("{name}_field_{current_var_name}")
-                                };
-                                is_var = true;
-                            }
-                            _ => todo!(),
-                        },
-                        TypedExpr::RecordAccess { label, record, .. } => {
-                            current_var_name = if current_var_name.is_empty() {
-                                label.to_string()
-                            } else {
-                                format!("{label}_field_{current_var_name}")
-                            };
-                            current_record = *record.clone();
-                        }
-                        _ => {}
-                    }
-                }
-                Term::Var(Name {
-                    text: format!("{current_var_name}_field_{label}"),
-                    unique: 0.into(),
-                })
-            }
-            TypedExpr::ModuleSelect { constructor, .. } => match constructor {
-                ModuleValueConstructor::Record { .. } => todo!(),
-                ModuleValueConstructor::Fn { module, name, .. } => Term::Var(Name {
-                    text: format!("{module}_{name}"),
-                    unique: 0.into(),
-                }),
-                ModuleValueConstructor::Constant { .. } => todo!(),
-            },
-            TypedExpr::Todo { .. } => todo!(),
-            TypedExpr::RecordUpdate { .. } => todo!(),
-            TypedExpr::Negate { .. } => todo!(),
-        }
+        self.type_mapping = outer_mapping;
+        self.module = outer_module;
+
+        function_lambda(&func.arguments, body)
     }
 
-    fn maybe_insert_def(
+    /// Refer to a member of a mutually recursive group. Inside the group's
+    /// knot this is a selection from the knot; otherwise the whole group is
+    /// compiled, and every member gets its own definition.
+    fn build_knot_member(
         &mut self,
-        current_term: Term<Name>,
-        scope_level: ScopeLevels,
+        group: usize,
+        specialised_name: String,
+        (module, func): (&str, &'a TypedFunction),
+        mapping: TypeMapping,
     ) -> Term<Name> {
-        let mut term = current_term;
-        // attempt to insert function definitions where needed
-        for func_key in self.uplc_function_holder_lookup.clone().keys() {
-            if scope_level.is_less_than(
-                self.uplc_function_holder_lookup
-                    .clone()
-                    .get(func_key)
-                    .unwrap(),
-                false,
-            ) {
-                let func_def = self.functions.get(func_key).unwrap();
-
-                let current_called = *self.function_recurse_lookup.get(func_key).unwrap_or(&0);
-
-                let mut function_body = self.recurse_code_gen(
-                    &func_def.body,
-                    scope_level.scope_increment_sequence(func_def.arguments.len() as i32),
-                );
+        if let Some(knot) = self.knots.iter_mut().rev().find(|knot| knot.group == group) {
+            let index = match knot.members.get_index_of(&specialised_name) {
+                Some(index) => index,
+                None => {
+                    knot.members.insert(specialised_name.clone(), None);
+                    knot.pending
+                        .push((specialised_name, module.to_string(), func, mapping));
+
+                    knot.members.len() - 1
+                }
+            };
 
-                let recurse_called = *self.function_recurse_lookup.get(func_key).unwrap_or(&0);
+            return knot_member(&knot.name, index);
+        }
 
-                if recurse_called > current_called {
-                    for arg in func_def.arguments.iter().rev() {
-                        function_body = Term::Lambda {
-                            parameter_name: Name {
-                                text: arg.arg_name.get_variable_name().unwrap_or("_").to_string(),
-                                unique: Unique::new(0),
-                            },
-                            body: Rc::new(function_body),
-                        }
-                    }
+        let knot_name = self.fresh("knot");
 
-                    function_body = Term::Lambda {
-                        parameter_name: Name {
-                            text: format!("{}_{}", func_key.module_name, func_key.function_name),
-                            unique: 0.into(),
-                        },
-                        body: function_body.into(),
-                    };
+        self.knots.push(Knot {
+            name: knot_name.clone(),
+            group,
+            members: IndexMap::from([(specialised_name.clone(), None)]),
+            pending: vec![(specialised_name.clone(), module.to_string(), func, mapping)],
+        });
 
-                    let mut recurse_term = Term::Apply {
-                        function: Term::Var(Name {
-                            text: "recurse".to_string(),
-                            unique: 0.into(),
-                        })
-                        .into(),
-                        argument: Term::Var(Name {
-                            text: "recurse".into(),
-                            unique: 0.into(),
-                        })
-                        .into(),
-                    };
+        while let Some((member, module, func, mapping)) =
+            self.knots.last_mut().unwrap().pending.pop()
+        {
+            let term = self.build_function_body(&module, func, mapping);
 
-                    for arg in func_def.arguments.iter() {
-                        recurse_term = Term::Apply {
-                            function: recurse_term.into(),
-                            argument: Term::Var(Name {
-                                text: arg.arg_name.get_variable_name().unwrap_or("_").to_string(),
-                                unique: 0.into(),
-                            })
-                            .into(),
-                        };
-                    }
+            self.knots.last_mut().unwrap().members[&member] = Some(term);
+        }
 
-                    function_body = Term::Apply {
-                        function: Term::Lambda {
-                            parameter_name: Name {
-                                text: "recurse".into(),
-                                unique: 0.into(),
-                            },
-                            body: recurse_term.into(),
-                        }
-                        .into(),
-                        argument: function_body.into(),
-                    }
-                }
+        let knot = self.knots.pop().unwrap();
 
-                for arg in func_def.arguments.iter().rev() {
-                    function_body = Term::Lambda {
-                        parameter_name: Name {
-                            text: arg.arg_name.get_variable_name().unwrap_or("_").to_string(),
-                            unique: Unique::new(0),
-                        },
-                        body: Rc::new(function_body),
-                    }
-                }
+        let index = self.fresh("index");
 
-                term = Term::Apply {
-                    function: Term::Lambda {
-                        parameter_name: Name {
-                            text: format!("{}_{}", func_key.module_name, func_key.function_name),
-                            unique: 0.into(),
-                        },
-                        body: term.into(),
-                    }
-                    .into(),
-                    argument: function_body.into(),
-                };
-                self.uplc_function_holder_lookup.shift_remove(func_key);
-            }
+        let names = knot.members.keys().cloned().collect::<Vec<_>>();
+
+        let members = knot
+            .members
+            .into_values()
+            .map(|term| term.expect("every knot member is compiled"))
+            .collect();
+
+        self.definitions
+            .insert(knot_name.clone(), tie_knot(&knot_name, &index, members));
+
+        for (i, member) in names.into_iter().enumerate() {
+            self.definitions.insert(member, knot_member(&knot_name, i));
         }
 
-        for (key, scope) in self.uplc_data_constr_lookup.clone().iter() {
-            if scope_level.is_less_than(scope, false) {
-                let data_constrs = *self.data_types.get(key).unwrap();
-                let mut constr_term = Term::Var(Name {
-                    text: "last_constructor_result".to_string(),
-                    unique: 0.into(),
-                });
+        var(&specialised_name)
+    }
 
-                let length = data_constrs.constructors.len();
+    fn build_record(&mut self, name: &str, arity: usize, tipo: &Arc<Type>) -> Term<Name> {
+        let tipo = self.resolve(tipo);
 
-                for index in (0..length - 1).rev() {
-                    constr_term = Term::Apply {
-                        function: Term::Apply {
-                            function: Term::Apply {
-                                function: Term::Force(
-                                    Term::Builtin(DefaultFunction::IfThenElse).into(),
-                                )
-                                .into(),
-                                argument: Term::Apply {
-                                    function: Term::Apply {
-                                        function: Term::Builtin(DefaultFunction::EqualsInteger)
-                                            .into(),
-                                        argument: Term::Constant(Constant::Integer(index as i128))
-                                            .into(),
-                                    }
-                                    .into(),
-                                    argument: Term::Var(Name {
-                                        text: "constr_index".to_string(),
-                                        unique: 0.into(),
-                                    })
-                                    .into(),
-                                }
-                                .into(),
-                            }
-                            .into(),
-                            argument: Term::Var(Name {
-                                text: format!("constr_{index}_result"),
-                                unique: 0.into(),
-                            })
-                            .into(),
-                        }
-                        .into(),
-                        argument: constr_term.into(),
-                    }
-                }
+        match tipo.as_ref() {
+            Type::Fn { args, ret } => {
+                let arg_names = (0..arity).map(|_| self.fresh("arg")).collect::<Vec<_>>();
 
-                constr_term = Term::Lambda {
-                    parameter_name: Name {
-                        text: "last_constructor_result".to_string(),
-                        unique: 0.into(),
-                    },
-                    body: Term::Force(constr_term.into()).into(),
-                };
+                let fields = arg_names
+                    .iter()
+                    .zip(args)
+                    .map(|(arg_name, arg_tipo)| (var(arg_name), arg_tipo.clone()))
+                    .collect();
 
-                for index in (0..length - 1).rev() {
-                    constr_term = Term::Lambda {
-                        parameter_name: Name {
-                            text: format!("constr_{index}_result"),
-                            unique: 0.into(),
-                        },
-                        body: constr_term.into(),
-                    }
+                let mut term = self.build_constr(name, ret, fields);
+
+                for arg_name in arg_names.iter().rev() {
+                    term = lambda(arg_name, term);
                 }
-                let data_type_name = data_constrs.name.clone();
 
-                constr_term = Term::Lambda {
-                    parameter_name: Name {
-                        text: "constr_data".to_string(),
-                        unique: 0.into(),
-                    },
-                    body: Term::Apply {
-                        function: Term::Lambda {
-                            parameter_name: Name {
-                                text: "constr_index".to_string(),
-                                unique: 0.into(),
-                            },
-                            body: constr_term.into(),
-                        }
-                        .into(),
-                        argument: Term::Apply {
-                            function: Term::Force(
-                                Term::Force(Term::Builtin(DefaultFunction::FstPair).into()).into(),
-                            )
-                            .into(),
-                            argument: Term::Apply {
-                                function: Term::Builtin(DefaultFunction::UnConstrData).into(),
-                                argument: Term::Var(Name {
-                                    text: "constr_data".to_string(),
-                                    unique: 0.into(),
-                                })
-                                .into(),
-                            }
-                            .into(),
-                        }
-                        .into(),
-                    }
-                    .into(),
-                };
-                let module = &key.module_name;
-
-                term = Term::Apply {
-                    function: Term::Lambda {
-                        parameter_name: Name {
-                            text: format!("choose_{module}_{data_type_name}_constr"),
-                            unique: 0.into(),
-                        },
-                        body: term.into(),
-                    }
-                    .into(),
-                    argument: constr_term.into(),
-                };
-                self.uplc_data_constr_lookup.shift_remove(key);
+                term
             }
+            _ if tipo.is_bool() => Term::Constant(Constant::Bool(name == "True")),
+            _ if tipo.is_nil() => Term::Constant(Constant::Unit),
+            _ => self.build_constr(name, &tipo, vec![]),
         }
+    }
 
-        // Pull out all uplc data holder fields and data usage, filter by Scope Level, Sort By Scope Depth, Then Apply
-        let mut data_holder: Vec<ConstrConversionInfo> = self
-            .uplc_data_usage_holder_lookup
-            .clone()
-            .into_iter()
-            .filter(|record_scope| scope_level.is_less_than(&record_scope.1, false))
-            .map(|(var_name, scope)| ConstrConversionInfo {
-                local_var: var_name,
-                field: None,
-                scope,
-                index: None,
-                returning_type: String::new(),
-            })
-            .collect();
+    /// Apply the constructor `name` of `tipo` to `fields`, given along with
+    /// their types.
+    fn build_constr(
+        &mut self,
+        name: &str,
+        tipo: &Arc<Type>,
+        fields: Vec<(Term<Name>, Arc<Type>)>,
+    ) -> Term<Name> {
+        let index = self.constructor_index(name, tipo);
 
-        data_holder.extend(
-            self.uplc_data_holder_lookup
-                .clone()
+        if self.repr(tipo) != Repr::Scott {
+            let fields = fields
                 .into_iter()
-                .filter(|record_scope| scope_level.is_less_than(&record_scope.1.scope, false))
-                .map(
-                    |(
-                        ConstrFieldKey {
-                            local_var,
-                            field_name,
-                        },
-                        ScopedExpr { scope, expr },
-                    )| {
-                        let index_type = match expr {
-                            TypedExpr::RecordAccess { index, tipo, .. } => {
-                                let tipo = &*tipo;
-
-                                let name = match tipo {
-                                    Type::App { name, .. } => name,
-                                    Type::Fn { .. } => todo!(),
-                                    Type::Var { .. } => todo!(),
-                                };
-                                (index, name.clone())
-                            }
-                            TypedExpr::Assignment { value, .. } => match *value {
-                                TypedExpr::RecordAccess { index, tipo, .. } => {
-                                    let tipo = &*tipo;
-
-                                    let name = match tipo {
-                                        Type::App { name, .. } => name,
-                                        Type::Fn { .. } => todo!(),
-                                        Type::Var { .. } => todo!(),
-                                    };
-                                    (index, name.clone())
-                                }
-                                _ => todo!(),
-                            },
-                            _ => todo!(),
-                        };
+                .map(|(field, field_tipo)| self.wrap_data(field, &field_tipo))
+                .collect();
 
-                        ConstrConversionInfo {
-                            local_var,
-                            field: Some(field_name),
-                            scope,
-                            index: Some(index_type.0),
-                            returning_type: index_type.1,
-                        }
-                    },
-                )
-                .collect::<Vec<ConstrConversionInfo>>(),
-        );
-        data_holder.sort_by(|item1, item2| {
-            if item1.scope.is_less_than(&item2.scope, true) {
-                Ordering::Less
-            } else if item2.scope.is_less_than(&item1.scope, true) {
-                Ordering::Greater
-            } else if item1.index < item2.index {
-                Ordering::Less
-            } else if item2.index < item1.index {
-                Ordering::Greater
-            } else {
-                Ordering::Equal
-            }
-        });
+            return constr(index, fields);
+        }
 
-        for ConstrConversionInfo {
-            local_var,
-            field,
-            index,
-            returning_type,
-            ..
-        } in data_holder.into_iter().rev()
-        {
-            if let (Some(index), Some(field)) = (index, field) {
-                let var_term = Term::Apply {
-                    function: Term::Apply {
-                        function: Term::Var(Name {
-                            text: "constr_field_get_arg".to_string(),
-                            unique: 0.into(),
-                        })
-                        .into(),
-                        argument: Term::Var(Name {
-                            text: format!("{local_var}_fields"),
-                            unique: 0.into(),
-                        })
-                        .into(),
-                    }
-                    .into(),
-                    argument: Term::Constant(Constant::Integer(index as i128)).into(),
-                };
+        let fields = fields.into_iter().map(|(field, _)| field).collect();
 
-                let type_conversion = match returning_type.as_str() {
-                    "ByteArray" => Term::Apply {
-                        function: Term::Builtin(DefaultFunction::UnBData).into(),
-                        argument: var_term.into(),
-                    },
-                    "Int" => Term::Apply {
-                        function: Term::Builtin(DefaultFunction::UnIData).into(),
-                        argument: var_term.into(),
-                    },
-                    _ => var_term,
-                };
+        self.build_scott_value(index, tipo, fields)
+    }
 
-                term = Term::Apply {
-                    function: Term::Lambda {
-                        parameter_name: Name {
-                            text: format!("{local_var}_field_{field}"),
-                            unique: 0.into(),
-                        },
-                        body: term.into(),
-                    }
-                    .into(),
-                    argument: type_conversion.into(),
-                };
-                self.uplc_data_holder_lookup.shift_remove(&ConstrFieldKey {
-                    local_var,
-                    field_name: field,
-                });
-            } else {
-                term = Term::Apply {
-                    function: Term::Lambda {
-                        parameter_name: Name {
-                            text: format!("{local_var}_fields"),
-                            unique: 0.into(),
-                        },
-                        body: term.into(),
-                    }
-                    .into(),
-                    // TODO: Find proper scope for this function if at all.
-                    argument: Term::Apply {
-                        function: Term::Var(Name {
-                            text: "constr_fields_exposer".to_string(),
-                            unique: 0.into(),
-                        })
-                        .into(),
-                        argument: Term::Var(Name {
-                            text: local_var.to_string(),
-                            unique: 0.into(),
-                        })
-                        .into(),
-                    }
-                    .into(),
-                };
+    /// A Scott encoded value of `tipo` built with the constructor at `index`:
+    /// a function of one handler per constructor, calling its own handler
+    /// with its fields.
+    fn build_scott_value(
+        &mut self,
+        index: usize,
+        tipo: &Arc<Type>,
+        fields: Vec<Term<Name>>,
+    ) -> Term<Name> {
+        let field_names = fields
+            .iter()
+            .map(|_| self.fresh("field"))
+            .collect::<Vec<_>>();
 
-                self.uplc_data_usage_holder_lookup.shift_remove(&local_var);
-            }
+        let mut term = var(&case_name(index));
+
+        if field_names.is_empty() {
+            term = apply(term, Term::Constant(Constant::Unit));
+        }
+
+        for field_name in field_names.iter() {
+            term = apply(term, var(field_name));
+        }
+
+        for case in (0..self.constructors(tipo).len()).rev() {
+            term = lambda(&case_name(case), term);
+        }
+
+        // Fields are evaluated once, when the value is built.
+        for (field_name, field) in field_names.iter().zip(fields).rev() {
+            term = apply(lambda(field_name, term), field);
         }
 
         term
     }
 
-    fn add_arg_getter(&self, term: Term<Name>) -> Term<Name> {
-        // Apply constr arg getter to top level.
-        Term::Apply {
-            function: Term::Lambda {
-                parameter_name: Name {
-                    text: "constr_field_get_arg".to_string(),
-                    unique: 0.into(),
-                },
-                body: term.into(),
-            }
-            .into(),
-            argument: Term::Lambda {
-                parameter_name: Name {
-                    text: "constr_list".to_string(),
-                    unique: 0.into(),
-                },
-                body: Term::Lambda {
-                    parameter_name: Name {
-                        text: "arg_number".to_string(),
-                        unique: 0.into(),
-                    },
-                    body: Term::Apply {
-                        function: Term::Lambda {
-                            parameter_name: Name {
-                                text: "recurse".to_string(),
-                                unique: 0.into(),
-                            },
-                            body: Term::Apply {
-                                function: Term::Apply {
-                                    function: Term::Apply {
-                                        function: Term::Var(Name {
-                                            text: "recurse".to_string(),
-                                            unique: 0.into(),
-                                        })
-                                        .into(),
-                                        argument: Term::Var(Name {
-                                            text: "recurse".to_string(),
-                                            unique: 0.into(),
-                                        })
-                                        .into(),
-                                    }
-                                    .into(),
-
-                                    // Start recursive with index 0 of list
-                                    argument: Term::Constant(Constant::Integer(0.into())).into(),
-                                }
-                                .into(),
-                                argument: Term::Var(Name {
-                                    text: "constr_list".to_string(),
-                                    unique: 0.into(),
-                                })
-                                .into(),
-                            }
-                            .into(),
-                        }
-                        .into(),
+    /// Read field `index` of `record`, a value of `tipo` built with the
+    /// constructor at `constructor`.
+    fn build_field(
+        &mut self,
+        record: Term<Name>,
+        tipo: &Arc<Type>,
+        constructor: usize,
+        index: usize,
+        field_tipo: &Arc<Type>,
+    ) -> Term<Name> {
+        if self.repr(tipo) == Repr::Scott {
+            self.build_scott_match(record, tipo, |case, fields| {
+                if case == constructor {
+                    var(&fields[index])
+                } else {
+                    Term::Error
+                }
+            })
+        } else {
+            self.unwrap_data(nth(constr_fields(record), index), field_tipo)
+        }
+    }
 
-                        argument: Term::Lambda {
-                            parameter_name: Name {
-                                text: "self_recursor".to_string(),
-                                unique: 0.into(),
-                            },
-                            body: Term::Lambda {
-                                parameter_name: Name {
-                                    text: "current_arg_number".to_string(),
-                                    unique: 0.into(),
-                                },
-                                body: Term::Lambda {
-                                    parameter_name: Name {
-                                        text: "list_of_constr_args".to_string(),
-                                        unique: 0.into(),
-                                    },
-                                    body: Term::Apply {
-                                        function: Term::Apply {
-                                            function: Term::Apply {
-                                                function: Term::Apply {
-                                                    function: Term::Force(
-                                                        Term::Builtin(DefaultFunction::IfThenElse)
-                                                            .into(),
-                                                    )
-                                                    .into(),
-                                                    argument: Term::Apply {
-                                                        function: Term::Apply {
-                                                            function: Term::Builtin(
-                                                                DefaultFunction::EqualsInteger,
-                                                            )
-                                                            .into(),
-                                                            argument: Term::Var(Name {
-                                                                text: "arg_number".to_string(),
-                                                                unique: 0.into(),
-                                                            })
-                                                            .into(),
-                                                        }
-                                                        .into(),
-                                                        argument: Term::Var(Name {
-                                                            text: "current_arg_number".to_string(),
-                                                            unique: 0.into(),
-                                                        })
-                                                        .into(),
-                                                    }
-                                                    .into(),
-                                                }
-                                                .into(),
-                                                argument: Term::Force(
-                                                    Term::Builtin(DefaultFunction::HeadList).into(),
-                                                )
-                                                .into(),
-                                            }
-                                            .into(),
-                                            argument: Term::Lambda {
-                                                parameter_name: Name {
-                                                    text: "current_list_of_constr_args".to_string(),
-                                                    unique: 0.into(),
-                                                },
-                                                body: Term::Apply {
-                                                    function: Term::Apply {
-                                                        function: Term::Apply {
-                                                            function: Term::Var(Name {
-                                                                text: "self_recursor".to_string(),
-                                                                unique: 0.into(),
-                                                            })
-                                                            .into(),
-                                                            argument: Term::Var(Name {
-                                                                text: "self_recursor".to_string(),
-                                                                unique: 0.into(),
-                                                            })
-                                                            .into(),
-                                                        }
-                                                        .into(),
-
-                                                        argument: Term::Apply {
-                                                            function: Term::Apply {
-                                                                function: Term::Builtin(
-                                                                    DefaultFunction::AddInteger,
-                                                                )
-                                                                .into(),
-                                                                argument: Term::Var(Name {
-                                                                    text: "current_arg_number"
-                                                                        .to_string(),
-                                                                    unique: 0.into(),
-                                                                })
-                                                                .into(),
-                                                            }
-                                                            .into(),
-                                                            argument: Term::Constant(
-                                                                Constant::Integer(1.into()),
-                                                            )
-                                                            .into(),
-                                                        }
-                                                        .into(),
-                                                    }
-                                                    .into(),
-
-                                                    argument: Term::Apply {
-                                                        function: Term::Force(
-                                                            Term::Builtin(
-                                                                DefaultFunction::TailList,
-                                                            )
-                                                            .into(),
-                                                        )
-                                                        .into(),
-
-                                                        argument: Term::Var(Name {
-                                                            text: "current_list_of_constr_args"
-                                                                .to_string(),
-                                                            unique: 0.into(),
-                                                        })
-                                                        .into(),
-                                                    }
-                                                    .into(),
-                                                }
-                                                .into(),
-                                            }
-                                            .into(),
-                                        }
-                                        .into(),
-                                        argument: Term::Var(Name {
-                                            text: "list_of_constr_args".to_string(),
-                                            unique: 0.into(),
-                                        })
-                                        .into(),
-                                    }
-                                    .into(),
-                                }
-                                .into(),
-                            }
-                            .into(),
-                        }
-                        .into(),
+    /// Copy the record `spread` with the fields in `args` replaced. A data
+    /// record only has its fields list rebuilt up to the last updated field:
+    /// the other fields are copied over as they are, without being decoded,
+    /// and the tail after it is shared.
+    fn build_record_update(
+        &mut self,
+        spread: &TypedExpr,
+        args: &[TypedRecordUpdateArg],
+        tipo: &Arc<Type>,
+    ) -> Term<Name> {
+        let record = self.build(spread);
+
+        // New values are evaluated once, before the record is copied.
+        let updates = args
+            .iter()
+            .map(|arg| {
+                let value = self.build(&arg.value);
+                (arg.index, self.fresh("update"), value, arg.value.tipo())
+            })
+            .collect::<Vec<_>>();
+
+        let mut term = if self.repr(tipo) == Repr::Scott {
+            let cases = self.constructors(tipo).len();
+
+            self.build_scott_match(record, tipo, |case, fields| {
+                let mut term = var(&case_name(case));
+
+                for (index, field) in fields.iter().enumerate() {
+                    let field = match updates.iter().find(|update| update.0 == index) {
+                        Some((_, update, _, _)) => var(update),
+                        None => var(field),
+                    };
+
+                    term = apply(term, field);
+                }
+
+                if fields.is_empty() {
+                    term = apply(term, Term::Constant(Constant::Unit));
+                }
+
+                for case in (0..cases).rev() {
+                    term = lambda(&case_name(case), term);
+                }
+
+                term
+            })
+        } else {
+            let pair = self.fresh("record");
+            let last = updates.iter().map(|update| update.0).max().unwrap_or(0);
+
+            let tails = (0..=last).map(|_| self.fresh("fields")).collect::<Vec<_>>();
+
+            // The fields after the last update are shared with the old record.
+            let mut fields = builtin_call(DefaultFunction::TailList, vec![var(&tails[last])]);
+
+            for index in (0..=last).rev() {
+                let field = match updates.iter().find(|update| update.0 == index) {
+                    Some((_, update, _, field_tipo)) => self.wrap_data(var(update), field_tipo),
+                    None => builtin_call(DefaultFunction::HeadList, vec![var(&tails[index])]),
+                };
+
+                fields = builtin_call(DefaultFunction::MkCons, vec![field, fields]);
+
+                let tail = if index == 0 {
+                    builtin_call(DefaultFunction::SndPair, vec![var(&pair)])
+                } else {
+                    builtin_call(DefaultFunction::TailList, vec![var(&tails[index - 1])])
+                };
+
+                fields = apply(lambda(&tails[index], fields), tail);
+            }
+
+            let term = builtin_call(
+                DefaultFunction::ConstrData,
+                vec![
+                    builtin_call(DefaultFunction::FstPair, vec![var(&pair)]),
+                    fields,
+                ],
+            );
+
+            apply(
+                lambda(&pair, term),
+                builtin_call(DefaultFunction::UnConstrData, vec![record]),
+            )
+        };
+
+        for (_, update, value, _) in updates.into_iter().rev() {
+            term = apply(lambda(&update, term), value);
+        }
+
+        term
+    }
+
+    fn build_constr_index(&mut self, record: Term<Name>, tipo: &Arc<Type>) -> Term<Name> {
+        if self.repr(tipo) == Repr::Scott {
+            self.build_scott_match(record, tipo, |case, _| {
+                Term::Constant(Constant::Integer(case as i128))
+            })
+        } else {
+            constr_index(record)
+        }
+    }
+
+    /// Call a Scott encoded `record` with a handler for each constructor of
+    /// `tipo`, as produced by `handler` from the names bound to its fields.
+    fn build_scott_match(
+        &mut self,
+        record: Term<Name>,
+        tipo: &Arc<Type>,
+        handler: impl Fn(usize, &[String]) -> Term<Name>,
+    ) -> Term<Name> {
+        let mut term = record;
+
+        for (case, fields) in self.constructors(tipo).iter().enumerate() {
+            let field_names = fields
+                .iter()
+                .map(|_| self.fresh("field"))
+                .collect::<Vec<_>>();
+
+            let mut case_term = handler(case, &field_names);
+
+            if field_names.is_empty() {
+                case_term = lambda("_", case_term);
+            }
+
+            for field_name in field_names.iter().rev() {
+                case_term = lambda(field_name, case_term);
+            }
+
+            term = apply(term, case_term);
+        }
+
+        term
+    }
+
+    fn build_builtin(&mut self, builtin: DefaultFunction, tipo: &Arc<Type>) -> Term<Name> {
+        let arg_names = (0..builtin.arity())
+            .map(|_| self.fresh("arg"))
+            .collect::<Vec<_>>();
+
+        let (args, ret) = match self.resolve(tipo).as_ref() {
+            Type::Fn { args, ret } => (args.clone(), ret.clone()),
+            _ => unreachable!("builtins are always functions"),
+        };
+
+        let mut term = if is_list_builtin(builtin) && self.repr(&args[0]) == Repr::Scott {
+            self.build_scott_list_builtin(builtin, var(&arg_names[0]), &args[0])
+        } else {
+            builtin_call(builtin, arg_names.iter().map(|name| var(name)).collect())
+        };
+
+        if builtin == DefaultFunction::HeadList && self.repr(&args[0]) != Repr::Scott {
+            term = self.unwrap_data(term, &ret);
+        }
+
+        for arg_name in arg_names.iter().rev() {
+            term = lambda(arg_name, term);
+        }
+
+        term
+    }
+
+    fn build_builtin_call(
+        &mut self,
+        builtin: DefaultFunction,
+        args: &[CallArg<TypedExpr>],
+        tipo: &Arc<Type>,
+    ) -> Term<Name> {
+        if is_list_builtin(builtin) {
+            let list_tipo = args[0].value.tipo();
+
+            if self.repr(&list_tipo) == Repr::Scott {
+                let list = self.build(&args[0].value);
+
+                return self.build_scott_list_builtin(builtin, list, &list_tipo);
+            }
+        }
+
+        let args = args.iter().map(|arg| self.build(&arg.value)).collect();
+
+        let term = builtin_call(builtin, args);
+
+        // List elements are data, whatever the type of the list.
+        if builtin == DefaultFunction::HeadList {
+            self.unwrap_data(term, tipo)
+        } else {
+            term
+        }
+    }
+
+    /// `head_list`, `tail_list` or `null_list` of a Scott encoded `list`.
+    fn build_scott_list_builtin(
+        &mut self,
+        builtin: DefaultFunction,
+        list: Term<Name>,
+        tipo: &Arc<Type>,
+    ) -> Term<Name> {
+        self.build_scott_match(list, tipo, |case, fields| match (builtin, case) {
+            (DefaultFunction::NullList, _) => Term::Constant(Constant::Bool(case == 0)),
+            (DefaultFunction::HeadList, 1) => var(&fields[0]),
+            (DefaultFunction::TailList, 1) => var(&fields[1]),
+            _ => Term::Error,
+        })
+    }
+
+    /// Match the value held by `subject` against `pattern`, binding the
+    /// pattern variables around `success`. `failure` is evaluated when the
+    /// pattern doesn't match, it is copied so it must stay small.
+    fn build_pattern(
+        &mut self,
+        pattern: &TypedPattern,
+        subject: &str,
+        tipo: &Arc<Type>,
+        success: Term<Name>,
+        failure: &Term<Name>,
+    ) -> Term<Name> {
+        match pattern {
+            crate::ast::Pattern::Int { value, .. } => if_then_else(
+                builtin_call(
+                    DefaultFunction::EqualsInteger,
+                    vec![var(subject), build_int(value)],
+                ),
+                success,
+                failure.clone(),
+            ),
+            crate::ast::Pattern::String { value, .. } => if_then_else(
+                builtin_call(
+                    DefaultFunction::EqualsString,
+                    vec![
+                        var(subject),
+                        Term::Constant(Constant::String(value.clone())),
+                    ],
+                ),
+                success,
+                failure.clone(),
+            ),
+            crate::ast::Pattern::Var { name, .. } => apply(lambda(name, success), var(subject)),
+            crate::ast::Pattern::VarUsage { name, .. } => if_then_else(
+                builtin_call(
+                    DefaultFunction::EqualsInteger,
+                    vec![var(subject), var(name)],
+                ),
+                success,
+                failure.clone(),
+            ),
+            crate::ast::Pattern::Assign { name, pattern, .. } => {
+                let term = self.build_pattern(pattern, subject, tipo, success, failure);
+
+                apply(lambda(name, term), var(subject))
+            }
+            crate::ast::Pattern::Discard { .. } => success,
+            crate::ast::Pattern::List { elements, tail, .. } => {
+                let tipo = self.resolve(tipo);
+
+                let element_tipo = match tipo.as_ref() {
+                    Type::App {
+                        module, name, args, ..
+                    } if module.is_empty() && name == "List" => args[0].clone(),
+                    _ => unreachable!("list patterns always match lists"),
+                };
+
+                self.build_list_pattern(
+                    elements,
+                    tail.as_deref(),
+                    subject,
+                    (&tipo, &element_tipo),
+                    success,
+                    failure,
+                )
+            }
+            crate::ast::Pattern::Constructor {
+                name,
+                arguments,
+                tipo: constructor_tipo,
+                ..
+            } => {
+                let tipo = self.resolve(tipo);
+
+                if tipo.is_bool() {
+                    return if name == "True" {
+                        if_then_else(var(subject), success, failure.clone())
+                    } else {
+                        if_then_else(var(subject), failure.clone(), success)
+                    };
+                }
+
+                if tipo.is_nil() {
+                    return success;
+                }
+
+                let field_tipos = match self.resolve(constructor_tipo).as_ref() {
+                    Type::Fn { args, .. } => args.clone(),
+                    _ => vec![],
+                };
+
+                let scott = self.repr(&tipo) == Repr::Scott;
+                let index = self.constructor_index(name, &tipo);
+                let fields = self.fresh("fields");
+
+                let mut term = success;
+
+                for (field_index, (arg, field_tipo)) in
+                    arguments.iter().zip(field_tipos).enumerate().rev()
+                {
+                    let field_name = match &arg.value {
+                        crate::ast::Pattern::Discard { .. } => continue,
+                        crate::ast::Pattern::Var { name, .. } => name.clone(),
+                        _ => self.fresh("field"),
+                    };
+
+                    if !matches!(arg.value, crate::ast::Pattern::Var { .. }) {
+                        term =
+                            self.build_pattern(&arg.value, &field_name, &field_tipo, term, failure);
                     }
-                    .into(),
+
+                    let value = if scott {
+                        self.build_field(var(subject), &tipo, index, field_index, &field_tipo)
+                    } else {
+                        self.unwrap_data(nth(var(&fields), field_index), &field_tipo)
+                    };
+
+                    term = apply(lambda(&field_name, term), value);
+                }
+
+                if !scott && arguments.iter().any(|arg| !arg.value.is_discard()) {
+                    term = apply(lambda(&fields, term), constr_fields(var(subject)));
                 }
-                .into(),
+
+                if self.constructors(&tipo).len() > 1 {
+                    let constr_index = self.build_constr_index(var(subject), &tipo);
+
+                    term = if_then_else(
+                        builtin_call(
+                            DefaultFunction::EqualsInteger,
+                            vec![
+                                constr_index,
+                                Term::Constant(Constant::Integer(index as i128)),
+                            ],
+                        ),
+                        term,
+                        failure.clone(),
+                    );
+                }
+
+                term
             }
-            .into(),
         }
     }
 
-    // fn add_field_length_check(&self, term: Term<Name>) -> Term<Name> {
-    // Term::Apply {
-    //     function: Term::Lambda {
-    //         parameter_name: Name {
-    //             text: "field_length_check".to_string(),
-    //             unique: 0.into(),
-    //         },
-    //         body: term.into(),
-    //     }
-    //     .into(),
-    //     argument: Term::Lambda {
-    //         parameter_name: Name {
-    //             text: "expected_field_length".to_string(),
-    //             unique: 0.into(),
-    //         },
-    //         body: Term::Lambda {
-    //             parameter_name: Name {
-    //                 text: "type_to_check".to_string(),
-    //                 unique: 0.into(),
-    //             },
-    //             body: Term::Apply {
-    //                 function: Term::Apply {
-    //                     function: Term::Apply {
-    //                         function: Term::Force(
-    //                             Term::Builtin(DefaultFunction::IfThenElse).into(),
-    //                         )
-    //                         .into(),
-    //                         argument: Term::Apply {
-    //                             function: Term::Apply {
-    //                                 function: Term::Apply {
-    //                                     function: Term::Builtin(DefaultFunction::EqualsInteger),
-    //                                     argument: Term::Apply { function: , argument: () },
-    //                                 },
-    //                                 argument: (),
-    //                             },
-    //                             argument: (),
-    //                         },
-    //                     }
-    //                     .into(),
-    //                     argument: (),
-    //                 }
-    //                 .into(),
-    //                 argument: (),
-    //             }
-    //             .into(),
-    //         }
-    //         .into(),
-    //     }
-    //     .into(),
-    // }
-    // todo!()
-    // }
+    fn build_guard(&mut self, guard: &TypedClauseGuard) -> Term<Name> {
+        match guard {
+            ClauseGuard::Var { name, .. } => var(name),
+            ClauseGuard::Constant(constant) => self.build_constant(constant),
+            ClauseGuard::And { left, right, .. } => {
+                let left = self.build_guard(left);
+                let right = self.build_guard(right);
+
+                if_then_else(left, right, Term::Constant(Constant::Bool(false)))
+            }
+            ClauseGuard::Or { left, right, .. } => {
+                let left = self.build_guard(left);
+                let right = self.build_guard(right);
+
+                if_then_else(left, Term::Constant(Constant::Bool(true)), right)
+            }
+            ClauseGuard::Equals {
+                left,
+                right,
+                location,
+            } => {
+                let tipo = left.tipo();
+                let left = self.build_guard(left);
+                let right = self.build_guard(right);
+
+                self.build_equality(left, right, &tipo, *location)
+            }
+            ClauseGuard::NotEquals {
+                left,
+                right,
+                location,
+            } => {
+                let tipo = left.tipo();
+                let left = self.build_guard(left);
+                let right = self.build_guard(right);
+
+                if_then_else(
+                    self.build_equality(left, right, &tipo, *location),
+                    Term::Constant(Constant::Bool(false)),
+                    Term::Constant(Constant::Bool(true)),
+                )
+            }
+            ClauseGuard::LtInt { left, right, .. } => {
+                let left = self.build_guard(left);
+                let right = self.build_guard(right);
+
+                builtin_call(DefaultFunction::LessThanInteger, vec![left, right])
+            }
+            ClauseGuard::LtEqInt { left, right, .. } => {
+                let left = self.build_guard(left);
+                let right = self.build_guard(right);
+
+                builtin_call(DefaultFunction::LessThanEqualsInteger, vec![left, right])
+            }
+            ClauseGuard::GtInt { left, right, .. } => {
+                let left = self.build_guard(left);
+                let right = self.build_guard(right);
+
+                builtin_call(DefaultFunction::LessThanInteger, vec![right, left])
+            }
+            ClauseGuard::GtEqInt { left, right, .. } => {
+                let left = self.build_guard(left);
+                let right = self.build_guard(right);
+
+                builtin_call(DefaultFunction::LessThanEqualsInteger, vec![right, left])
+            }
+        }
+    }
+
+    fn build_list_pattern(
+        &mut self,
+        elements: &[TypedPattern],
+        tail: Option<&TypedPattern>,
+        subject: &str,
+        (tipo, element_tipo): (&Arc<Type>, &Arc<Type>),
+        success: Term<Name>,
+        failure: &Term<Name>,
+    ) -> Term<Name> {
+        if self.repr(tipo) == Repr::Scott {
+            return self.build_scott_list_pattern(
+                elements,
+                tail,
+                subject,
+                (tipo, element_tipo),
+                success,
+                failure,
+            );
+        }
+
+        let (element, elements) = match elements.split_first() {
+            Some(split) => split,
+            None => {
+                return match tail {
+                    Some(tail) => self.build_pattern(tail, subject, tipo, success, failure),
+                    None => if_then_else(
+                        builtin_call(DefaultFunction::NullList, vec![var(subject)]),
+                        success,
+                        failure.clone(),
+                    ),
+                };
+            }
+        };
+
+        let rest = self.fresh("rest");
+
+        let mut term = self.build_list_pattern(
+            elements,
+            tail,
+            &rest,
+            (tipo, element_tipo),
+            success,
+            failure,
+        );
+
+        term = apply(
+            lambda(&rest, term),
+            builtin_call(DefaultFunction::TailList, vec![var(subject)]),
+        );
+
+        if !element.is_discard() {
+            let head = self.fresh("head");
+
+            term = self.build_pattern(element, &head, element_tipo, term, failure);
+
+            let value = self.unwrap_data(
+                builtin_call(DefaultFunction::HeadList, vec![var(subject)]),
+                element_tipo,
+            );
+
+            term = apply(lambda(&head, term), value);
+        }
+
+        if_then_else(
+            builtin_call(DefaultFunction::NullList, vec![var(subject)]),
+            failure.clone(),
+            term,
+        )
+    }
+
+    /// Like `build_list_pattern`, for a Scott encoded list: the subject is
+    /// called with a handler for the empty list and one for its head and tail.
+    fn build_scott_list_pattern(
+        &mut self,
+        elements: &[TypedPattern],
+        tail: Option<&TypedPattern>,
+        subject: &str,
+        (tipo, element_tipo): (&Arc<Type>, &Arc<Type>),
+        success: Term<Name>,
+        failure: &Term<Name>,
+    ) -> Term<Name> {
+        let (element, elements) = match (elements.split_first(), tail) {
+            (Some(split), _) => split,
+            (None, Some(tail)) => return self.build_pattern(tail, subject, tipo, success, failure),
+            (None, None) => {
+                return apply(
+                    apply(var(subject), lambda("_", success)),
+                    lambda("_", lambda("_", failure.clone())),
+                )
+            }
+        };
+
+        let head = self.fresh("head");
+        let rest = self.fresh("rest");
+
+        let mut term = self.build_scott_list_pattern(
+            elements,
+            tail,
+            &rest,
+            (tipo, element_tipo),
+            success,
+            failure,
+        );
+
+        term = self.build_pattern(element, &head, element_tipo, term, failure);
+
+        apply(
+            apply(var(subject), lambda("_", failure.clone())),
+            lambda(&head, lambda(&rest, term)),
+        )
+    }
+
+    /// A function from `Data` to `Bool` telling whether the data is a well
+    /// formed value of `tipo`: constructor indexes, field counts and the fields
+    /// themselves are checked, down through lists and nested records.
+    ///
+    /// The validators of every type reachable from `tipo` are tied in one knot
+    /// so recursive types can be checked.
+    fn build_validator(&mut self, tipo: &Arc<Type>) -> Term<Name> {
+        let tipo = self.resolve(tipo);
+
+        let name = validator_name(&tipo);
+
+        if self.definitions.contains_key(&name) {
+            return var(&name);
+        }
+
+        let mut types = IndexMap::new();
+
+        self.reachable_types(&tipo, &mut types);
+
+        let knot_name = self.fresh("knot");
+
+        let members = types
+            .values()
+            .map(|tipo| self.validator_body(tipo, &knot_name, &types))
+            .collect();
+
+        let index = self.fresh("index");
+
+        self.definitions
+            .insert(knot_name.clone(), tie_knot(&knot_name, &index, members));
+
+        for (i, key) in types.keys().enumerate() {
+            if !self.definitions.contains_key(key) {
+                self.definitions
+                    .insert(key.clone(), knot_member(&knot_name, i));
+            }
+        }
+
+        var(&name)
+    }
+
+    fn reachable_types(&self, tipo: &Arc<Type>, types: &mut IndexMap<String, Arc<Type>>) {
+        let name = validator_name(tipo);
+
+        if types.contains_key(&name) {
+            return;
+        }
+
+        types.insert(name, tipo.clone());
+
+        match self.repr(tipo) {
+            Repr::List => {
+                if let Type::App { args, .. } = tipo.as_ref() {
+                    self.reachable_types(&args[0], types);
+                }
+            }
+            Repr::Data if has_constructors(tipo) => {
+                for field in self.constructors(tipo).iter().flatten() {
+                    self.reachable_types(field, types);
+                }
+            }
+            _ => {}
+        }
+    }
+
+    fn validator_body(
+        &self,
+        tipo: &Arc<Type>,
+        knot: &str,
+        types: &IndexMap<String, Arc<Type>>,
+    ) -> Term<Name> {
+        let validator = |tipo: &Arc<Type>| {
+            knot_member(
+                knot,
+                types
+                    .get_index_of(&validator_name(tipo))
+                    .expect("every reachable type has a validator"),
+            )
+        };
+
+        let valid = Term::Constant(Constant::Bool(true));
+        let invalid = || Term::Constant(Constant::Bool(false));
+
+        let body = match self.repr(tipo) {
+            Repr::Integer => choose_data(
+                var("__data"),
+                [invalid(), invalid(), invalid(), valid, invalid()],
+            ),
+            Repr::ByteString | Repr::String => choose_data(
+                var("__data"),
+                [invalid(), invalid(), invalid(), invalid(), valid],
+            ),
+            Repr::Bool | Repr::Unit => {
+                let max_index = if self.repr(tipo) == Repr::Bool { 1 } else { 0 };
+
+                let is_valid = and(
+                    builtin_call(
+                        DefaultFunction::LessThanEqualsInteger,
+                        vec![
+                            constr_index(var("__data")),
+                            Term::Constant(Constant::Integer(max_index)),
+                        ],
+                    ),
+                    builtin_call(
+                        DefaultFunction::NullList,
+                        vec![constr_fields(var("__data"))],
+                    ),
+                );
+
+                choose_data(
+                    var("__data"),
+                    [is_valid, invalid(), invalid(), invalid(), invalid()],
+                )
+            }
+            Repr::List => {
+                let element = match tipo.as_ref() {
+                    Type::App { args, .. } => &args[0],
+                    _ => unreachable!("lists are type applications"),
+                };
+
+                let elements = apply(
+                    lambda(
+                        "__go",
+                        apply(
+                            apply(var("__go"), var("__go")),
+                            builtin_call(DefaultFunction::UnListData, vec![var("__data")]),
+                        ),
+                    ),
+                    lambda(
+                        "__go",
+                        lambda(
+                            "__elements",
+                            choose_list(
+                                var("__elements"),
+                                valid,
+                                and(
+                                    apply(
+                                        validator(element),
+                                        builtin_call(
+                                            DefaultFunction::HeadList,
+                                            vec![var("__elements")],
+                                        ),
+                                    ),
+                                    apply(
+                                        apply(var("__go"), var("__go")),
+                                        builtin_call(
+                                            DefaultFunction::TailList,
+                                            vec![var("__elements")],
+                                        ),
+                                    ),
+                                ),
+                            ),
+                        ),
+                    ),
+                );
+
+                choose_data(
+                    var("__data"),
+                    [invalid(), invalid(), elements, invalid(), invalid()],
+                )
+            }
+            Repr::Data if has_constructors(tipo) => {
+                let constructors = self.constructors(tipo);
+
+                let is_valid = constructors.iter().enumerate().rev().fold(
+                    invalid(),
+                    |otherwise, (index, fields)| {
+                        let fields_valid = fields.iter().rev().fold(
+                            builtin_call(DefaultFunction::NullList, vec![var("__fields")]),
+                            |rest, field| {
+                                choose_list(
+                                    var("__fields"),
+                                    invalid(),
+                                    and(
+                                        apply(
+                                            validator(field),
+                                            builtin_call(
+                                                DefaultFunction::HeadList,
+                                                vec![var("__fields")],
+                                            ),
+                                        ),
+                                        apply(
+                                            lambda("__fields", rest),
+                                            builtin_call(
+                                                DefaultFunction::TailList,
+                                                vec![var("__fields")],
+                                            ),
+                                        ),
+                                    ),
+                                )
+                            },
+                        );
+
+                        if_then_else(
+                            builtin_call(
+                                DefaultFunction::EqualsInteger,
+                                vec![
+                                    constr_index(var("__data")),
+                                    Term::Constant(Constant::Integer(index as i128)),
+                                ],
+                            ),
+                            apply(
+                                lambda("__fields", fields_valid),
+                                constr_fields(var("__data")),
+                            ),
+                            otherwise,
+                        )
+                    },
+                );
+
+                choose_data(
+                    var("__data"),
+                    [is_valid, invalid(), invalid(), invalid(), invalid()],
+                )
+            }
+            // Data, and values that are never decoded from data.
+            _ => valid,
+        };
+
+        lambda("__data", body)
+    }
+
+    fn constructor_index(&self, name: &str, tipo: &Arc<Type>) -> usize {
+        match self.resolve(tipo).as_ref() {
+            Type::App {
+                module,
+                name: type_name,
+                ..
+            } if module.is_empty() && type_name == "Option" => {
+                if name == "Some" {
+                    0
+                } else {
+                    1
+                }
+            }
+            Type::App {
+                module,
+                name: type_name,
+                ..
+            } => self
+                .data_type(module, type_name)
+                .constructors
+                .iter()
+                .position(|constructor| constructor.name == name)
+                .unwrap_or_else(|| panic!("unknown constructor {} of {}", name, type_name)),
+            _ => unreachable!("records always have a named type"),
+        }
+    }
+
+    /// The field types of each constructor of `tipo`.
+    fn constructors(&self, tipo: &Arc<Type>) -> Vec<Vec<Arc<Type>>> {
+        match self.resolve(tipo).as_ref() {
+            Type::App {
+                module, name, args, ..
+            } if module.is_empty() && name == "Option" => vec![args.clone(), vec![]],
+            // Only Scott encoded lists are matched on as records.
+            Type::App {
+                module, name, args, ..
+            } if module.is_empty() && name == "List" => {
+                vec![vec![], vec![args[0].clone(), self.resolve(tipo)]]
+            }
+            Type::App {
+                module, name, args, ..
+            } => {
+                let data_type = self.data_type(module, name);
+
+                let mut mapping = HashMap::new();
+
+                for (parameter, arg) in data_type.typed_parameters.iter().zip(args) {
+                    match_generics(parameter, arg, &mut mapping);
+                }
+
+                data_type
+                    .constructors
+                    .iter()
+                    .map(|constructor| {
+                        constructor
+                            .arguments
+                            .iter()
+                            .map(|arg| resolve(&arg.tipo, &mapping))
+                            .collect()
+                    })
+                    .collect()
+            }
+            _ => unreachable!("records always have a named type"),
+        }
+    }
+
+    /// Whether values of `tipo` may hold a function, in which case they can't
+    /// be represented as data.
+    fn holds_functions(&self, tipo: &Arc<Type>, visited: &mut Vec<String>) -> bool {
+        match tipo.as_ref() {
+            Type::Fn { .. } => true,
+            Type::App {
+                module, name, args, ..
+            } if module.is_empty() && name == "List" => self.holds_functions(&args[0], visited),
+            Type::App { module, name, .. } if module.is_empty() && name != "Option" => false,
+            Type::App { .. } => {
+                let key = type_key(tipo);
+
+                if visited.contains(&key) {
+                    return false;
+                }
+
+                visited.push(key);
+
+                self.constructors(tipo)
+                    .iter()
+                    .flatten()
+                    .any(|field| self.holds_functions(field, visited))
+            }
+            Type::Var { .. } => false,
+        }
+    }
+
+    fn data_type(&self, module: &str, name: &str) -> &'a DataType<Arc<Type>> {
+        self.data_types
+            .get(&DataTypeKey {
+                module_name: module.to_string(),
+                defined_type: name.to_string(),
+            })
+            .unwrap_or_else(|| panic!("unknown data type {}.{}", module, name))
+    }
+
+    fn wrap_data(&self, term: Term<Name>, tipo: &Arc<Type>) -> Term<Name> {
+        match self.repr(tipo) {
+            Repr::Integer => builtin_call(DefaultFunction::IData, vec![term]),
+            Repr::ByteString => builtin_call(DefaultFunction::BData, vec![term]),
+            Repr::String => builtin_call(
+                DefaultFunction::BData,
+                vec![builtin_call(DefaultFunction::EncodeUtf8, vec![term])],
+            ),
+            Repr::Bool => builtin_call(
+                DefaultFunction::IfThenElse,
+                vec![term, constr(1, vec![]), constr(0, vec![])],
+            ),
+            Repr::Unit => apply(lambda("_", constr(0, vec![])), term),
+            Repr::List => builtin_call(DefaultFunction::ListData, vec![term]),
+            Repr::Data => term,
+            // Lists and records holding them are Scott encoded, and they are
+            // never compared or decoded from data.
+            Repr::Function | Repr::Scott => {
+                unreachable!("values holding functions are never converted to data")
+            }
+        }
+    }
+
+    fn unwrap_data(&self, term: Term<Name>, tipo: &Arc<Type>) -> Term<Name> {
+        match self.repr(tipo) {
+            Repr::Integer => builtin_call(DefaultFunction::UnIData, vec![term]),
+            Repr::ByteString => builtin_call(DefaultFunction::UnBData, vec![term]),
+            Repr::String => builtin_call(
+                DefaultFunction::DecodeUtf8,
+                vec![builtin_call(DefaultFunction::UnBData, vec![term])],
+            ),
+            Repr::Bool => builtin_call(
+                DefaultFunction::EqualsInteger,
+                vec![constr_index(term), Term::Constant(Constant::Integer(1))],
+            ),
+            Repr::Unit => apply(lambda("_", Term::Constant(Constant::Unit)), term),
+            Repr::List => builtin_call(DefaultFunction::UnListData, vec![term]),
+            Repr::Data => term,
+            // Lists and records holding them are Scott encoded, and they are
+            // never compared or decoded from data.
+            Repr::Function | Repr::Scott => {
+                unreachable!("values holding functions are never converted to data")
+            }
+        }
+    }
+
+    fn repr(&self, tipo: &Arc<Type>) -> Repr {
+        let tipo = self.resolve(tipo);
+
+        match tipo.as_ref() {
+            Type::App { module, name, .. } if module.is_empty() => match name.as_str() {
+                "Int" => Repr::Integer,
+                "ByteArray" => Repr::ByteString,
+                "String" => Repr::String,
+                "Bool" => Repr::Bool,
+                "Nil" => Repr::Unit,
+                "List" | "Option" if self.holds_functions(&tipo, &mut vec![]) => Repr::Scott,
+                "List" => Repr::List,
+                _ => Repr::Data,
+            },
+            Type::App { .. } if self.holds_functions(&tipo, &mut vec![]) => Repr::Scott,
+            Type::Fn { .. } => Repr::Function,
+            _ => Repr::Data,
+        }
+    }
+
+    /// Substitute the type variables of the function being compiled.
+    fn resolve(&self, tipo: &Arc<Type>) -> Arc<Type> {
+        resolve(tipo, &self.type_mapping)
+    }
+
+    /// The mutually recursive group `key` belongs to, if any. Functions that
+    /// only call themselves are not part of a group.
+    fn recursion_group(&mut self, key: &FunctionAccessKey) -> Option<usize> {
+        let functions = self.functions;
+
+        self.recursion_groups
+            .get_or_insert_with(|| recursion_groups(functions))
+            .get(key)
+            .copied()
+    }
+
+    fn fresh(&mut self, prefix: &str) -> String {
+        self.id += 1;
+
+        format!("__{}_{}", prefix, self.id)
+    }
+}
+
+/// Find the groups of mutually recursive functions, as the strongly connected
+/// components of the call graph with more than one member.
+fn recursion_groups(
+    functions: &HashMap<FunctionAccessKey, &TypedFunction>,
+) -> HashMap<FunctionAccessKey, usize> {
+    let mut graph = Graph::<&FunctionAccessKey, ()>::new();
+
+    let indices: HashMap<&FunctionAccessKey, NodeIndex> = functions
+        .keys()
+        .map(|key| (key, graph.add_node(key)))
+        .collect();
+
+    for (key, func) in functions {
+        let mut callees = Vec::new();
+
+        collect_calls(&func.body, &mut callees);
+
+        for callee in callees {
+            if let Some(callee) = indices.get(&callee) {
+                graph.update_edge(indices[key], *callee, ());
+            }
+        }
+    }
+
+    let mut groups = HashMap::new();
+
+    for (group, component) in tarjan_scc(&graph)
+        .into_iter()
+        .filter(|component| component.len() > 1)
+        .enumerate()
+    {
+        for node in component {
+            groups.insert(graph[node].clone(), group);
+        }
+    }
+
+    groups
+}
+
+/// Whether values of `tipo` hold functions anywhere, in a record field, a
+/// list or an instance of a generic type, and so can't be represented as data.
+pub fn holds_functions(
+    tipo: &Arc<Type>,
+    data_types: &HashMap<DataTypeKey, &DataType<Arc<tipo::Type>>>,
+) -> bool {
+    let functions = HashMap::new();
+
+    CodeGenerator::new(&functions, data_types, false)
+        .holds_functions(&resolve(tipo, &HashMap::new()), &mut vec![])
+}
+
+/// Collect the names of the variables `pattern` binds.
+fn pattern_variables(pattern: &TypedPattern, variables: &mut BTreeSet<String>) {
+    match pattern {
+        crate::ast::Pattern::Var { name, .. } => {
+            variables.insert(name.clone());
+        }
+        crate::ast::Pattern::Assign { name, pattern, .. } => {
+            variables.insert(name.clone());
+
+            pattern_variables(pattern, variables);
+        }
+        crate::ast::Pattern::List { elements, tail, .. } => {
+            for element in elements.iter().chain(tail.as_deref()) {
+                pattern_variables(element, variables);
+            }
+        }
+        crate::ast::Pattern::Constructor { arguments, .. } => {
+            for argument in arguments {
+                pattern_variables(&argument.value, variables);
+            }
+        }
+        crate::ast::Pattern::Int { .. }
+        | crate::ast::Pattern::String { .. }
+        | crate::ast::Pattern::VarUsage { .. }
+        | crate::ast::Pattern::Discard { .. } => {}
+    }
+}
+
+/// Collect the module functions `expr` refers to.
+fn collect_calls(expr: &TypedExpr, calls: &mut Vec<FunctionAccessKey>) {
+    match expr {
+        TypedExpr::Int { .. }
+        | TypedExpr::String { .. }
+        | TypedExpr::ByteArray { .. }
+        | TypedExpr::Todo { .. }
+        | TypedExpr::ErrorTerm { .. } => {}
+        TypedExpr::Var { constructor, .. } => {
+            if let ValueConstructorVariant::ModuleFn {
+                name,
+                module,
+                builtin: None,
+                ..
+            } = &constructor.variant
+            {
+                calls.push(FunctionAccessKey {
+                    module_name: module.clone(),
+                    function_name: name.clone(),
+                });
+            }
+        }
+        TypedExpr::ModuleSelect { constructor, .. } => {
+            if let ModuleValueConstructor::Fn { module, name, .. } = constructor {
+                calls.push(FunctionAccessKey {
+                    module_name: module.clone(),
+                    function_name: name.clone(),
+                });
+            }
+        }
+        TypedExpr::Sequence { expressions, .. } | TypedExpr::Pipeline { expressions, .. } => {
+            for expression in expressions {
+                collect_calls(expression, calls);
+            }
+        }
+        TypedExpr::Fn { body, .. } | TypedExpr::Trace { then: body, .. } => {
+            collect_calls(body, calls)
+        }
+        TypedExpr::List { elements, tail, .. } => {
+            for element in elements {
+                collect_calls(element, calls);
+            }
+
+            if let Some(tail) = tail {
+                collect_calls(tail, calls);
+            }
+        }
+        TypedExpr::Call { fun, args, .. } => {
+            collect_calls(fun, calls);
+
+            for arg in args {
+                collect_calls(&arg.value, calls);
+            }
+        }
+        TypedExpr::BinOp { left, right, .. } => {
+            collect_calls(left, calls);
+            collect_calls(right, calls);
+        }
+        TypedExpr::Assignment { value, .. } => collect_calls(value, calls),
+        TypedExpr::Try { value, then, .. } => {
+            collect_calls(value, calls);
+            collect_calls(then, calls);
+        }
+        TypedExpr::When {
+            subjects, clauses, ..
+        } => {
+            for subject in subjects {
+                collect_calls(subject, calls);
+            }
+
+            for clause in clauses {
+                collect_calls(&clause.then, calls);
+            }
+        }
+        TypedExpr::If {
+            branches,
+            final_else,
+            ..
+        } => {
+            for branch in branches {
+                collect_calls(&branch.condition, calls);
+                collect_calls(&branch.body, calls);
+            }
+
+            collect_calls(final_else, calls);
+        }
+        TypedExpr::RecordAccess { record, .. } => collect_calls(record, calls),
+        TypedExpr::RecordUpdate { spread, args, .. } => {
+            collect_calls(spread, calls);
+
+            for arg in args {
+                collect_calls(&arg.value, calls);
+            }
+        }
+        TypedExpr::Negate { value, .. } | TypedExpr::NegateInt { value, .. } => {
+            collect_calls(value, calls)
+        }
+    }
+}
+
+/// Record in `mapping` what each type variable of `declared` stands for in
+/// `concrete`.
+fn match_generics(
+    declared: &Arc<Type>,
+    concrete: &Arc<Type>,
+    mapping: &mut HashMap<u64, Arc<Type>>,
+) {
+    match (declared.as_ref(), concrete.as_ref()) {
+        (Type::Var { tipo }, _) => match &*tipo.borrow() {
+            TypeVar::Link { tipo } => match_generics(tipo, concrete, mapping),
+            TypeVar::Unbound { id } | TypeVar::Generic { id } => {
+                if !matches!(concrete.as_ref(), Type::Var { .. }) {
+                    mapping.insert(*id, concrete.clone());
+                }
+            }
+        },
+        (
+            Type::App { args, .. },
+            Type::App {
+                args: concrete_args,
+                ..
+            },
+        ) => {
+            for (arg, concrete_arg) in args.iter().zip(concrete_args) {
+                match_generics(arg, concrete_arg, mapping);
+            }
+        }
+        (
+            Type::Fn { args, ret },
+            Type::Fn {
+                args: concrete_args,
+                ret: concrete_ret,
+            },
+        ) => {
+            for (arg, concrete_arg) in args.iter().zip(concrete_args) {
+                match_generics(arg, concrete_arg, mapping);
+            }
+
+            match_generics(ret, concrete_ret, mapping);
+        }
+        _ => {}
+    }
+}
+
+fn resolve(tipo: &Arc<Type>, mapping: &HashMap<u64, Arc<Type>>) -> Arc<Type> {
+    match tipo.as_ref() {
+        Type::App {
+            public,
+            module,
+            name,
+            args,
+        } => Type::App {
+            public: *public,
+            module: module.clone(),
+            name: name.clone(),
+            args: args.iter().map(|arg| resolve(arg, mapping)).collect(),
+        }
+        .into(),
+        Type::Fn { args, ret } => function(
+            args.iter().map(|arg| resolve(arg, mapping)).collect(),
+            resolve(ret, mapping),
+        ),
+        Type::Var { tipo: type_var } => match &*type_var.borrow() {
+            TypeVar::Link { tipo } => resolve(tipo, mapping),
+            TypeVar::Unbound { id } | TypeVar::Generic { id } => {
+                mapping.get(id).cloned().unwrap_or_else(|| tipo.clone())
+            }
+        },
+    }
+}
+
+fn specialised_name(module: &str, name: &str, mapping: &HashMap<u64, Arc<Type>>) -> String {
+    let mut specialised_name = format!("{}_{}", module.replace('/', "_"), name);
+
+    let mut ids = mapping.keys().collect::<Vec<_>>();
+
+    ids.sort();
+
+    for id in ids {
+        specialised_name.push('_');
+        specialised_name.push_str(&type_key(&mapping[id]));
+    }
+
+    specialised_name
+}
+
+/// A name for `tipo`, unique across modules, to build the names of the
+/// definitions specialised for it.
+fn type_key(tipo: &Type) -> String {
+    match tipo {
+        Type::App {
+            module, name, args, ..
+        } => {
+            let mut key = if module.is_empty() {
+                name.clone()
+            } else {
+                format!("{}_{}", module.replace('/', "_"), name)
+            };
+
+            for arg in args {
+                key.push('_');
+                key.push_str(&type_key(arg));
+            }
+
+            key
+        }
+        Type::Fn { args, ret } => {
+            let mut key = "fn".to_string();
+
+            for arg in args {
+                key.push('_');
+                key.push_str(&type_key(arg));
+            }
+
+            key.push('_');
+            key.push_str(&type_key(ret));
+
+            key
+        }
+        Type::Var { tipo } => match &*tipo.borrow() {
+            TypeVar::Link { tipo } => type_key(tipo),
+            _ => "Data".to_string(),
+        },
+    }
+}
+
+fn function_lambda(args: &[TypedArg], body: Term<Name>) -> Term<Name> {
+    let mut term = body;
+
+    // Functions without arguments are called with unit.
+    if args.is_empty() {
+        term = lambda("_", term);
+    }
+
+    for arg in args.iter().rev() {
+        term = lambda(arg.arg_name.get_variable_name().unwrap_or("_"), term);
+    }
+
+    term
+}
+
+/// `\knot -> \index -> member_index`, where members refer to one another
+/// through `knot_member`.
+fn tie_knot(name: &str, index: &str, members: Vec<Term<Name>>) -> Term<Name> {
+    let mut members = members.into_iter().enumerate().rev();
+
+    let (_, last) = members.next().expect("knots are never empty");
+
+    let selection = members.fold(last, |otherwise, (i, member)| {
+        if_then_else(
+            builtin_call(
+                DefaultFunction::EqualsInteger,
+                vec![var(index), Term::Constant(Constant::Integer(i as i128))],
+            ),
+            member,
+            otherwise,
+        )
+    });
+
+    lambda(name, lambda(index, selection))
+}
+
+fn knot_member(knot: &str, index: usize) -> Term<Name> {
+    apply(
+        apply(var(knot), var(knot)),
+        Term::Constant(Constant::Integer(index as i128)),
+    )
+}
+
+/// Whether `tipo` is `Option` or a custom type, as opposed to a prelude
+/// scalar, a list or `Data`.
+fn has_constructors(tipo: &Type) -> bool {
+    match tipo {
+        Type::App { module, name, .. } => !module.is_empty() || name == "Option",
+        _ => false,
+    }
+}
+
+/// Whether `builtin` takes a list of any type, which may be Scott encoded.
+fn is_list_builtin(builtin: DefaultFunction) -> bool {
+    matches!(
+        builtin,
+        DefaultFunction::HeadList | DefaultFunction::TailList | DefaultFunction::NullList
+    )
+}
+
+fn validator_name(tipo: &Type) -> String {
+    format!("__valid_{}", type_key(tipo))
+}
+
+fn case_name(index: usize) -> String {
+    format!("__case_{}", index)
+}
+
+fn builtin_by_name(name: &str) -> DefaultFunction {
+    DefaultFunction::iter()
+        .find(|builtin| builtin.aiken_name() == name)
+        .unwrap_or_else(|| panic!("unknown builtin {}", name))
+}
+
+fn build_int(literal: &str) -> Term<Name> {
+    let value = int_value(literal).expect("integer literals are checked by the lexer");
+
+    Term::Constant(Constant::Integer(value))
+}
+
+fn name(text: &str) -> Name {
+    Name {
+        text: text.to_string(),
+        unique: Unique::new(0),
+    }
+}
+
+fn var(text: &str) -> Term<Name> {
+    Term::Var(name(text))
+}
+
+fn lambda(parameter_name: &str, body: Term<Name>) -> Term<Name> {
+    Term::Lambda {
+        parameter_name: name(parameter_name),
+        body: Rc::new(body),
+    }
+}
+
+fn apply(function: Term<Name>, argument: Term<Name>) -> Term<Name> {
+    Term::Apply {
+        function: Rc::new(function),
+        argument: Rc::new(argument),
+    }
+}
+
+fn builtin_call(builtin: DefaultFunction, args: Vec<Term<Name>>) -> Term<Name> {
+    let mut term = Term::Builtin(builtin);
+
+    for _ in 0..builtin.force_count() {
+        term = Term::Force(term.into());
+    }
+
+    args.into_iter().fold(term, apply)
+}
+
+fn if_then_else(condition: Term<Name>, then: Term<Name>, otherwise: Term<Name>) -> Term<Name> {
+    Term::Force(
+        builtin_call(
+            DefaultFunction::IfThenElse,
+            vec![
+                condition,
+                Term::Delay(then.into()),
+                Term::Delay(otherwise.into()),
+            ],
+        )
+        .into(),
+    )
+}
+
+/// Trace `message` before evaluating `then`.
+fn trace(message: &str, then: Term<Name>) -> Term<Name> {
+    Term::Force(
+        builtin_call(
+            DefaultFunction::Trace,
+            vec![
+                Term::Constant(Constant::String(message.to_string())),
+                Term::Delay(then.into()),
+            ],
+        )
+        .into(),
+    )
+}
+
+fn and(left: Term<Name>, right: Term<Name>) -> Term<Name> {
+    if_then_else(left, right, Term::Constant(Constant::Bool(false)))
+}
+
+/// Branch on the kind of `data`: constructor, map, list, integer or bytes.
+fn choose_data(data: Term<Name>, branches: [Term<Name>; 5]) -> Term<Name> {
+    let mut args = vec![data];
+
+    args.extend(
+        branches
+            .into_iter()
+            .map(|branch| Term::Delay(branch.into())),
+    );
+
+    Term::Force(builtin_call(DefaultFunction::ChooseData, args).into())
+}
+
+fn choose_list(list: Term<Name>, empty: Term<Name>, non_empty: Term<Name>) -> Term<Name> {
+    Term::Force(
+        builtin_call(
+            DefaultFunction::ChooseList,
+            vec![
+                list,
+                Term::Delay(empty.into()),
+                Term::Delay(non_empty.into()),
+            ],
+        )
+        .into(),
+    )
+}
+
+fn constr(index: usize, fields: Vec<Term<Name>>) -> Term<Name> {
+    let fields = fields.into_iter().rev().fold(
+        Term::Constant(Constant::ProtoList(UplcType::Data, vec![])),
+        |list, field| builtin_call(DefaultFunction::MkCons, vec![field, list]),
+    );
+
+    builtin_call(
+        DefaultFunction::ConstrData,
+        vec![Term::Constant(Constant::Integer(index as i128)), fields],
+    )
+}
+
+fn constr_index(term: Term<Name>) -> Term<Name> {
+    builtin_call(
+        DefaultFunction::FstPair,
+        vec![builtin_call(DefaultFunction::UnConstrData, vec![term])],
+    )
+}
+
+fn constr_fields(term: Term<Name>) -> Term<Name> {
+    builtin_call(
+        DefaultFunction::SndPair,
+        vec![builtin_call(DefaultFunction::UnConstrData, vec![term])],
+    )
+}
+
+fn nth(list: Term<Name>, index: usize) -> Term<Name> {
+    let tail = (0..index).fold(list, |list, _| {
+        builtin_call(DefaultFunction::TailList, vec![list])
+    });
+
+    builtin_call(DefaultFunction::HeadList, vec![tail])
 }
@@ -0,0 +1,49 @@
+use std::sync::Arc;
+
+use miette::Diagnostic;
+
+use crate::{ast::Span, tipo::Type};
+
+/// Why a well-typed program can't be compiled: values holding functions,
+/// which can't be turned into data, and `try` expressions.
+#[derive(Debug, thiserror::Error, Diagnostic)]
+pub enum Error {
+    #[error("values of type {} can't be compared", .tipo.to_pretty(0))]
+    #[diagnostic(help("They hold functions, which have no equality."))]
+    FunctionComparison {
+        module: String,
+        #[label]
+        location: Span,
+        tipo: Arc<Type>,
+    },
+
+    #[error("data can't be cast into {}", .tipo.to_pretty(0))]
+    #[diagnostic(help("It holds functions, which can't be decoded from data."))]
+    FunctionFromData {
+        module: String,
+        #[label]
+        location: Span,
+        tipo: Arc<Type>,
+    },
+
+    #[error("try expressions can't be compiled")]
+    #[diagnostic(help(
+        "There are no Result values to unwrap, match on the value with `when` instead."
+    ))]
+    Try {
+        module: String,
+        #[label]
+        location: Span,
+    },
+}
+
+impl Error {
+    /// The module the error was found in.
+    pub fn module(&self) -> &str {
+        match self {
+            Error::FunctionComparison { module, .. }
+            | Error::FunctionFromData { module, .. }
+            | Error::Try { module, .. } => module,
+        }
+    }
+}
@@ -0,0 +1,52 @@
+//! What validators are known by on chain: the address of spending
+//! validators, the reward address of withdrawal and publishing validators and
+//! the policy id of minting validators, all derived from their script hash.
+
+use pallas::{
+    crypto::hash::Hash,
+    ledger::addresses::{self, Address, ShelleyAddress, ShelleyDelegationPart, ShelleyPaymentPart},
+};
+
+use crate::config::{Network, StakeCredential};
+
+impl From<Network> for addresses::Network {
+    fn from(network: Network) -> Self {
+        match network {
+            Network::Mainnet => addresses::Network::Mainnet,
+            Network::Testnet => addresses::Network::Testnet,
+        }
+    }
+}
+
+/// The address locking funds with the script `hash`. Staking rights go to
+/// `stake` when given, otherwise it's an enterprise address.
+pub fn payment_address(
+    hash: Hash<28>,
+    network: Network,
+    stake: Option<StakeCredential>,
+) -> ShelleyAddress {
+    let delegation = match stake {
+        Some(StakeCredential::Key(key)) => ShelleyDelegationPart::Key(key),
+        Some(StakeCredential::Script(script)) => ShelleyDelegationPart::Script(script),
+        None => ShelleyDelegationPart::Null,
+    };
+
+    ShelleyAddress::new(network.into(), ShelleyPaymentPart::Script(hash), delegation)
+}
+
+/// The reward address of the script `hash`, which rewards are withdrawn from
+/// and certificates are published for.
+pub fn stake_address(hash: Hash<28>, network: Network) -> Address {
+    // Stake addresses can't be built directly: their header is the type of
+    // a script stake address, 0b1111, followed by the network id.
+    let header = 0b1111_0000 | addresses::Network::from(network).value();
+
+    Address::from_bytes(&[&[header], hash.as_ref()].concat())
+        .expect("a script hash makes a valid stake address")
+}
+
+/// The policy id of the tokens minted by the script `hash`, which is the hash
+/// itself.
+pub fn policy_id(hash: Hash<28>) -> String {
+    hex::encode(hash)
+}
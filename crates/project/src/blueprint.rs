@@ -0,0 +1,590 @@
+//! A [CIP-57](https://cips.cardano.org/cips/cip57/) blueprint, the
+//! `plutus.json` describing the validators of a project along with the
+//! schemas of their datum, redeemer and parameters.
+
+use std::{
+    collections::{BTreeMap, HashMap},
+    sync::Arc,
+};
+
+use aiken_lang::{
+    ast::{DataType, TypedArg, TypedFunction},
+    tipo::{Type, TypeVar},
+    uplc::DataTypeKey,
+};
+use pallas::ledger::primitives::babbage::PlutusData;
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+use uplc::{
+    ast::{DeBruijn, Program},
+    json::plutus_data_to_json,
+};
+
+use crate::{
+    config::{Config, PlutusVersion},
+    error::Error,
+    script::Script,
+    MINT, PUBLISH, SPEND, WITHDRAW,
+};
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct Blueprint {
+    pub preamble: Preamble,
+    pub validators: Vec<Validator>,
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub definitions: BTreeMap<String, Value>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Preamble {
+    pub title: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub description: Option<String>,
+    pub version: String,
+    pub plutus_version: PlutusVersion,
+    pub compiler: Compiler,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct Compiler {
+    pub name: String,
+    pub version: String,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Validator {
+    pub title: String,
+    pub purpose: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub datum: Option<Argument>,
+    pub redeemer: Argument,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub parameters: Vec<Argument>,
+    pub compiled_code: String,
+    pub hash: String,
+    /// Set when the validator isn't compiled for the version of the preamble
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub plutus_version: Option<PlutusVersion>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct Argument {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub title: Option<String>,
+    pub schema: Value,
+}
+
+impl Blueprint {
+    /// Describe `validators`, compiled into `scripts` in the same order.
+    /// Validators are sorted by title so that the output is the same from one
+    /// build to the next.
+    pub fn new(
+        config: &Config,
+        validators: &[(String, TypedFunction)],
+        scripts: &[Script],
+        data_types: &HashMap<DataTypeKey, &DataType<Arc<Type>>>,
+    ) -> Result<Blueprint, Error> {
+        let mut schemas = Schemas::new(data_types);
+
+        let mut validators = validators
+            .iter()
+            .zip(scripts)
+            .map(|((module, func), script)| {
+                Validator::new(module, func, script, config.plutus, &mut schemas)
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+
+        validators.sort_by(|a, b| a.title.cmp(&b.title));
+
+        Ok(Blueprint {
+            preamble: Preamble {
+                title: config.name.clone(),
+                description: Some(config.description.clone()).filter(|d| !d.is_empty()),
+                version: config.version.clone(),
+                plutus_version: config.plutus,
+                compiler: Compiler {
+                    name: "Aiken".to_string(),
+                    version: env!("CARGO_PKG_VERSION").to_string(),
+                },
+            },
+            validators,
+            definitions: schemas.definitions,
+        })
+    }
+
+    /// Apply `params` to the leading parameters of the validator `title`,
+    /// returning the CBOR of the applied script along with its Plutus version.
+    /// The validator is updated in place and keeps its remaining parameters,
+    /// if any.
+    pub fn apply(
+        &mut self,
+        title: &str,
+        params: &[PlutusData],
+    ) -> Result<(Vec<u8>, PlutusVersion), Error> {
+        let known = self
+            .validators
+            .iter()
+            .map(|validator| validator.title.clone())
+            .collect();
+
+        let validator = self
+            .validators
+            .iter_mut()
+            .find(|validator| validator.title == title)
+            .ok_or_else(|| Error::UnknownValidator {
+                title: title.to_string(),
+                known,
+            })?;
+
+        if params.len() > validator.parameters.len() {
+            return Err(Error::TooManyParameters {
+                title: validator.title.clone(),
+                expected: validator.parameters.len(),
+                given: params.len(),
+            });
+        }
+
+        for (index, (parameter, data)) in validator.parameters.iter().zip(params).enumerate() {
+            check(
+                &parameter.schema,
+                &self.definitions,
+                &plutus_data_to_json(data),
+            )
+            .map_err(|reason| Error::InvalidParameter {
+                title: validator.title.clone(),
+                parameter: parameter
+                    .title
+                    .clone()
+                    .unwrap_or_else(|| format!("#{}", index + 1)),
+                reason,
+            })?;
+        }
+
+        let invalid = |reason: String| Error::InvalidBlueprint { reason };
+
+        let bytes = hex::decode(&validator.compiled_code)
+            .map_err(|error| invalid(format!("{}: {}", validator.title, error)))?;
+
+        let mut buffer = Vec::new();
+
+        let mut program = Program::<DeBruijn>::from_cbor(&bytes, &mut buffer)
+            .map_err(|error| invalid(format!("{}: {}", validator.title, error)))?;
+
+        for data in params {
+            program = program.apply_data(data.clone());
+        }
+
+        let cbor = program
+            .to_cbor()
+            .map_err(|error| invalid(format!("{}: {}", validator.title, error)))?;
+
+        let plutus = validator
+            .plutus_version
+            .unwrap_or(self.preamble.plutus_version);
+
+        validator.compiled_code = hex::encode(&cbor);
+        validator.hash = hex::encode(plutus.script_hash(&cbor));
+        validator.parameters.drain(..params.len());
+
+        Ok((cbor, plutus))
+    }
+}
+
+impl Validator {
+    fn new(
+        module: &str,
+        func: &TypedFunction,
+        script: &Script,
+        default: PlutusVersion,
+        schemas: &mut Schemas,
+    ) -> Result<Self, Error> {
+        let title = format!("{}.{}", module, func.name);
+
+        let invalid = |reason: String| Error::BlueprintSchema {
+            title: title.clone(),
+            reason,
+        };
+
+        let program: Program<DeBruijn> = script.program.clone().into();
+
+        let cbor = program
+            .to_cbor()
+            .map_err(|error| invalid(error.to_string()))?;
+
+        let hash = script.plutus.script_hash(&cbor);
+
+        // The datum (for spending), the redeemer and the script context come
+        // last, any argument before them is a parameter of the validator.
+        let required = match func.name.as_str() {
+            SPEND => 3,
+            MINT | WITHDRAW | PUBLISH => 2,
+            name => return Err(invalid(format!("unknown purpose {}", name))),
+        };
+
+        if func.arguments.len() < required {
+            return Err(invalid(format!(
+                "expected at least {} arguments, found {}",
+                required,
+                func.arguments.len()
+            )));
+        }
+
+        let (parameters, arguments) = func.arguments.split_at(func.arguments.len() - required);
+
+        let datum = if func.name == SPEND {
+            Some(schemas.argument(&arguments[0]).map_err(invalid)?)
+        } else {
+            None
+        };
+
+        Ok(Validator {
+            purpose: func.name.clone(),
+            datum,
+            redeemer: schemas
+                .argument(&arguments[required - 2])
+                .map_err(invalid)?,
+            parameters: parameters
+                .iter()
+                .map(|arg| schemas.argument(arg))
+                .collect::<Result<_, _>>()
+                .map_err(invalid)?,
+            compiled_code: hex::encode(&cbor),
+            hash: hex::encode(hash),
+            plutus_version: Some(script.plutus).filter(|plutus| *plutus != default),
+            title,
+        })
+    }
+}
+
+/// Derives the schemas of types from their definitions, collecting those of
+/// custom types under `definitions` so recursive types can refer to
+/// themselves. Failures are reported as the reason a type can't be
+/// described.
+pub(crate) struct Schemas<'a> {
+    data_types: &'a HashMap<DataTypeKey, &'a DataType<Arc<Type>>>,
+    pub(crate) definitions: BTreeMap<String, Value>,
+}
+
+impl<'a> Schemas<'a> {
+    pub(crate) fn new(data_types: &'a HashMap<DataTypeKey, &'a DataType<Arc<Type>>>) -> Self {
+        Schemas {
+            data_types,
+            definitions: BTreeMap::new(),
+        }
+    }
+
+    fn argument(&mut self, arg: &TypedArg) -> Result<Argument, String> {
+        Ok(Argument {
+            title: arg.get_variable_name().map(str::to_string),
+            schema: self.schema(&arg.tipo)?,
+        })
+    }
+
+    pub(crate) fn schema(&mut self, tipo: &Arc<Type>) -> Result<Value, String> {
+        let tipo = collapse_links(tipo);
+
+        match tipo.as_ref() {
+            Type::App {
+                module, name, args, ..
+            } => match (module.as_str(), name.as_str()) {
+                ("", "Int") => Ok(json!({ "dataType": "integer" })),
+                ("", "ByteArray") => Ok(json!({ "dataType": "bytes" })),
+                // Strings are stored in data as their UTF-8 bytes.
+                ("", "String") => Ok(json!({ "dataType": "bytes" })),
+                ("", "Data") => Ok(json!({})),
+                ("", "List") => {
+                    let items = match args.first() {
+                        Some(item) => self.schema(item)?,
+                        None => json!({}),
+                    };
+
+                    Ok(json!({ "dataType": "list", "items": items }))
+                }
+                _ => self.definition(module, name, args),
+            },
+            // A generic argument is any data.
+            Type::Var { .. } => Ok(json!({})),
+            Type::Fn { .. } => Err("functions can't be passed as data".to_string()),
+        }
+    }
+
+    /// A reference to the schema of a custom type, stored in `definitions`.
+    pub(crate) fn definition(
+        &mut self,
+        module: &str,
+        name: &str,
+        args: &[Arc<Type>],
+    ) -> Result<Value, String> {
+        let key = definition_key(module, name, args);
+
+        let reference = json!({ "$ref": format!("#/definitions/{}", key.replace('/', "~1")) });
+
+        if self.definitions.contains_key(&key) {
+            return Ok(reference);
+        }
+
+        // Claim the key first, the type may refer to itself.
+        self.definitions.insert(key.clone(), Value::Null);
+
+        let constructors = match (module, name) {
+            ("", "Bool") => vec![
+                constructor_schema("False", None, 0, vec![]),
+                constructor_schema("True", None, 1, vec![]),
+            ],
+            ("", "Nil") => vec![constructor_schema("Nil", None, 0, vec![])],
+            ("", "Option") => {
+                let value = match args.first() {
+                    Some(value) => self.schema(value)?,
+                    None => json!({}),
+                };
+
+                vec![
+                    constructor_schema("Some", None, 0, vec![value]),
+                    constructor_schema("None", None, 1, vec![]),
+                ]
+            }
+            _ => {
+                let data_type = *self
+                    .data_types
+                    .get(&DataTypeKey {
+                        module_name: module.to_string(),
+                        defined_type: name.to_string(),
+                    })
+                    .ok_or_else(|| format!("unknown data type {}.{}", module, name))?;
+
+                let mut mapping = HashMap::new();
+
+                for (parameter, arg) in data_type.typed_parameters.iter().zip(args) {
+                    if let Type::Var { tipo } = collapse_links(parameter).as_ref() {
+                        if let TypeVar::Generic { id } = *tipo.borrow() {
+                            mapping.insert(id, arg.clone());
+                        }
+                    }
+                }
+
+                data_type
+                    .constructors
+                    .iter()
+                    .enumerate()
+                    .map(|(index, constructor)| {
+                        let fields = constructor
+                            .arguments
+                            .iter()
+                            .map(|arg| {
+                                let schema = self.schema(&substitute(&arg.tipo, &mapping))?;
+
+                                Ok(match (&arg.label, schema) {
+                                    (Some(label), Value::Object(object)) => {
+                                        let mut field = json!({ "title": label });
+
+                                        for (key, value) in object {
+                                            field[key] = value;
+                                        }
+
+                                        field
+                                    }
+                                    (_, schema) => schema,
+                                })
+                            })
+                            .collect::<Result<_, String>>()?;
+
+                        Ok(constructor_schema(
+                            &constructor.name,
+                            constructor.documentation.as_deref(),
+                            index,
+                            fields,
+                        ))
+                    })
+                    .collect::<Result<_, String>>()?
+            }
+        };
+
+        let mut schema = json!({ "title": name });
+
+        if let Some(doc) = self.documentation(module, name) {
+            schema["description"] = json!(doc.trim());
+        }
+
+        schema["anyOf"] = Value::Array(constructors);
+
+        self.definitions.insert(key, schema);
+
+        Ok(reference)
+    }
+
+    fn documentation(&self, module: &str, name: &str) -> Option<&str> {
+        self.data_types
+            .get(&DataTypeKey {
+                module_name: module.to_string(),
+                defined_type: name.to_string(),
+            })
+            .and_then(|data_type| data_type.doc.as_deref())
+    }
+}
+
+fn constructor_schema(name: &str, doc: Option<&str>, index: usize, fields: Vec<Value>) -> Value {
+    let mut schema = json!({ "title": name });
+
+    if let Some(doc) = doc {
+        schema["description"] = json!(doc.trim());
+    }
+
+    schema["dataType"] = json!("constructor");
+    schema["index"] = json!(index);
+    schema["fields"] = Value::Array(fields);
+
+    schema
+}
+
+/// Check `data`, in the detailed JSON schema of Plutus data, against
+/// `schema`.
+fn check(
+    schema: &Value,
+    definitions: &BTreeMap<String, Value>,
+    data: &Value,
+) -> Result<(), String> {
+    if let Some(reference) = schema.get("$ref").and_then(Value::as_str) {
+        let key = reference
+            .trim_start_matches("#/definitions/")
+            .replace("~1", "/");
+
+        let schema = definitions
+            .get(&key)
+            .ok_or_else(|| format!("unknown definition {}", reference))?;
+
+        return check(schema, definitions, data);
+    }
+
+    if let Some(constructors) = schema.get("anyOf").and_then(Value::as_array) {
+        let index = data
+            .get("constructor")
+            .ok_or_else(|| format!("expected a constructor, found {}", data))?;
+
+        let constructor = constructors
+            .iter()
+            .find(|constructor| constructor.get("index") == Some(index))
+            .ok_or_else(|| format!("unknown constructor {}", index))?;
+
+        return check(constructor, definitions, data);
+    }
+
+    let expect = |key: &str, expected: &str| {
+        data.get(key)
+            .map(|_| ())
+            .ok_or_else(|| format!("expected {}, found {}", expected, data))
+    };
+
+    match schema.get("dataType").and_then(Value::as_str) {
+        // Any data.
+        None => Ok(()),
+        Some("integer") => expect("int", "an integer"),
+        Some("bytes") => expect("bytes", "bytes"),
+        Some("map") => expect("map", "a map"),
+        Some("list") => {
+            let items = data
+                .get("list")
+                .and_then(Value::as_array)
+                .ok_or_else(|| format!("expected a list, found {}", data))?;
+
+            items
+                .iter()
+                .try_for_each(|item| check(&schema["items"], definitions, item))
+        }
+        Some("constructor") => {
+            if data.get("constructor") != schema.get("index") {
+                return Err(format!(
+                    "expected constructor {}, found {}",
+                    schema["index"], data
+                ));
+            }
+
+            let fields = data
+                .get("fields")
+                .and_then(Value::as_array)
+                .ok_or_else(|| format!("expected fields, found {}", data))?;
+
+            let expected = schema["fields"].as_array().cloned().unwrap_or_default();
+
+            if fields.len() != expected.len() {
+                return Err(format!(
+                    "expected {} field(s), found {}",
+                    expected.len(),
+                    data
+                ));
+            }
+
+            expected
+                .iter()
+                .zip(fields)
+                .try_for_each(|(schema, field)| check(schema, definitions, field))
+        }
+        Some(other) => Err(format!("unknown data type {}", other)),
+    }
+}
+
+/// A name for a custom type, e.g. `aiken/transaction/OutputReference` or
+/// `Option$Int` for instances of generic types.
+pub(crate) fn definition_key(module: &str, name: &str, args: &[Arc<Type>]) -> String {
+    let mut key = if module.is_empty() {
+        name.to_string()
+    } else {
+        format!("{}/{}", module, name)
+    };
+
+    if !args.is_empty() {
+        let args = args
+            .iter()
+            .map(|arg| match collapse_links(arg).as_ref() {
+                Type::App {
+                    module, name, args, ..
+                } => definition_key(module, name, args),
+                _ => "Data".to_string(),
+            })
+            .collect::<Vec<_>>();
+
+        key = format!("{}${}", key, args.join("_"));
+    }
+
+    key
+}
+
+fn collapse_links(tipo: &Arc<Type>) -> Arc<Type> {
+    if let Type::Var { tipo: var } = tipo.as_ref() {
+        if let TypeVar::Link { tipo } = &*var.borrow() {
+            return collapse_links(tipo);
+        }
+    }
+
+    tipo.clone()
+}
+
+/// Replace the generic type variables of `tipo` as given by `mapping`.
+pub(crate) fn substitute(tipo: &Arc<Type>, mapping: &HashMap<u64, Arc<Type>>) -> Arc<Type> {
+    let tipo = collapse_links(tipo);
+
+    match tipo.as_ref() {
+        Type::App {
+            public,
+            module,
+            name,
+            args,
+        } => Arc::new(Type::App {
+            public: *public,
+            module: module.clone(),
+            name: name.clone(),
+            args: args.iter().map(|arg| substitute(arg, mapping)).collect(),
+        }),
+        Type::Fn { args, ret } => Arc::new(Type::Fn {
+            args: args.iter().map(|arg| substitute(arg, mapping)).collect(),
+            ret: substitute(ret, mapping),
+        }),
+        Type::Var { tipo: var } => match &*var.borrow() {
+            TypeVar::Generic { id } | TypeVar::Unbound { id } => {
+                mapping.get(id).cloned().unwrap_or_else(|| tipo.clone())
+            }
+            TypeVar::Link { .. } => unreachable!("links are collapsed"),
+        },
+    }
+}
@@ -0,0 +1,165 @@
+//! The build cache, under `build/`. It keeps the interface of each
+//! type-checked module along with a hash of its source and of the hashes of
+//! its dependencies, so that modules that haven't changed since the last
+//! build don't need to be checked again.
+
+use std::{
+    collections::{BTreeMap, HashMap},
+    fs, io,
+    path::{Path, PathBuf},
+};
+
+use aiken_lang::tipo::TypeInfo;
+use pallas::crypto::hash::Hasher;
+use serde::{de::DeserializeOwned, Deserialize, Serialize};
+
+use crate::{config::Lints, error::Error, module::ParsedModule};
+
+const VERSION: &str = env!("CARGO_PKG_VERSION");
+
+pub struct Cache {
+    dir: PathBuf,
+}
+
+/// A type-checked module, valid as long as its hash is unchanged.
+#[derive(Serialize, Deserialize)]
+struct CachedModule {
+    version: String,
+    hash: String,
+    type_info: TypeInfo,
+}
+
+/// What the validators were last compiled from.
+#[derive(Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Manifest {
+    pub version: String,
+    /// The hash of `aiken.toml`, as the outputs depend on most of it
+    pub config: String,
+    pub keep_traces: bool,
+    /// The hash of each validator module.
+    pub validators: BTreeMap<String, String>,
+}
+
+impl Manifest {
+    pub fn new(config: &str, keep_traces: bool, validators: BTreeMap<String, String>) -> Self {
+        let mut hasher = Hasher::<256>::new();
+
+        hasher.input(config.as_bytes());
+
+        Manifest {
+            version: VERSION.to_string(),
+            config: hex::encode(hasher.finalize()),
+            keep_traces,
+            validators,
+        }
+    }
+}
+
+impl Cache {
+    pub fn new(root: &Path) -> Self {
+        Cache {
+            dir: root.join("build").join("cache"),
+        }
+    }
+
+    /// Remove everything from the cache.
+    pub fn clean(&self) -> Result<(), Error> {
+        match fs::remove_dir_all(&self.dir) {
+            Err(error) if error.kind() != io::ErrorKind::NotFound => Err(Error::FileIo {
+                error,
+                path: self.dir.clone(),
+            }),
+            _ => Ok(()),
+        }
+    }
+
+    /// The interface of the module `name`, if it was cached with the same
+    /// `hash`. An unreadable cache entry is ignored, the module is then
+    /// simply checked again.
+    pub fn module(&self, name: &str, hash: &str) -> Option<TypeInfo> {
+        self.read::<CachedModule>(&self.module_path(name))
+            .filter(|cached| cached.version == VERSION && cached.hash == hash)
+            .map(|cached| cached.type_info)
+    }
+
+    pub fn store_module(&self, name: &str, hash: &str, type_info: &TypeInfo) -> Result<(), Error> {
+        let cached = CachedModule {
+            version: VERSION.to_string(),
+            hash: hash.to_string(),
+            type_info: type_info.clone(),
+        };
+
+        self.write(&self.module_path(name), &cached)
+    }
+
+    pub fn manifest(&self) -> Option<Manifest> {
+        self.read(&self.dir.join("manifest.json"))
+    }
+
+    pub fn store_manifest(&self, manifest: &Manifest) -> Result<(), Error> {
+        self.write(&self.dir.join("manifest.json"), manifest)
+    }
+
+    fn module_path(&self, name: &str) -> PathBuf {
+        self.dir.join("modules").join(format!("{}.json", name))
+    }
+
+    fn read<T: DeserializeOwned>(&self, path: &Path) -> Option<T> {
+        let json = fs::read_to_string(path).ok()?;
+
+        serde_json::from_str(&json).ok()
+    }
+
+    fn write<T: Serialize>(&self, path: &Path, value: &T) -> Result<(), Error> {
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent)?;
+        }
+
+        fs::write(path, serde_json::to_string(value).unwrap())?;
+
+        Ok(())
+    }
+}
+
+/// Hash each module in `sequence` from its source and the hashes of its
+/// dependencies, so that a change to a module also changes the hash of every
+/// module depending on it. The `lints` are hashed too, as modules whose
+/// warnings are allowed are cached and must be checked again when they no
+/// longer are.
+pub fn hash_modules(
+    package: &str,
+    lints: &Lints,
+    modules: &HashMap<String, ParsedModule>,
+    sequence: &[String],
+) -> HashMap<String, String> {
+    let mut hashes: HashMap<String, String> = HashMap::with_capacity(sequence.len());
+
+    let lints = serde_json::to_string(lints).unwrap();
+
+    for name in sequence {
+        let module = &modules[name];
+
+        let mut hasher = Hasher::<256>::new();
+
+        hasher.input(package.as_bytes());
+        hasher.input(lints.as_bytes());
+        hasher.input(&[module.kind.is_validator() as u8]);
+        hasher.input(module.code.as_bytes());
+
+        let (_, mut deps) = module.deps_for_graph();
+
+        deps.sort();
+
+        for dep in deps {
+            // Modules from outside the project come with the compiler.
+            if let Some(hash) = hashes.get(&dep) {
+                hasher.input(dep.as_bytes());
+                hasher.input(hash.as_bytes());
+            }
+        }
+
+        hashes.insert(name.clone(), hex::encode(hasher.finalize()));
+    }
+
+    hashes
+}
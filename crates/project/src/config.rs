@@ -1,21 +1,315 @@
-use std::{fs, io, path::PathBuf};
+use std::{collections::BTreeMap, fs, path::PathBuf};
 
-use serde::Deserialize;
+use aiken_lang::{ast::Span, tipo::error::Warning};
+use miette::NamedSource;
+use pallas::{
+    crypto::hash::Hash,
+    ledger::primitives::{
+        alonzo,
+        babbage::{self, Language},
+    },
+};
+use pallas_traverse::ComputeHash;
+use serde::{Deserialize, Serialize};
+use toml::Spanned;
+
+use crate::error::Error;
 
 #[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
 pub struct Config {
     pub name: String,
     pub version: String,
     #[serde(default)]
     pub description: String,
+    pub repository: Option<String>,
+    #[serde(default)]
+    pub licences: Vec<String>,
+    /// Other packages this one depends on, with their version requirements.
+    /// They aren't fetched yet.
+    #[serde(default)]
+    pub dependencies: BTreeMap<String, String>,
+    /// The Plutus version validators are compiled for, unless overridden in
+    /// their `[validators.<module>]` section
+    #[serde(default)]
+    pub plutus: PlutusVersion,
+    #[serde(default)]
+    pub validators: BTreeMap<String, ValidatorOptions>,
+    /// The network addresses are derived for
+    #[serde(default)]
+    pub network: Network,
+    /// What the addresses of spending validators delegate to, e.g.
+    ///
+    /// ```toml
+    /// [stake]
+    /// key = "<stake key hash, as hex>"
+    /// ```
+    pub stake: Option<StakeCredential>,
+    #[serde(default)]
+    pub build: BuildOptions,
+    #[serde(default)]
+    pub lints: Lints,
+}
+
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum PlutusVersion {
+    V1,
+    #[default]
+    V2,
+}
+
+impl PlutusVersion {
+    pub fn language(&self) -> Language {
+        match self {
+            PlutusVersion::V1 => Language::PlutusV1,
+            PlutusVersion::V2 => Language::PlutusV2,
+        }
+    }
+
+    /// The type of scripts in the text envelopes of the cardano-cli
+    pub fn script_type(&self) -> &'static str {
+        match self {
+            PlutusVersion::V1 => "PlutusScriptV1",
+            PlutusVersion::V2 => "PlutusScriptV2",
+        }
+    }
+
+    /// The hash of a script, which is prefixed by its language.
+    pub fn script_hash(&self, cbor: &[u8]) -> Hash<28> {
+        match self {
+            PlutusVersion::V1 => alonzo::PlutusScript(cbor.to_vec().into()).compute_hash(),
+            PlutusVersion::V2 => babbage::PlutusV2Script(cbor.to_vec().into()).compute_hash(),
+        }
+    }
+}
+
+impl std::fmt::Display for PlutusVersion {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            PlutusVersion::V1 => write!(f, "Plutus V1"),
+            PlutusVersion::V2 => write!(f, "Plutus V2"),
+        }
+    }
+}
+
+/// Options for the validators of one module
+#[derive(Debug, Default, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct ValidatorOptions {
+    pub plutus: Option<PlutusVersion>,
+}
+
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Network {
+    Mainnet,
+    #[default]
+    Testnet,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(try_from = "Stake")]
+pub enum StakeCredential {
+    Key(Hash<28>),
+    Script(Hash<28>),
+}
+
+/// The `[stake]` section as written, with exactly one of its fields set
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct Stake {
+    key: Option<String>,
+    script: Option<String>,
+}
+
+impl TryFrom<Stake> for StakeCredential {
+    type Error = String;
+
+    fn try_from(stake: Stake) -> Result<Self, Self::Error> {
+        let hash = |hex: String| {
+            hex.parse()
+                .map_err(|_| format!("{} is not a 28 bytes hash, as hex", hex))
+        };
+
+        match stake {
+            Stake {
+                key: Some(key),
+                script: None,
+            } => hash(key).map(StakeCredential::Key),
+            Stake {
+                key: None,
+                script: Some(script),
+            } => hash(script).map(StakeCredential::Script),
+            _ => Err("stake takes either a key or a script hash".to_string()),
+        }
+    }
+}
+
+/// Defaults for the flags of `aiken build`, from the `[build]` section.
+#[derive(Debug, Default, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct BuildOptions {
+    /// Also dump textual uplc
+    pub uplc: bool,
+    /// Keep traces and error messages in the compiled validators
+    pub keep_traces: bool,
+}
+
+/// What to do with each kind of warning, from the `[lints]` section, e.g.
+///
+/// ```toml
+/// [lints]
+/// todo = "deny"
+/// unused_variable = "allow"
+/// ```
+#[derive(Debug, Default, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct Lints {
+    pub todo: LintLevel,
+    pub implicitly_discarded_result: LintLevel,
+    pub unused_literal: LintLevel,
+    pub no_fields_record_update: LintLevel,
+    pub all_fields_record_update: LintLevel,
+    pub unused_type: LintLevel,
+    pub unused_constructor: LintLevel,
+    pub unused_imported_value: LintLevel,
+    pub unused_imported_module: LintLevel,
+    pub unused_private_module_constant: LintLevel,
+    pub unused_private_function: LintLevel,
+    pub unused_variable: LintLevel,
+}
+
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum LintLevel {
+    /// Say nothing
+    Allow,
+    /// Report a warning
+    #[default]
+    Warn,
+    /// Report an error, failing the compilation
+    Deny,
+}
+
+impl Lints {
+    pub fn level(&self, warning: &Warning) -> LintLevel {
+        match warning {
+            Warning::Todo { .. } => self.todo,
+            Warning::ImplicitlyDiscardedResult { .. } => self.implicitly_discarded_result,
+            Warning::UnusedLiteral { .. } => self.unused_literal,
+            Warning::NoFieldsRecordUpdate { .. } => self.no_fields_record_update,
+            Warning::AllFieldsRecordUpdate { .. } => self.all_fields_record_update,
+            Warning::UnusedType { .. } => self.unused_type,
+            Warning::UnusedConstructor { .. } => self.unused_constructor,
+            Warning::UnusedImportedValue { .. } => self.unused_imported_value,
+            Warning::UnusedImportedModule { .. } => self.unused_imported_module,
+            Warning::UnusedPrivateModuleConstant { .. } => self.unused_private_module_constant,
+            Warning::UnusedPrivateFunction { .. } => self.unused_private_function,
+            Warning::UnusedVariable { .. } => self.unused_variable,
+        }
+    }
+}
+
+/// The values that are validated once the configuration is parsed, along with
+/// their location.
+#[derive(Deserialize)]
+struct Located {
+    name: Spanned<String>,
+    version: Spanned<String>,
 }
 
 impl Config {
-    pub fn load(dir: PathBuf) -> io::Result<Config> {
-        let raw_config = fs::read_to_string(dir.join("aiken.toml"))?;
+    /// The Plutus version the validators of `module` are compiled for
+    pub fn plutus_version(&self, module: &str) -> PlutusVersion {
+        self.validators
+            .get(module)
+            .and_then(|options| options.plutus)
+            .unwrap_or(self.plutus)
+    }
+
+    pub fn load(dir: PathBuf) -> Result<Config, Error> {
+        let path = dir.join("aiken.toml");
+
+        let raw_config = fs::read_to_string(&path).map_err(|error| Error::FileIo {
+            error,
+            path: path.clone(),
+        })?;
+
+        let invalid =
+            |message: String, location: Option<Span>, help: Option<&str>| Error::InvalidConfig {
+                path: path.clone(),
+                src: raw_config.clone(),
+                named: NamedSource::new(path.display().to_string(), raw_config.clone()),
+                message,
+                location,
+                help: help.map(str::to_string),
+            };
+
+        // Spans count characters, toml counts bytes
+        let char_offset = |byte: usize| raw_config[..byte.min(raw_config.len())].chars().count();
+
+        let toml_error = |error: toml::de::Error| {
+            let location = error.line_col().map(|(line, column)| {
+                let start = char_offset(
+                    raw_config
+                        .split_inclusive('\n')
+                        .take(line)
+                        .map(str::len)
+                        .sum::<usize>()
+                        + column,
+                );
+
+                Span {
+                    start,
+                    end: (start + 1).min(raw_config.chars().count()),
+                }
+            });
 
-        let config = toml::from_str(&raw_config).unwrap();
+            // The position is already shown by the label
+            let message = error.to_string();
+            let message = match message.rsplit_once(" at line ") {
+                Some((message, _)) if location.is_some() => message.to_string(),
+                _ => message,
+            };
+
+            invalid(message, location, None)
+        };
+
+        let config: Config = toml::from_str(&raw_config).map_err(toml_error)?;
+
+        let located: Located = toml::from_str(&raw_config).map_err(toml_error)?;
+
+        let span = |value: &Spanned<String>| Span {
+            start: char_offset(value.start()),
+            end: char_offset(value.end()),
+        };
+
+        if !regex::Regex::new("^[a-z][a-z0-9_]*$")
+            .expect("name regex could not be compiled")
+            .is_match(&config.name)
+        {
+            return Err(invalid(
+                format!("{} is not a valid project name", config.name),
+                Some(span(&located.name)),
+                Some("Project names must start with a lowercase letter and may only contain lowercase letters, numbers and underscores."),
+            ));
+        }
+
+        if !regex::Regex::new(SEMVER)
+            .expect("version regex could not be compiled")
+            .is_match(&config.version)
+        {
+            return Err(invalid(
+                format!("{} is not a valid version", config.version),
+                Some(span(&located.version)),
+                Some("Versions follow semantic versioning, e.g. 1.0.0"),
+            ));
+        }
 
         Ok(config)
     }
 }
+
+/// From https://semver.org
+const SEMVER: &str = r"^(0|[1-9]\d*)\.(0|[1-9]\d*)\.(0|[1-9]\d*)(?:-((?:0|[1-9]\d*|\d*[a-zA-Z-][0-9a-zA-Z-]*)(?:\.(?:0|[1-9]\d*|\d*[a-zA-Z-][0-9a-zA-Z-]*))*))?(?:\+([0-9a-zA-Z-]+(?:\.[0-9a-zA-Z-]+)*))?$";
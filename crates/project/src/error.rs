@@ -4,10 +4,13 @@ use std::{
     path::{Path, PathBuf},
 };
 
-use aiken_lang::{ast::Span, parser::error::ParseError, tipo};
+use aiken_lang::{ast::Span, parser::error::ParseError, tipo, uplc};
 use miette::{
     Diagnostic, EyreContext, LabeledSpan, MietteHandlerOpts, NamedSource, RgbColors, SourceCode,
 };
+use serde_json::json;
+
+use crate::config::PlutusVersion;
 
 #[allow(dead_code)]
 #[derive(thiserror::Error)]
@@ -28,6 +31,16 @@ pub enum Error {
     #[error(transparent)]
     StandardIo(#[from] io::Error),
 
+    #[error("{message}")]
+    InvalidConfig {
+        path: PathBuf,
+        src: String,
+        named: NamedSource,
+        message: String,
+        location: Option<Span>,
+        help: Option<String>,
+    },
+
     #[error("cyclical module imports")]
     ImportCycle { modules: Vec<String> },
 
@@ -56,6 +69,34 @@ pub enum Error {
         error: tipo::error::Error,
     },
 
+    #[error("code generation")]
+    CodeGen {
+        path: PathBuf,
+        src: String,
+        named: NamedSource,
+        #[source]
+        error: uplc::error::Error,
+    },
+
+    #[error("{builtin} isn't available in {plutus}")]
+    UnsupportedBuiltin {
+        builtin: String,
+        plutus: PlutusVersion,
+        location: Span,
+        src: String,
+        path: PathBuf,
+        named: NamedSource,
+    },
+
+    #[error("denied warning")]
+    DeniedWarning {
+        path: PathBuf,
+        src: String,
+        named: NamedSource,
+        #[source]
+        warning: tipo::error::Warning,
+    },
+
     #[error("validator functions must return Bool")]
     ValidatorMustReturnBool {
         path: PathBuf,
@@ -64,15 +105,77 @@ pub enum Error {
         location: Span,
     },
 
-    #[error("{name} requires at least {at_least} arguments")]
+    #[error("{name} requires {expected} arguments")]
     WrongValidatorArity {
         name: String,
-        at_least: u8,
+        expected: u8,
+        location: Span,
+        path: PathBuf,
+        src: String,
+        named: NamedSource,
+    },
+
+    #[error("{name} is not a validator purpose")]
+    UnknownValidatorPurpose {
+        name: String,
+        location: Span,
+        path: PathBuf,
+        src: String,
+        named: NamedSource,
+    },
+
+    #[error("{name} is handled more than once")]
+    DuplicateValidatorPurpose {
+        name: String,
+        location: Span,
+        path: PathBuf,
+        src: String,
+        named: NamedSource,
+    },
+
+    #[error("{name} is not in a validator block")]
+    HandlerOutsideValidator {
+        name: String,
+        purpose: String,
         location: Span,
         path: PathBuf,
         src: String,
         named: NamedSource,
     },
+
+    #[error("validator arguments can't hold functions")]
+    ValidatorArgumentNotData {
+        location: Span,
+        path: PathBuf,
+        src: String,
+        named: NamedSource,
+    },
+
+    #[error("no blueprint found at {}", path.display())]
+    MissingBlueprint { path: PathBuf },
+
+    #[error("invalid blueprint: {reason}")]
+    InvalidBlueprint { reason: String },
+
+    #[error("{title} can't be described in the blueprint: {reason}")]
+    BlueprintSchema { title: String, reason: String },
+
+    #[error("unknown validator {title}")]
+    UnknownValidator { title: String, known: Vec<String> },
+
+    #[error("{title} takes {expected} parameter(s) but {given} were given")]
+    TooManyParameters {
+        title: String,
+        expected: usize,
+        given: usize,
+    },
+
+    #[error("parameter {parameter} of {title} doesn't match its schema: {reason}")]
+    InvalidParameter {
+        title: String,
+        parameter: String,
+        reason: String,
+    },
 }
 
 impl Error {
@@ -94,6 +197,14 @@ impl Error {
         }
     }
 
+    /// One JSON record per error, see [`to_json`].
+    pub fn to_json(&self) -> Vec<serde_json::Value> {
+        match self {
+            Error::List(errors) => errors.iter().flat_map(|error| error.to_json()).collect(),
+            rest => vec![to_json(rest, rest.path(), rest.src())],
+        }
+    }
+
     pub fn from_parse_errors(errs: Vec<ParseError>, path: &Path, src: &str) -> Self {
         let mut errors = Vec::with_capacity(errs.len());
 
@@ -143,11 +254,25 @@ impl Error {
             Error::Format { .. } => None,
             Error::StandardIo(_) => None,
             Error::ImportCycle { .. } => None,
+            Error::InvalidConfig { path, .. } => Some(path.to_path_buf()),
             Error::List(_) => None,
             Error::Parse { path, .. } => Some(path.to_path_buf()),
             Error::Type { path, .. } => Some(path.to_path_buf()),
+            Error::CodeGen { path, .. } => Some(path.to_path_buf()),
+            Error::DeniedWarning { path, .. } => Some(path.to_path_buf()),
+            Error::UnsupportedBuiltin { path, .. } => Some(path.to_path_buf()),
             Error::ValidatorMustReturnBool { path, .. } => Some(path.to_path_buf()),
             Error::WrongValidatorArity { path, .. } => Some(path.to_path_buf()),
+            Error::UnknownValidatorPurpose { path, .. } => Some(path.to_path_buf()),
+            Error::DuplicateValidatorPurpose { path, .. } => Some(path.to_path_buf()),
+            Error::HandlerOutsideValidator { path, .. } => Some(path.to_path_buf()),
+            Error::ValidatorArgumentNotData { path, .. } => Some(path.to_path_buf()),
+            Error::MissingBlueprint { path } => Some(path.to_path_buf()),
+            Error::InvalidBlueprint { .. } => None,
+            Error::BlueprintSchema { .. } => None,
+            Error::UnknownValidator { .. } => None,
+            Error::TooManyParameters { .. } => None,
+            Error::InvalidParameter { .. } => None,
         }
     }
 
@@ -158,11 +283,25 @@ impl Error {
             Error::Format { .. } => None,
             Error::StandardIo(_) => None,
             Error::ImportCycle { .. } => None,
+            Error::InvalidConfig { src, .. } => Some(src.to_string()),
             Error::List(_) => None,
             Error::Parse { src, .. } => Some(src.to_string()),
             Error::Type { src, .. } => Some(src.to_string()),
+            Error::CodeGen { src, .. } => Some(src.to_string()),
+            Error::DeniedWarning { src, .. } => Some(src.to_string()),
+            Error::UnsupportedBuiltin { src, .. } => Some(src.to_string()),
             Error::ValidatorMustReturnBool { src, .. } => Some(src.to_string()),
             Error::WrongValidatorArity { src, .. } => Some(src.to_string()),
+            Error::UnknownValidatorPurpose { src, .. } => Some(src.to_string()),
+            Error::DuplicateValidatorPurpose { src, .. } => Some(src.to_string()),
+            Error::HandlerOutsideValidator { src, .. } => Some(src.to_string()),
+            Error::ValidatorArgumentNotData { src, .. } => Some(src.to_string()),
+            Error::MissingBlueprint { .. } => None,
+            Error::InvalidBlueprint { .. } => None,
+            Error::BlueprintSchema { .. } => None,
+            Error::UnknownValidator { .. } => None,
+            Error::TooManyParameters { .. } => None,
+            Error::InvalidParameter { .. } => None,
         }
     }
 }
@@ -196,13 +335,27 @@ impl Diagnostic for Error {
             Error::DuplicateModule { .. } => Some(Box::new("aiken::module::duplicate")),
             Error::FileIo { .. } => None,
             Error::ImportCycle { .. } => Some(Box::new("aiken::module::cyclical")),
+            Error::InvalidConfig { .. } => Some(Box::new("aiken::config")),
             Error::List(_) => None,
             Error::Parse { .. } => Some(Box::new("aiken::parser")),
             Error::Type { .. } => Some(Box::new("aiken::typecheck")),
+            Error::CodeGen { .. } => Some(Box::new("aiken::codegen")),
+            Error::DeniedWarning { .. } => Some(Box::new("aiken::lint")),
+            Error::UnsupportedBuiltin { .. } => Some(Box::new("aiken::validators")),
             Error::StandardIo(_) => None,
             Error::Format { .. } => None,
             Error::ValidatorMustReturnBool { .. } => Some(Box::new("aiken::scripts")),
             Error::WrongValidatorArity { .. } => Some(Box::new("aiken::validators")),
+            Error::UnknownValidatorPurpose { .. } => Some(Box::new("aiken::validators")),
+            Error::DuplicateValidatorPurpose { .. } => Some(Box::new("aiken::validators")),
+            Error::HandlerOutsideValidator { .. } => Some(Box::new("aiken::validators")),
+            Error::ValidatorArgumentNotData { .. } => Some(Box::new("aiken::validators")),
+            Error::MissingBlueprint { .. } => Some(Box::new("aiken::blueprint")),
+            Error::InvalidBlueprint { .. } => Some(Box::new("aiken::blueprint")),
+            Error::BlueprintSchema { .. } => Some(Box::new("aiken::blueprint")),
+            Error::UnknownValidator { .. } => Some(Box::new("aiken::blueprint")),
+            Error::TooManyParameters { .. } => Some(Box::new("aiken::blueprint")),
+            Error::InvalidParameter { .. } => Some(Box::new("aiken::blueprint")),
         }
     }
 
@@ -218,13 +371,35 @@ impl Diagnostic for Error {
                 "Try moving the shared code to a separate module that the others can depend on\n- {}",
                 modules.join("\n- ")
             ))),
+            Error::InvalidConfig { help, .. } => help
+                .as_ref()
+                .map(|help| Box::new(help) as Box<dyn Display>),
             Error::List(_) => None,
             Error::Parse { error, .. } => error.kind.help(),
             Error::Type { error, .. } => error.help(),
+            Error::CodeGen { error, .. } => error.help(),
+            Error::DeniedWarning { .. } => Some(Box::new("This warning is denied by the [lints] of aiken.toml or by --deny-warnings.")),
+            Error::UnsupportedBuiltin { .. } => Some(Box::new("This validator uses the builtin, possibly through the functions it calls. Compile it for Plutus V2 with `plutus = \"v2\"` in aiken.toml, or in the `[validators.<module>]` section for its module.")),
             Error::StandardIo(_) => None,
             Error::Format { .. } => None,
             Error::ValidatorMustReturnBool { .. } => Some(Box::new("Try annotating the validator's return type with Bool")),
-            Error::WrongValidatorArity { .. } => Some(Box::new("Validators require a minimum number of arguments please add the missing arguments.\nIf you don't need one of the required arguments use an underscore `_datum`.")),
+            Error::WrongValidatorArity { .. } => Some(Box::new("A spend handler takes a datum, a redeemer and a script context, other handlers take a redeemer and a script context.\nParameters go on the validator itself, before its handlers.\nIf you don't need one of the required arguments use an underscore `_datum`.")),
+            Error::UnknownValidatorPurpose { .. } => Some(Box::new("Handlers are named after their purpose: spend, mint, withdraw or publish.")),
+            Error::DuplicateValidatorPurpose { .. } => Some(Box::new("Each purpose can only be handled once per module, move one of the handlers to another module.")),
+            Error::HandlerOutsideValidator { purpose, .. } => Some(Box::new(format!(
+                "Only the handlers of `validator` blocks are compiled:\n\nvalidator {{\n  fn {}(..) {{ .. }}\n}}",
+                purpose
+            ))),
+            Error::ValidatorArgumentNotData { .. } => Some(Box::new("Parameters, datums, redeemers and script contexts are passed to scripts as Data, which can't hold functions.")),
+            Error::MissingBlueprint { .. } => Some(Box::new("Run `aiken build` first to compile the validators.")),
+            Error::InvalidBlueprint { .. } => Some(Box::new("Run `aiken build` again to regenerate plutus.json.")),
+            Error::BlueprintSchema { .. } => None,
+            Error::UnknownValidator { known, .. } => Some(Box::new(format!(
+                "Validators are named after their module and purpose:\n- {}",
+                known.join("\n- ")
+            ))),
+            Error::TooManyParameters { .. } => None,
+            Error::InvalidParameter { .. } => Some(Box::new("Parameters are given as CBOR hex, as a UPLC constant `(con data #..)` or as JSON like `{ \"int\": 42 }`.")),
         }
     }
 
@@ -233,17 +408,36 @@ impl Diagnostic for Error {
             Error::DuplicateModule { .. } => None,
             Error::FileIo { .. } => None,
             Error::ImportCycle { .. } => None,
+            Error::InvalidConfig { location, .. } => location.map(|location| {
+                Box::new(vec![LabeledSpan::new_with_span(None, location)].into_iter())
+                    as Box<dyn Iterator<Item = LabeledSpan>>
+            }),
             Error::List(_) => None,
             Error::Parse { error, .. } => error.labels(),
             Error::Type { error, .. } => error.labels(),
+            Error::CodeGen { error, .. } => error.labels(),
+            Error::DeniedWarning { warning, .. } => warning.labels(),
+            Error::UnsupportedBuiltin { location, .. } => Some(Box::new(
+                vec![LabeledSpan::new_with_span(None, *location)].into_iter(),
+            )),
             Error::StandardIo(_) => None,
             Error::Format { .. } => None,
             Error::ValidatorMustReturnBool { location, .. } => Some(Box::new(
                 vec![LabeledSpan::new_with_span(None, *location)].into_iter(),
             )),
-            Error::WrongValidatorArity { location, .. } => Some(Box::new(
+            Error::WrongValidatorArity { location, .. }
+            | Error::UnknownValidatorPurpose { location, .. }
+            | Error::DuplicateValidatorPurpose { location, .. }
+            | Error::HandlerOutsideValidator { location, .. }
+            | Error::ValidatorArgumentNotData { location, .. } => Some(Box::new(
                 vec![LabeledSpan::new_with_span(None, *location)].into_iter(),
             )),
+            Error::MissingBlueprint { .. } => None,
+            Error::InvalidBlueprint { .. } => None,
+            Error::BlueprintSchema { .. } => None,
+            Error::UnknownValidator { .. } => None,
+            Error::TooManyParameters { .. } => None,
+            Error::InvalidParameter { .. } => None,
         }
     }
 
@@ -252,13 +446,27 @@ impl Diagnostic for Error {
             Error::DuplicateModule { .. } => None,
             Error::FileIo { .. } => None,
             Error::ImportCycle { .. } => None,
+            Error::InvalidConfig { named, .. } => Some(named),
             Error::List(_) => None,
             Error::Parse { named, .. } => Some(named),
             Error::Type { named, .. } => Some(named),
+            Error::CodeGen { named, .. } => Some(named),
+            Error::DeniedWarning { named, .. } => Some(named),
+            Error::UnsupportedBuiltin { named, .. } => Some(named),
             Error::StandardIo(_) => None,
             Error::Format { .. } => None,
             Error::ValidatorMustReturnBool { named, .. } => Some(named),
             Error::WrongValidatorArity { named, .. } => Some(named),
+            Error::UnknownValidatorPurpose { named, .. } => Some(named),
+            Error::DuplicateValidatorPurpose { named, .. } => Some(named),
+            Error::HandlerOutsideValidator { named, .. } => Some(named),
+            Error::ValidatorArgumentNotData { named, .. } => Some(named),
+            Error::MissingBlueprint { .. } => None,
+            Error::InvalidBlueprint { .. } => None,
+            Error::BlueprintSchema { .. } => None,
+            Error::UnknownValidator { .. } => None,
+            Error::TooManyParameters { .. } => None,
+            Error::InvalidParameter { .. } => None,
         }
     }
 }
@@ -311,6 +519,85 @@ impl Warning {
     pub fn report(&self) {
         eprintln!("Warning: {:?}", self)
     }
+
+    pub fn to_json(&self) -> serde_json::Value {
+        match self {
+            Warning::Type { path, src, .. } => to_json(self, Some(path.clone()), Some(src.clone())),
+        }
+    }
+}
+
+/// A diagnostic as a JSON record, for tools rather than people. Labels come
+/// with both their byte offsets and their 1-based line and column, counted in
+/// characters, in `src`.
+fn to_json(
+    diagnostic: &dyn Diagnostic,
+    path: Option<PathBuf>,
+    src: Option<String>,
+) -> serde_json::Value {
+    let severity = match diagnostic.severity() {
+        Some(miette::Severity::Advice) => "advice",
+        Some(miette::Severity::Warning) => "warning",
+        Some(miette::Severity::Error) | None => "error",
+    };
+
+    // The message of a diagnostic is often only the stage it comes from, the
+    // details are in its sources.
+    let mut message = diagnostic.to_string();
+    let mut source = diagnostic.source();
+
+    while let Some(error) = source {
+        message.push_str(&format!(": {}", error));
+
+        source = error.source();
+    }
+
+    // Spans count characters, tools expect bytes
+    let byte_offset = |offset: usize| match &src {
+        Some(src) => src
+            .char_indices()
+            .nth(offset)
+            .map_or(src.len(), |(index, _)| index),
+        None => offset,
+    };
+
+    let position = |offset: usize| {
+        src.as_ref().map(|src| {
+            let before = &src[..offset];
+
+            let line = before.matches('\n').count() + 1;
+            let column = before.rsplit('\n').next().unwrap_or("").chars().count() + 1;
+
+            json!({ "line": line, "column": column })
+        })
+    };
+
+    let labels = diagnostic
+        .labels()
+        .into_iter()
+        .flatten()
+        .map(|label| {
+            let start = byte_offset(label.offset());
+            let end = byte_offset(label.offset() + label.len());
+
+            json!({
+                "label": label.label(),
+                "start": start,
+                "end": end,
+                "start_position": position(start),
+                "end_position": position(end),
+            })
+        })
+        .collect::<Vec<_>>();
+
+    json!({
+        "severity": severity,
+        "code": diagnostic.code().map(|code| code.to_string()),
+        "message": message,
+        "help": diagnostic.help().map(|help| help.to_string()),
+        "path": path,
+        "labels": labels,
+    })
 }
 
 impl Debug for Warning {
@@ -1,34 +1,38 @@
 use std::{
-    collections::HashMap,
-    fs,
+    collections::{HashMap, HashSet},
+    fs, io,
     path::{Path, PathBuf},
 };
 
+pub mod address;
+pub mod blueprint;
+pub mod cache;
 pub mod config;
 pub mod error;
 pub mod format;
 pub mod module;
 pub mod script;
 
+#[cfg(test)]
+mod tests;
+
 use aiken_lang::{
-    ast::{Definition, Function, ModuleKind, TypedFunction},
+    ast::{Definition, Function, ModuleKind, TypedFunction, Validator},
     builtins,
     tipo::TypeInfo,
-    uplc::{CodeGenerator, DataTypeKey, FunctionAccessKey},
+    uplc::{holds_functions, CodeGenerator, DataTypeKey, FunctionAccessKey},
     IdGenerator,
 };
+use blueprint::Blueprint;
+use cache::{Cache, Manifest};
 use miette::NamedSource;
-use pallas::{
-    codec::minicbor,
-    ledger::{addresses::Address, primitives::babbage},
-};
-use pallas_traverse::ComputeHash;
+use pallas::{codec::minicbor, ledger::primitives::babbage::PlutusData};
 use script::Script;
 use serde_json::json;
 use uplc::ast::{DeBruijn, Program};
 
 use crate::{
-    config::Config,
+    config::{Config, LintLevel, PlutusVersion},
     error::{Error, Warning},
     module::{CheckedModule, CheckedModules, ParsedModule, ParsedModules},
 };
@@ -42,14 +46,18 @@ pub struct Source {
 }
 
 pub const SPEND: &str = "spend";
-pub const CERT: &str = "cert";
 pub const MINT: &str = "mint";
-pub const WITHDRAWL: &str = "withdrawl";
-pub const VALIDATOR_NAMES: [&str; 4] = [SPEND, CERT, MINT, WITHDRAWL];
+pub const WITHDRAW: &str = "withdraw";
+pub const PUBLISH: &str = "publish";
+pub const VALIDATOR_NAMES: [&str; 4] = [SPEND, MINT, WITHDRAW, PUBLISH];
 
 pub struct Project {
+    /// The hash of each module whose types in `module_types` are up to date
+    /// and free of warnings, so a later compilation can skip it.
+    checked: HashMap<String, String>,
     config: Config,
     defined_modules: HashMap<String, PathBuf>,
+    deny_warnings: bool,
     id_gen: IdGenerator,
     module_types: HashMap<String, TypeInfo>,
     root: PathBuf,
@@ -67,8 +75,10 @@ impl Project {
         module_types.insert("aiken/builtin".to_string(), builtins::plutus(&id_gen));
 
         Project {
+            checked: HashMap::new(),
             config,
             defined_modules: HashMap::new(),
+            deny_warnings: false,
             id_gen,
             module_types,
             root,
@@ -77,29 +87,138 @@ impl Project {
         }
     }
 
-    pub fn build(&mut self, uplc: bool) -> Result<(), Error> {
-        self.compile(true, uplc)
+    pub fn build(&mut self, uplc: bool, keep_traces: bool) -> Result<(), Error> {
+        let uplc = uplc || self.config.build.uplc;
+        let keep_traces = keep_traces || self.config.build.keep_traces;
+
+        self.compile(true, uplc, keep_traces)
     }
 
     pub fn check(&mut self) -> Result<(), Error> {
-        self.compile(false, false)
+        self.compile(false, false, true)
+    }
+
+    /// Fail the compilation on any warning that isn't allowed by the lints
+    /// of the project.
+    pub fn deny_warnings(&mut self) {
+        self.deny_warnings = true;
+    }
+
+    /// Wipe the build cache, so that the next compilation starts afresh.
+    pub fn clean(&self) -> Result<(), Error> {
+        Cache::new(&self.root).clean()
+    }
+
+    /// Apply `params` to the validator `title` of the last build. Its entry
+    /// in `plutus.json` and its assets are replaced by the applied script.
+    pub fn apply(&self, title: &str, params: &[PlutusData]) -> Result<(), Error> {
+        let path = self.root.join("plutus.json");
+
+        let json = fs::read_to_string(&path).map_err(|error| match error.kind() {
+            io::ErrorKind::NotFound => Error::MissingBlueprint { path: path.clone() },
+            _ => Error::FileIo {
+                error,
+                path: path.clone(),
+            },
+        })?;
+
+        let mut blueprint: Blueprint =
+            serde_json::from_str(&json).map_err(|error| Error::InvalidBlueprint {
+                reason: error.to_string(),
+            })?;
+
+        let (cbor, plutus) = blueprint.apply(title, params)?;
+
+        let mut json = serde_json::to_string_pretty(&blueprint).unwrap();
+
+        json.push('\n');
+
+        fs::write(&path, json)?;
+
+        // Validators are titled after their module and purpose, like their
+        // assets folder.
+        let (module, purpose) = title.rsplit_once('.').unwrap_or(("", title));
+
+        let script_output_dir = self.root.join("assets").join(module).join(purpose);
+
+        fs::create_dir_all(&script_output_dir)?;
+
+        self.write_script_outputs(&script_output_dir, purpose, cbor, plutus)
     }
 
-    pub fn compile(&mut self, uplc_gen: bool, uplc_dump: bool) -> Result<(), Error> {
+    pub fn compile(
+        &mut self,
+        uplc_gen: bool,
+        uplc_dump: bool,
+        keep_traces: bool,
+    ) -> Result<(), Error> {
         self.read_source_files()?;
 
+        let previous_modules = std::mem::take(&mut self.defined_modules);
+
         let parsed_modules = self.parse_sources()?;
 
-        let processing_sequence = parsed_modules.sequence()?;
+        // Forget the modules removed since the last compilation
+        for name in previous_modules.keys() {
+            if !self.defined_modules.contains_key(name) {
+                self.module_types.remove(name);
+                self.checked.remove(name);
+            }
+        }
 
-        let mut checked_modules = self.type_check(parsed_modules, processing_sequence)?;
+        let processing_sequence = parsed_modules.sequence()?;
 
-        let validators = self.validate_validators(&mut checked_modules)?;
+        let cache = Cache::new(&self.root);
+
+        let hashes = cache::hash_modules(
+            &self.config.name,
+            &self.config.lints,
+            &parsed_modules,
+            &processing_sequence,
+        );
+
+        let config = fs::read_to_string(self.root.join("aiken.toml")).unwrap_or_default();
+
+        let manifest = Manifest::new(
+            &config,
+            keep_traces,
+            parsed_modules
+                .values()
+                .filter(|module| module.kind.is_validator())
+                .map(|module| (module.name.clone(), hashes[&module.name].clone()))
+                .collect(),
+        );
+
+        // Code generation needs every module, so nothing can be skipped
+        // unless the outputs of the last build are still up to date.
+        let uplc_gen = uplc_gen
+            && (uplc_dump
+                || cache.manifest().as_ref() != Some(&manifest)
+                || !self.root.join("plutus.json").exists());
+
+        let (checked_modules, cacheable) = self.type_check(
+            parsed_modules,
+            processing_sequence,
+            &hashes,
+            (!uplc_gen).then_some(&cache),
+        )?;
+
+        let validators = self.validate_validators(&checked_modules)?;
+
+        for name in cacheable {
+            cache.store_module(&name, &hashes[&name], &self.module_types[&name])?;
+
+            self.checked.insert(name.clone(), hashes[&name].clone());
+        }
 
         if uplc_gen {
-            let programs = self.code_gen(validators, &checked_modules)?;
+            let programs = self.code_gen(&validators, &checked_modules, keep_traces)?;
+
+            self.write_blueprint(&validators, &programs, &checked_modules)?;
 
             self.write_build_outputs(programs, uplc_dump)?;
+
+            cache.store_manifest(&manifest)?;
         }
 
         Ok(())
@@ -177,10 +296,29 @@ impl Project {
         &mut self,
         mut parsed_modules: ParsedModules,
         processing_sequence: Vec<String>,
-    ) -> Result<CheckedModules, Error> {
+        hashes: &HashMap<String, String>,
+        cache: Option<&Cache>,
+    ) -> Result<(CheckedModules, Vec<String>), Error> {
         let mut modules = HashMap::with_capacity(parsed_modules.len() + 1);
+        let mut cacheable = Vec::new();
+        let mut denied = Vec::new();
 
         for name in processing_sequence {
+            let hash = &hashes[&name];
+
+            if let Some(cache) = cache {
+                if self.checked.get(&name) == Some(hash) {
+                    continue;
+                }
+
+                if let Some(type_info) = cache.module(&name, hash) {
+                    self.module_types.insert(name.clone(), type_info);
+                    self.checked.insert(name, hash.clone());
+
+                    continue;
+                }
+            }
+
             if let Some(ParsedModule {
                 name,
                 path,
@@ -208,12 +346,32 @@ impl Project {
                         error,
                     })?;
 
-                // Register any warnings emitted as type warnings
-                let type_warnings = type_warnings
-                    .into_iter()
-                    .map(|w| Warning::from_type_warning(w, path.clone(), code.clone()));
+                // Modules with warnings are checked again every time, so
+                // that their warnings keep being reported. Allowed warnings
+                // don't count, they are never reported.
+                if type_warnings
+                    .iter()
+                    .all(|warning| self.config.lints.level(warning) == LintLevel::Allow)
+                {
+                    cacheable.push(name.clone());
+                }
 
-                self.warnings.extend(type_warnings);
+                // Register any warnings emitted as type warnings, or as errors
+                // when the lints deny them
+                for warning in type_warnings {
+                    match self.config.lints.level(&warning) {
+                        LintLevel::Allow => {}
+                        LintLevel::Warn if !self.deny_warnings => self.warnings.push(
+                            Warning::from_type_warning(warning, path.clone(), code.clone()),
+                        ),
+                        LintLevel::Warn | LintLevel::Deny => denied.push(Error::DeniedWarning {
+                            path: path.clone(),
+                            src: code.clone(),
+                            named: NamedSource::new(path.display().to_string(), code.clone()),
+                            warning,
+                        }),
+                    }
+                }
 
                 // Register the types from this module so they can be imported into
                 // other modules.
@@ -234,74 +392,139 @@ impl Project {
             }
         }
 
-        Ok(modules.into())
+        if denied.is_empty() {
+            Ok((modules.into(), cacheable))
+        } else {
+            Err(Error::List(denied))
+        }
     }
 
     fn validate_validators(
         &self,
-        checked_modules: &mut CheckedModules,
+        checked_modules: &CheckedModules,
     ) -> Result<Vec<(String, TypedFunction)>, Error> {
         let mut errors = Vec::new();
         let mut validators = Vec::new();
-        let mut indices_to_remove = Vec::new();
-
-        for module in checked_modules.validators() {
-            for (index, def) in module.ast.definitions().enumerate() {
-                if let Definition::Fn(func_def) = def {
-                    if VALIDATOR_NAMES.contains(&func_def.name.as_str()) {
-                        // validators must return a Bool
-                        if !func_def.return_type.is_bool() {
-                            errors.push(Error::ValidatorMustReturnBool {
-                                location: func_def.location,
-                                src: module.code.clone(),
-                                path: module.input_path.clone(),
-                                named: NamedSource::new(
-                                    module.input_path.display().to_string(),
-                                    module.code.clone(),
-                                ),
-                            })
-                        }
 
-                        // depending on name, validate the minimum number of arguments
-                        // if too low, push a new error on to errors
-                        if [MINT, CERT, WITHDRAWL].contains(&func_def.name.as_str())
-                            && func_def.arguments.len() < 2
-                        {
-                            errors.push(Error::WrongValidatorArity {
-                                location: func_def.location,
-                                src: module.code.clone(),
-                                path: module.input_path.clone(),
-                                named: NamedSource::new(
-                                    module.input_path.display().to_string(),
-                                    module.code.clone(),
-                                ),
-                                name: func_def.name.clone(),
-                                at_least: 2,
-                            })
-                        }
+        let data_types = checked_modules.data_types();
+
+        for module in checked_modules
+            .values()
+            .filter(|module| module.kind.is_validator())
+        {
+            let named =
+                || NamedSource::new(module.input_path.display().to_string(), module.code.clone());
 
-                        if SPEND == func_def.name && func_def.arguments.len() < 3 {
-                            errors.push(Error::WrongValidatorArity {
-                                location: func_def.location,
+            let mut purposes = HashSet::new();
+
+            // handlers used to be top-level functions, which are now ignored
+            for def in module.ast.definitions() {
+                if let Definition::Fn(func) = def {
+                    let purpose = match func.name.as_str() {
+                        "withdrawl" => WITHDRAW,
+                        "cert" => PUBLISH,
+                        name if VALIDATOR_NAMES.contains(&name) => name,
+                        _ => continue,
+                    };
+
+                    errors.push(Error::HandlerOutsideValidator {
+                        name: func.name.clone(),
+                        purpose: purpose.to_string(),
+                        location: func.location,
+                        src: module.code.clone(),
+                        path: module.input_path.clone(),
+                        named: named(),
+                    });
+                }
+            }
+
+            let blocks = module.ast.definitions().filter_map(|def| match def {
+                Definition::Validator(Validator {
+                    params, handlers, ..
+                }) => Some((params, handlers)),
+                _ => None,
+            });
+
+            for (params, handlers) in blocks {
+                // the parameters are shared by the handlers, report them once
+                let arguments = params
+                    .iter()
+                    .chain(handlers.iter().flat_map(|handler| &handler.arguments));
+
+                for arg in arguments.filter(|arg| holds_functions(&arg.tipo, &data_types)) {
+                    errors.push(Error::ValidatorArgumentNotData {
+                        location: arg.location,
+                        src: module.code.clone(),
+                        path: module.input_path.clone(),
+                        named: named(),
+                    })
+                }
+
+                for handler in handlers {
+                    // depending on the purpose, validate the number of arguments
+                    // coming after the parameters
+                    let expected = match handler.name.as_str() {
+                        SPEND => 3,
+                        MINT | WITHDRAW | PUBLISH => 2,
+                        _ => {
+                            errors.push(Error::UnknownValidatorPurpose {
+                                name: handler.name.clone(),
+                                location: handler.location,
                                 src: module.code.clone(),
                                 path: module.input_path.clone(),
-                                named: NamedSource::new(
-                                    module.input_path.display().to_string(),
-                                    module.code.clone(),
-                                ),
-                                name: func_def.name.clone(),
-                                at_least: 3,
-                            })
+                                named: named(),
+                            });
+
+                            continue;
                         }
+                    };
 
-                        validators.push((module.name.clone(), func_def.clone()));
-                        indices_to_remove.push(index);
+                    if !purposes.insert(handler.name.as_str()) {
+                        errors.push(Error::DuplicateValidatorPurpose {
+                            name: handler.name.clone(),
+                            location: handler.location,
+                            src: module.code.clone(),
+                            path: module.input_path.clone(),
+                            named: named(),
+                        });
                     }
-                }
-            }
 
-            for index in indices_to_remove.drain(0..) {
-                module.ast.definitions.remove(index);
+                    // validators must return a Bool
+                    if !handler.return_type.is_bool() {
+                        errors.push(Error::ValidatorMustReturnBool {
+                            location: handler.location,
+                            src: module.code.clone(),
+                            path: module.input_path.clone(),
+                            named: named(),
+                        })
+                    }
+
+                    if handler.arguments.len() != expected {
+                        errors.push(Error::WrongValidatorArity {
+                            location: handler.location,
+                            src: module.code.clone(),
+                            path: module.input_path.clone(),
+                            named: named(),
+                            name: handler.name.clone(),
+                            expected: expected as u8,
+                        })
+                    }
+
+                    let arguments = params
+                        .iter()
+                        .chain(&handler.arguments)
+                        .cloned()
+                        .collect::<Vec<_>>();
+
+                    // the script takes the parameters of the validator first
+                    validators.push((
+                        module.name.clone(),
+                        Function {
+                            arguments,
+                            ..handler.clone()
+                        },
+                    ));
+                }
             }
         }
 
@@ -314,9 +537,11 @@ impl Project {
 
     fn code_gen(
         &mut self,
-        validators: Vec<(String, TypedFunction)>,
+        validators: &[(String, TypedFunction)],
         checked_modules: &CheckedModules,
+        keep_traces: bool,
     ) -> Result<Vec<Script>, Error> {
+        let mut errors = Vec::new();
         let mut programs = Vec::new();
         let mut functions = HashMap::new();
         let mut type_aliases = HashMap::new();
@@ -354,6 +579,7 @@ impl Project {
                     Definition::ModuleConstant(mc) => {
                         constants.insert((module.name.clone(), mc.name.clone()), mc);
                     }
+                    Definition::Validator(_) => {}
                 }
             }
         }
@@ -363,8 +589,9 @@ impl Project {
                 arguments,
                 name,
                 body,
+                location,
                 ..
-            } = func_def;
+            } = func_def.clone();
 
             let mut generator = CodeGenerator::new(
                 &functions,
@@ -372,23 +599,94 @@ impl Project {
                 &data_types,
                 // &imports,
                 // &constants,
+                keep_traces,
             );
 
-            let program = generator.generate(body, arguments);
+            let program = match generator.generate(module_name, body, arguments) {
+                Ok(program) => program,
+                Err(generation_errors) => {
+                    for error in generation_errors {
+                        // The error may come from a function of another module.
+                        let module = &checked_modules[error.module()];
+
+                        errors.push(Error::CodeGen {
+                            path: module.input_path.clone(),
+                            src: module.code.clone(),
+                            named: NamedSource::new(
+                                module.input_path.display().to_string(),
+                                module.code.clone(),
+                            ),
+                            error,
+                        });
+                    }
+
+                    continue;
+                }
+            };
+
+            let plutus = self.config.plutus_version(module_name);
+
+            let language = plutus.language();
+
+            let module = &checked_modules[module_name];
+
+            for builtin in program.term.builtins() {
+                if !builtin.is_available_in(&language) {
+                    errors.push(Error::UnsupportedBuiltin {
+                        builtin: builtin.aiken_name(),
+                        plutus,
+                        location,
+                        src: module.code.clone(),
+                        path: module.input_path.clone(),
+                        named: NamedSource::new(
+                            module.input_path.display().to_string(),
+                            module.code.clone(),
+                        ),
+                    });
+                }
+            }
 
-            let script = Script::new(module_name, name, program.try_into().unwrap());
+            let script = Script::new(
+                module_name.clone(),
+                name,
+                program.try_into().unwrap(),
+                plutus,
+            );
 
             programs.push(script);
         }
 
-        Ok(programs)
+        if errors.is_empty() {
+            Ok(programs)
+        } else {
+            Err(Error::List(errors))
+        }
+    }
+
+    fn write_blueprint(
+        &self,
+        validators: &[(String, TypedFunction)],
+        programs: &[Script],
+        checked_modules: &CheckedModules,
+    ) -> Result<(), Error> {
+        let data_types = checked_modules.data_types();
+
+        let blueprint = Blueprint::new(&self.config, validators, programs, &data_types)?;
+
+        let mut json = serde_json::to_string_pretty(&blueprint).unwrap();
+
+        json.push('\n');
+
+        fs::write(self.root.join("plutus.json"), json)?;
+
+        Ok(())
     }
 
     fn write_build_outputs(&self, programs: Vec<Script>, uplc_dump: bool) -> Result<(), Error> {
         let assets = self.root.join("assets");
 
         for script in programs {
-            let script_output_dir = assets.join(script.module).join(script.name);
+            let script_output_dir = assets.join(&script.module).join(&script.name);
 
             fs::create_dir_all(&script_output_dir)?;
 
@@ -403,65 +701,67 @@ impl Project {
 
             let cbor = program.to_cbor().unwrap();
 
-            // Create file containing just the script cbor hex
-            let script_path = script_output_dir.join("script.txt");
-
-            let cbor_hex = hex::encode(&cbor);
-
-            fs::write(script_path, &cbor_hex)?;
-
-            // Create the payment script JSON file
-            let payment_script_path = script_output_dir.join("payment_script.json");
+            self.write_script_outputs(&script_output_dir, &script.name, cbor, script.plutus)?;
+        }
 
-            let mut bytes = Vec::new();
+        Ok(())
+    }
 
-            let mut encoder = minicbor::Encoder::new(&mut bytes);
+    /// Write the compiled script `cbor` of a `purpose` validator in `dir`: as
+    /// hex, as a `cardano-cli` payment script, and as what it's known by on
+    /// chain for the configured network.
+    fn write_script_outputs(
+        &self,
+        dir: &Path,
+        purpose: &str,
+        cbor: Vec<u8>,
+        plutus: PlutusVersion,
+    ) -> Result<(), Error> {
+        // Create file containing just the script cbor hex
+        let script_path = dir.join("script.txt");
 
-            encoder.bytes(&cbor).unwrap();
+        let cbor_hex = hex::encode(&cbor);
 
-            let prefixed_cbor_hex = hex::encode(&bytes);
+        fs::write(script_path, &cbor_hex)?;
 
-            let payment_script = json!({
-                "type": "PlutusScriptV2",
-                "description": "Generated by Aiken",
-                "cborHex": prefixed_cbor_hex
-            });
+        // Create the payment script JSON file
+        let payment_script_path = dir.join("payment_script.json");
 
-            fs::write(
-                payment_script_path,
-                serde_json::to_string_pretty(&payment_script).unwrap(),
-            )?;
+        let mut bytes = Vec::new();
 
-            // Create mainnet and testnet addresses
-            let plutus_script = babbage::PlutusV2Script(cbor.into());
+        let mut encoder = minicbor::Encoder::new(&mut bytes);
 
-            let hash = plutus_script.compute_hash();
+        encoder.bytes(&cbor).unwrap();
 
-            // mainnet
-            let mainnet_path = script_output_dir.join("mainnet.txt");
-            let mut mainnet_bytes: Vec<u8> = vec![0b01110001];
+        let prefixed_cbor_hex = hex::encode(&bytes);
 
-            mainnet_bytes.extend(hash.iter());
+        let payment_script = json!({
+            "type": plutus.script_type(),
+            "description": "Generated by Aiken",
+            "cborHex": prefixed_cbor_hex
+        });
 
-            let mainnet_addr = Address::from_bytes(&mainnet_bytes)
-                .unwrap()
-                .to_bech32()
-                .unwrap();
+        fs::write(
+            payment_script_path,
+            serde_json::to_string_pretty(&payment_script).unwrap(),
+        )?;
 
-            fs::write(mainnet_path, mainnet_addr)?;
+        let hash = plutus.script_hash(&cbor);
 
-            // testnet
-            let testnet_path = script_output_dir.join("testnet.txt");
-            let mut testnet_bytes: Vec<u8> = vec![0b01110000];
+        let network = self.config.network;
 
-            testnet_bytes.extend(hash.iter());
+        match purpose {
+            SPEND => {
+                let address = address::payment_address(hash, network, self.config.stake);
 
-            let testnet_addr = Address::from_bytes(&testnet_bytes)
-                .unwrap()
-                .to_bech32()
-                .unwrap();
+                fs::write(dir.join("address.txt"), address.to_bech32().unwrap())?;
+            }
+            MINT => fs::write(dir.join("policy_id.txt"), address::policy_id(hash))?,
+            _ => {
+                let address = address::stake_address(hash, network);
 
-            fs::write(testnet_path, testnet_addr)?;
+                fs::write(dir.join("stake_address.txt"), address.to_bech32().unwrap())?;
+            }
         }
 
         Ok(())
@@ -2,9 +2,14 @@ use std::{
     collections::{HashMap, HashSet},
     ops::{Deref, DerefMut},
     path::PathBuf,
+    sync::Arc,
 };
 
-use aiken_lang::ast::{ModuleKind, TypedModule, UntypedModule};
+use aiken_lang::{
+    ast::{DataType, Definition, ModuleKind, TypedModule, UntypedModule},
+    tipo::Type,
+    uplc::DataTypeKey,
+};
 use petgraph::{algo, graph::NodeIndex, Direction, Graph};
 
 use crate::error::Error;
@@ -187,6 +192,27 @@ impl CheckedModules {
             .filter(|module| module.kind.is_validator())
     }
 
+    /// The custom types defined by every module.
+    pub fn data_types(&self) -> HashMap<DataTypeKey, &DataType<Arc<Type>>> {
+        let mut data_types = HashMap::new();
+
+        for module in self.0.values() {
+            for def in module.ast.definitions() {
+                if let Definition::DataType(dt) = def {
+                    data_types.insert(
+                        DataTypeKey {
+                            module_name: module.name.clone(),
+                            defined_type: dt.name.clone(),
+                        },
+                        dt,
+                    );
+                }
+            }
+        }
+
+        data_types
+    }
+
     pub fn into_validators(self) -> impl Iterator<Item = CheckedModule> {
         self.0
             .into_values()
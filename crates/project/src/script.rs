@@ -1,18 +1,27 @@
 use uplc::ast::{NamedDeBruijn, Program};
 
+use crate::config::PlutusVersion;
+
 #[derive(Debug)]
 pub struct Script {
     pub module: String,
     pub name: String,
     pub program: Program<NamedDeBruijn>,
+    pub plutus: PlutusVersion,
 }
 
 impl Script {
-    pub fn new(module: String, name: String, program: Program<NamedDeBruijn>) -> Script {
+    pub fn new(
+        module: String,
+        name: String,
+        program: Program<NamedDeBruijn>,
+        plutus: PlutusVersion,
+    ) -> Script {
         Script {
             module,
             name,
             program,
+            plutus,
         }
     }
 }
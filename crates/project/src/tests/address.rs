@@ -0,0 +1,84 @@
+//! Test vectors of CIP-19, whose script hash is also the stake script hash
+//! of its script delegated addresses.
+
+use pallas::crypto::hash::Hash;
+
+use crate::{
+    address::{payment_address, policy_id, stake_address},
+    config::{Network, StakeCredential},
+};
+
+const SCRIPT: &str = "c37b1b5dc0669f1d3c61a6fddb2e8fde96be87b881c60bce8e8d542f";
+
+const STAKE_KEY: &str = "337b62cfff6403a06a3acbc34f8c46003c69fe79a3628cefa9c47251";
+
+fn hash(hex: &str) -> Hash<28> {
+    hex.parse().unwrap()
+}
+
+fn bech32(network: Network, stake: Option<StakeCredential>) -> String {
+    payment_address(hash(SCRIPT), network, stake)
+        .to_bech32()
+        .unwrap()
+}
+
+#[test]
+fn enterprise_address() {
+    assert_eq!(
+        bech32(Network::Mainnet, None),
+        "addr1w8phkx6acpnf78fuvxn0mkew3l0fd058hzquvz7w36x4gtcyjy7wx"
+    );
+    assert_eq!(
+        bech32(Network::Testnet, None),
+        "addr_test1wrphkx6acpnf78fuvxn0mkew3l0fd058hzquvz7w36x4gtcl6szpr"
+    );
+}
+
+#[test]
+fn base_address_with_stake_key() {
+    let stake = Some(StakeCredential::Key(hash(STAKE_KEY)));
+
+    assert_eq!(
+        bech32(Network::Mainnet, stake),
+        "addr1z8phkx6acpnf78fuvxn0mkew3l0fd058hzquvz7w36x4gten0d3vllmyqwsx5wktcd8cc3sq835lu7drv2xwl2wywfgs9yc0hh"
+    );
+    assert_eq!(
+        bech32(Network::Testnet, stake),
+        "addr_test1zrphkx6acpnf78fuvxn0mkew3l0fd058hzquvz7w36x4gten0d3vllmyqwsx5wktcd8cc3sq835lu7drv2xwl2wywfgsxj90mg"
+    );
+}
+
+#[test]
+fn base_address_with_stake_script() {
+    let stake = Some(StakeCredential::Script(hash(SCRIPT)));
+
+    assert_eq!(
+        bech32(Network::Mainnet, stake),
+        "addr1x8phkx6acpnf78fuvxn0mkew3l0fd058hzquvz7w36x4gt7r0vd4msrxnuwnccdxlhdjar77j6lg0wypcc9uar5d2shskhj42g"
+    );
+    assert_eq!(
+        bech32(Network::Testnet, stake),
+        "addr_test1xrphkx6acpnf78fuvxn0mkew3l0fd058hzquvz7w36x4gt7r0vd4msrxnuwnccdxlhdjar77j6lg0wypcc9uar5d2shs4p04xh"
+    );
+}
+
+#[test]
+fn script_stake_address() {
+    assert_eq!(
+        stake_address(hash(SCRIPT), Network::Mainnet)
+            .to_bech32()
+            .unwrap(),
+        "stake178phkx6acpnf78fuvxn0mkew3l0fd058hzquvz7w36x4gtcccycj5"
+    );
+    assert_eq!(
+        stake_address(hash(SCRIPT), Network::Testnet)
+            .to_bech32()
+            .unwrap(),
+        "stake_test17rphkx6acpnf78fuvxn0mkew3l0fd058hzquvz7w36x4gtcljw6kf"
+    );
+}
+
+#[test]
+fn policy_id_is_script_hash() {
+    assert_eq!(policy_id(hash(SCRIPT)), SCRIPT);
+}
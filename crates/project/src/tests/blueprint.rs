@@ -0,0 +1,271 @@
+use std::{collections::HashMap, sync::Arc};
+
+use aiken_lang::{
+    ast::{DataType, Definition, ModuleKind, TypedModule},
+    builtins, parser,
+    tipo::Type,
+    uplc::DataTypeKey,
+    IdGenerator,
+};
+use serde_json::{json, Value};
+
+use crate::blueprint::{definition_key, Schemas};
+
+const FIXTURES: &str = r#"
+pub type Tree {
+  Leaf
+  Node(Tree, Int, Tree)
+}
+
+pub type Box(a) {
+  Box(a)
+}
+
+pub type Interval {
+  lower: Int,
+  upper: Int,
+}
+
+pub fn tree(_t: Tree) {
+  True
+}
+
+pub fn boxed(_b: Box(Int)) {
+  True
+}
+
+pub fn nested(_b: Box(Option(Int))) {
+  True
+}
+
+pub fn flags(_b: Bool, _o: Option(ByteArray)) {
+  True
+}
+
+pub fn interval(_i: Interval) {
+  True
+}
+
+pub fn function(_f: fn(Int) -> Int) {
+  True
+}
+"#;
+
+/// Type-check the fixtures as the module `test/fixtures`.
+fn fixtures() -> TypedModule {
+    let id_gen = IdGenerator::new();
+
+    let mut module_types = HashMap::new();
+
+    module_types.insert("aiken".to_string(), builtins::prelude(&id_gen));
+    module_types.insert("aiken/builtin".to_string(), builtins::plutus(&id_gen));
+
+    let (mut ast, _) = parser::module(FIXTURES, ModuleKind::Lib).expect("failed to parse");
+
+    ast.name = "test/fixtures".to_string();
+
+    ast.infer(&id_gen, ModuleKind::Lib, "test", &module_types, &mut vec![])
+        .expect("failed to type check")
+}
+
+fn data_types(module: &TypedModule) -> HashMap<DataTypeKey, &DataType<Arc<Type>>> {
+    module
+        .definitions()
+        .filter_map(|def| match def {
+            Definition::DataType(data_type) => Some((
+                DataTypeKey {
+                    module_name: module.name.clone(),
+                    defined_type: data_type.name.clone(),
+                },
+                data_type,
+            )),
+            _ => None,
+        })
+        .collect()
+}
+
+/// The types of the arguments of the function `name`.
+fn arguments(module: &TypedModule, name: &str) -> Vec<Arc<Type>> {
+    module
+        .definitions()
+        .find_map(|def| match def {
+            Definition::Fn(func) if func.name == name => {
+                Some(func.arguments.iter().map(|arg| arg.tipo.clone()).collect())
+            }
+            _ => None,
+        })
+        .unwrap_or_else(|| panic!("no function {}", name))
+}
+
+/// The schemas of the arguments of the function `name`, with the definitions
+/// they refer to.
+fn schemas(name: &str) -> Result<(Vec<Value>, Value), String> {
+    let module = fixtures();
+
+    let data_types = data_types(&module);
+
+    let mut schemas = Schemas::new(&data_types);
+
+    let arguments = arguments(&module, name)
+        .iter()
+        .map(|tipo| schemas.schema(tipo))
+        .collect::<Result<_, _>>()?;
+
+    Ok((arguments, json!(schemas.definitions)))
+}
+
+#[test]
+fn recursive_type() {
+    let (arguments, definitions) = schemas("tree").unwrap();
+
+    let tree = json!({ "$ref": "#/definitions/test~1fixtures~1Tree" });
+
+    assert_eq!(arguments, vec![tree.clone()]);
+
+    assert_eq!(
+        definitions,
+        json!({
+            "test/fixtures/Tree": {
+                "title": "Tree",
+                "anyOf": [
+                    {
+                        "title": "Leaf",
+                        "dataType": "constructor",
+                        "index": 0,
+                        "fields": []
+                    },
+                    {
+                        "title": "Node",
+                        "dataType": "constructor",
+                        "index": 1,
+                        "fields": [tree, { "dataType": "integer" }, tree]
+                    }
+                ]
+            }
+        })
+    );
+}
+
+#[test]
+fn generic_instances() {
+    let (arguments, definitions) = schemas("boxed").unwrap();
+
+    assert_eq!(
+        arguments,
+        vec![json!({ "$ref": "#/definitions/test~1fixtures~1Box$Int" })]
+    );
+
+    assert_eq!(
+        definitions["test/fixtures/Box$Int"]["anyOf"][0]["fields"],
+        json!([{ "dataType": "integer" }])
+    );
+
+    let (arguments, definitions) = schemas("nested").unwrap();
+
+    assert_eq!(
+        arguments,
+        vec![json!({ "$ref": "#/definitions/test~1fixtures~1Box$Option$Int" })]
+    );
+
+    assert_eq!(
+        definitions["test/fixtures/Box$Option$Int"]["anyOf"][0]["fields"],
+        json!([{ "$ref": "#/definitions/Option$Int" }])
+    );
+
+    assert_eq!(
+        definitions["Option$Int"]["anyOf"][0]["fields"],
+        json!([{ "dataType": "integer" }])
+    );
+}
+
+#[test]
+fn generic_keys() {
+    let module = fixtures();
+
+    let nested = arguments(&module, "nested");
+
+    match nested[0].as_ref() {
+        Type::App {
+            module, name, args, ..
+        } => assert_eq!(
+            definition_key(module, name, args),
+            "test/fixtures/Box$Option$Int"
+        ),
+        _ => panic!("expected a custom type"),
+    }
+
+    assert_eq!(definition_key("", "Option", &[]), "Option");
+}
+
+#[test]
+fn bool_and_option() {
+    let (arguments, definitions) = schemas("flags").unwrap();
+
+    assert_eq!(
+        arguments,
+        vec![
+            json!({ "$ref": "#/definitions/Bool" }),
+            json!({ "$ref": "#/definitions/Option$ByteArray" }),
+        ]
+    );
+
+    assert_eq!(
+        definitions,
+        json!({
+            "Bool": {
+                "title": "Bool",
+                "anyOf": [
+                    { "title": "False", "dataType": "constructor", "index": 0, "fields": [] },
+                    { "title": "True", "dataType": "constructor", "index": 1, "fields": [] }
+                ]
+            },
+            "Option$ByteArray": {
+                "title": "Option",
+                "anyOf": [
+                    {
+                        "title": "Some",
+                        "dataType": "constructor",
+                        "index": 0,
+                        "fields": [{ "dataType": "bytes" }]
+                    },
+                    { "title": "None", "dataType": "constructor", "index": 1, "fields": [] }
+                ]
+            }
+        })
+    );
+}
+
+#[test]
+fn labelled_fields() {
+    let (_, definitions) = schemas("interval").unwrap();
+
+    assert_eq!(
+        definitions["test/fixtures/Interval"]["anyOf"][0]["fields"],
+        json!([
+            { "title": "lower", "dataType": "integer" },
+            { "title": "upper", "dataType": "integer" }
+        ])
+    );
+}
+
+#[test]
+fn functions_cannot_be_described() {
+    assert_eq!(
+        schemas("function"),
+        Err("functions can't be passed as data".to_string())
+    );
+}
+
+#[test]
+fn unknown_data_types() {
+    let module = fixtures();
+
+    let data_types = HashMap::new();
+
+    let mut schemas = Schemas::new(&data_types);
+
+    assert_eq!(
+        schemas.schema(&arguments(&module, "tree")[0]),
+        Err("unknown data type test/fixtures.Tree".to_string())
+    );
+}
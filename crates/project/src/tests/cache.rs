@@ -0,0 +1,176 @@
+use std::{
+    env, fs,
+    path::{Path, PathBuf},
+    process,
+};
+
+use crate::{
+    cache::{Cache, Manifest},
+    config::Config,
+    Project,
+};
+
+const UTIL: &str = r#"
+pub fn positive(n: Int) -> Bool {
+  n > 0
+}
+"#;
+
+const MINTER: &str = r#"
+use util
+
+validator {
+  fn mint(redeemer: Int, _ctx: Data) {
+    util.positive(redeemer)
+  }
+}
+"#;
+
+const TOML: &str = "name = \"test\"\nversion = \"1.0.0\"\n";
+
+/// Create a project named after `test`, with a library module `util` and a
+/// validator module `minter` depending on it.
+fn project(test: &str) -> PathBuf {
+    let dir = env::temp_dir().join(format!("aiken-cache-{}-{}", process::id(), test));
+
+    fs::create_dir_all(dir.join("lib")).unwrap();
+    fs::create_dir_all(dir.join("validators")).unwrap();
+    fs::write(dir.join("aiken.toml"), TOML).unwrap();
+    fs::write(dir.join("lib").join("util.ak"), UTIL).unwrap();
+    fs::write(dir.join("validators").join("minter.ak"), MINTER).unwrap();
+
+    dir
+}
+
+fn load(dir: &Path) -> Project {
+    Project::new(Config::load(dir.to_path_buf()).unwrap(), dir.to_path_buf())
+}
+
+/// The hash the module `name` was last cached with.
+fn cached_hash(dir: &Path, name: &str) -> Option<String> {
+    let path = dir
+        .join("build")
+        .join("cache")
+        .join("modules")
+        .join(format!("{}.json", name));
+
+    let json: serde_json::Value = serde_json::from_str(&fs::read_to_string(path).ok()?).ok()?;
+
+    json["hash"].as_str().map(String::from)
+}
+
+#[test]
+fn unchanged_modules_come_from_the_cache() {
+    let dir = project("unchanged_modules_come_from_the_cache");
+
+    let mut first = load(&dir);
+
+    first.check().unwrap();
+
+    // Mark the cached interface, so that using it is visible
+    let hash = cached_hash(&dir, "util").unwrap();
+
+    let mut type_info = first.module_types["util"].clone();
+
+    type_info.package = "from-cache".to_string();
+
+    Cache::new(&dir)
+        .store_module("util", &hash, &type_info)
+        .unwrap();
+
+    let mut second = load(&dir);
+
+    second.check().unwrap();
+
+    assert_eq!(second.module_types["util"].package, "from-cache");
+
+    fs::remove_dir_all(dir).unwrap();
+}
+
+#[test]
+fn changed_dependency_invalidates_dependents() {
+    let dir = project("changed_dependency_invalidates_dependents");
+
+    load(&dir).check().unwrap();
+
+    let util = cached_hash(&dir, "util").unwrap();
+    let minter = cached_hash(&dir, "minter").unwrap();
+
+    fs::write(
+        dir.join("lib").join("util.ak"),
+        UTIL.replace("n > 0", "n >= 1"),
+    )
+    .unwrap();
+
+    load(&dir).check().unwrap();
+
+    assert_ne!(cached_hash(&dir, "util").unwrap(), util);
+    assert_ne!(cached_hash(&dir, "minter").unwrap(), minter);
+
+    fs::remove_dir_all(dir).unwrap();
+}
+
+#[test]
+fn clean_wipes_the_cache() {
+    let dir = project("clean_wipes_the_cache");
+
+    let mut project = load(&dir);
+
+    project.build(false, false).unwrap();
+
+    assert!(Cache::new(&dir).manifest().is_some());
+    assert!(cached_hash(&dir, "util").is_some());
+
+    project.clean().unwrap();
+
+    assert!(!dir.join("build").join("cache").exists());
+    assert!(Cache::new(&dir).manifest().is_none());
+
+    // Cleaning an already clean project is fine
+    project.clean().unwrap();
+
+    fs::remove_dir_all(dir).unwrap();
+}
+
+#[test]
+fn manifest_follows_keep_traces_and_config() {
+    let dir = project("manifest_follows_keep_traces_and_config");
+
+    let cache = Cache::new(&dir);
+
+    load(&dir).build(false, false).unwrap();
+
+    let without_traces = cache.manifest().unwrap();
+
+    assert!(!without_traces.keep_traces);
+
+    load(&dir).build(false, true).unwrap();
+
+    let with_traces = cache.manifest().unwrap();
+
+    assert!(with_traces.keep_traces);
+    assert_ne!(with_traces, without_traces);
+    assert_eq!(with_traces.config, without_traces.config);
+    assert_eq!(with_traces.validators, without_traces.validators);
+
+    fs::write(
+        dir.join("aiken.toml"),
+        format!("{}description = \"changed\"\n", TOML),
+    )
+    .unwrap();
+
+    load(&dir).build(false, true).unwrap();
+
+    let changed_config = cache.manifest().unwrap();
+
+    assert_ne!(changed_config.config, with_traces.config);
+    assert_eq!(
+        changed_config,
+        Manifest {
+            config: changed_config.config.clone(),
+            ..with_traces
+        }
+    );
+
+    fs::remove_dir_all(dir).unwrap();
+}
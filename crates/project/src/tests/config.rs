@@ -0,0 +1,97 @@
+use std::{env, fs, process};
+
+use aiken_lang::ast::Span;
+
+use crate::{
+    config::{Config, PlutusVersion},
+    error::Error,
+};
+
+/// Load `aiken.toml` from a fresh directory named after `test`.
+fn load(test: &str, toml: &str) -> Result<Config, Error> {
+    let dir = env::temp_dir().join(format!("aiken-config-{}-{}", process::id(), test));
+
+    fs::create_dir_all(&dir).unwrap();
+    fs::write(dir.join("aiken.toml"), toml).unwrap();
+
+    let config = Config::load(dir.clone());
+
+    fs::remove_dir_all(dir).unwrap();
+
+    config
+}
+
+fn invalid_location(result: Result<Config, Error>) -> Option<Span> {
+    match result {
+        Err(Error::InvalidConfig { location, .. }) => location,
+        Err(error) => panic!("expected an invalid configuration, got {:?}", error),
+        Ok(_) => panic!("expected an invalid configuration"),
+    }
+}
+
+#[test]
+fn invalid_name_location() {
+    let toml = "description = \"€€€\"\nname = \"Bad Name\"\nversion = \"1.0.0\"\n";
+
+    // Spans count characters, the name comes after 20 of them
+    assert_eq!(
+        invalid_location(load("invalid_name_location", toml)),
+        Some(Span { start: 27, end: 37 })
+    );
+}
+
+#[test]
+fn invalid_version_location() {
+    let toml = "name = \"project\"\nversion = \"1.0\"\n";
+
+    assert_eq!(
+        invalid_location(load("invalid_version_location", toml)),
+        Some(Span { start: 27, end: 32 })
+    );
+}
+
+#[test]
+fn invalid_toml_location() {
+    let toml = "description = \"€€€\"\nname = \"project\"\nversion = 1.0.0\n";
+
+    // At the second dot of the version, which isn't a float
+    assert_eq!(
+        invalid_location(load("invalid_toml_location", toml)),
+        Some(Span { start: 50, end: 51 })
+    );
+}
+
+#[test]
+fn missing_name() {
+    let toml = "version = \"1.0.0\"\n";
+
+    assert!(matches!(
+        load("missing_name", toml),
+        Err(Error::InvalidConfig { .. })
+    ));
+}
+
+#[test]
+fn script_hashes() {
+    // The script always succeeding, as a CBOR byte string of its flat encoding
+    let cbor = hex::decode("4d01000033222220051200120011").unwrap();
+
+    assert_eq!(
+        hex::encode(PlutusVersion::V1.script_hash(&cbor)),
+        "67f33146617a5e61936081db3b2117cbf59bd2123748f58ac9678656"
+    );
+    assert_eq!(
+        hex::encode(PlutusVersion::V2.script_hash(&cbor)),
+        "793f8c8cffba081b2a56462fc219cc8fe652d6a338b62c7b134876e7"
+    );
+}
+
+#[test]
+fn module_plutus_version() {
+    let toml = "name = \"project\"\nversion = \"1.0.0\"\nplutus = \"v1\"\n\n[validators.minter]\nplutus = \"v2\"\n";
+
+    let config = load("module_plutus_version", toml).unwrap();
+
+    assert_eq!(config.plutus_version("minter"), PlutusVersion::V2);
+    assert_eq!(config.plutus_version("spender"), PlutusVersion::V1);
+}
@@ -0,0 +1,58 @@
+use std::{collections::HashMap, path::PathBuf};
+
+use aiken_lang::{ast::ModuleKind, builtins, parser, IdGenerator};
+use miette::NamedSource;
+use serde_json::json;
+
+use crate::error::Error;
+
+/// Type-check `src` as a library module, expecting it to fail.
+fn type_error(src: &str) -> Error {
+    let id_gen = IdGenerator::new();
+
+    let mut module_types = HashMap::new();
+
+    module_types.insert("aiken".to_string(), builtins::prelude(&id_gen));
+
+    let (mut ast, _) = parser::module(src, ModuleKind::Lib).expect("failed to parse");
+
+    ast.name = "test".to_string();
+
+    let error = ast
+        .infer(&id_gen, ModuleKind::Lib, "test", &module_types, &mut vec![])
+        .expect_err("type checking should fail");
+
+    let path = PathBuf::from("lib/test.ak");
+
+    Error::Type {
+        path: path.clone(),
+        src: src.to_string(),
+        named: NamedSource::new(path.display().to_string(), src.to_string()),
+        error,
+    }
+}
+
+#[test]
+fn json_labels_in_bytes() {
+    let error = type_error("pub fn f() -> Int {\n  \"€€€€€€€\"\n}");
+
+    let records = error.to_json();
+
+    assert_eq!(records.len(), 1);
+
+    let labels = records[0]["labels"].as_array().unwrap();
+
+    assert_eq!(labels.len(), 1);
+
+    // Each € is 3 bytes long
+    assert_eq!(labels[0]["start"], json!(22));
+    assert_eq!(labels[0]["end"], json!(45));
+    assert_eq!(
+        labels[0]["start_position"],
+        json!({ "line": 2, "column": 3 })
+    );
+    assert_eq!(
+        labels[0]["end_position"],
+        json!({ "line": 2, "column": 12 })
+    );
+}
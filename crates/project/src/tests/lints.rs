@@ -0,0 +1,158 @@
+use std::{
+    env, fs,
+    path::{Path, PathBuf},
+    process,
+};
+
+use aiken_lang::tipo::error::Warning as TypeWarning;
+use miette::Diagnostic;
+
+use crate::{config::Config, error::Error, Project};
+
+const LATER: &str = r#"
+pub fn later() -> Int {
+  todo
+}
+"#;
+
+/// Create a project named after `test` with the given `[lints]` and a library
+/// module `later` left to do.
+fn project(test: &str, lints: &str) -> PathBuf {
+    let dir = env::temp_dir().join(format!("aiken-lints-{}-{}", process::id(), test));
+
+    fs::create_dir_all(dir.join("lib")).unwrap();
+    fs::write(dir.join("lib").join("later.ak"), LATER).unwrap();
+
+    set_lints(&dir, lints);
+
+    dir
+}
+
+fn set_lints(dir: &Path, lints: &str) {
+    let toml = format!(
+        "name = \"test\"\nversion = \"1.0.0\"\n\n[lints]\n{}\n",
+        lints
+    );
+
+    fs::write(dir.join("aiken.toml"), toml).unwrap();
+}
+
+/// Check the project in `dir`, returning the warnings it reported.
+fn check(dir: &Path, deny_warnings: bool) -> Result<Vec<String>, Error> {
+    let mut project = Project::new(Config::load(dir.to_path_buf()).unwrap(), dir.to_path_buf());
+
+    if deny_warnings {
+        project.deny_warnings();
+    }
+
+    project.check()?;
+
+    Ok(project
+        .warnings
+        .iter()
+        .map(|warning| warning.to_string())
+        .collect())
+}
+
+/// The code of each error of a failed check, with whether it denies a `todo`.
+fn denied(result: Result<Vec<String>, Error>) -> Vec<(String, bool)> {
+    match result {
+        Err(Error::List(errors)) => errors
+            .iter()
+            .map(|error| {
+                let code = error
+                    .code()
+                    .map(|code| code.to_string())
+                    .unwrap_or_default();
+
+                let todo = matches!(
+                    error,
+                    Error::DeniedWarning {
+                        warning: TypeWarning::Todo { .. },
+                        ..
+                    }
+                );
+
+                (code, todo)
+            })
+            .collect(),
+        result => panic!("expected denied warnings, got {:?}", result),
+    }
+}
+
+#[test]
+fn warnings_are_reported_by_default() {
+    let dir = project("warnings_are_reported_by_default", "");
+
+    let warnings = check(&dir, false).unwrap();
+
+    fs::remove_dir_all(dir).unwrap();
+
+    assert_eq!(warnings.len(), 1);
+}
+
+#[test]
+fn deny_turns_warnings_into_errors() {
+    let dir = project("deny_turns_warnings_into_errors", "todo = \"deny\"");
+
+    let result = check(&dir, false);
+
+    fs::remove_dir_all(dir).unwrap();
+
+    assert_eq!(denied(result), vec![("aiken::lint".to_string(), true)]);
+}
+
+#[test]
+fn allow_suppresses_warnings() {
+    let dir = project("allow_suppresses_warnings", "todo = \"allow\"");
+
+    let warnings = check(&dir, false).unwrap();
+
+    let denying = check(&dir, true).unwrap();
+
+    fs::remove_dir_all(dir).unwrap();
+
+    assert!(warnings.is_empty());
+
+    // --deny-warnings leaves allowed warnings alone
+    assert!(denying.is_empty());
+}
+
+#[test]
+fn deny_warnings_promotes_warn() {
+    let dir = project("deny_warnings_promotes_warn", "todo = \"warn\"");
+
+    let result = check(&dir, true);
+
+    fs::remove_dir_all(dir).unwrap();
+
+    assert_eq!(denied(result), vec![("aiken::lint".to_string(), true)]);
+}
+
+#[test]
+fn modules_with_allowed_warnings_are_cached() {
+    let dir = project(
+        "modules_with_allowed_warnings_are_cached",
+        "todo = \"allow\"",
+    );
+
+    check(&dir, false).unwrap();
+
+    let cached = dir
+        .join("build")
+        .join("cache")
+        .join("modules")
+        .join("later.json")
+        .exists();
+
+    // The lints are part of the hash, so the cached module isn't used once
+    // its warnings are no longer allowed.
+    set_lints(&dir, "todo = \"warn\"");
+
+    let warnings = check(&dir, false).unwrap();
+
+    fs::remove_dir_all(dir).unwrap();
+
+    assert!(cached);
+    assert_eq!(warnings.len(), 1);
+}
@@ -0,0 +1,7 @@
+mod address;
+mod blueprint;
+mod cache;
+mod config;
+mod error;
+mod lints;
+mod project;
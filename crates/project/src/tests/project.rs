@@ -0,0 +1,113 @@
+use std::{env, fs, process};
+
+use crate::{
+    blueprint::Blueprint,
+    config::{Config, PlutusVersion},
+    error::Error,
+    Project,
+};
+
+const SERIALISE: &str = r#"
+use aiken/builtin
+
+validator {
+  fn mint(redeemer: Data, _ctx: Data) {
+    builtin.length_of_bytearray(builtin.serialise_data(redeemer)) > 0
+  }
+}
+"#;
+
+/// Build a project named after `test`, with its `aiken.toml` and a single
+/// validator module `minter`, returning the blueprint it wrote.
+fn build(test: &str, toml: &str, minter: &str) -> Result<Blueprint, Error> {
+    let dir = env::temp_dir().join(format!("aiken-project-{}-{}", process::id(), test));
+
+    fs::create_dir_all(dir.join("validators")).unwrap();
+    fs::write(dir.join("aiken.toml"), toml).unwrap();
+    fs::write(dir.join("validators").join("minter.ak"), minter).unwrap();
+
+    let result = Config::load(dir.clone())
+        .and_then(|config| Project::new(config, dir.clone()).build(false, false))
+        .map(|_| {
+            let json = fs::read_to_string(dir.join("plutus.json")).unwrap();
+
+            serde_json::from_str(&json).unwrap()
+        });
+
+    fs::remove_dir_all(dir).unwrap();
+
+    result
+}
+
+/// The Plutus version of each validator of `blueprint`, checking that its
+/// hash is computed for that version.
+fn plutus_versions(blueprint: Blueprint) -> Vec<PlutusVersion> {
+    blueprint
+        .validators
+        .iter()
+        .map(|validator| {
+            let plutus = validator
+                .plutus_version
+                .unwrap_or(blueprint.preamble.plutus_version);
+
+            let cbor = hex::decode(&validator.compiled_code).unwrap();
+
+            assert_eq!(validator.hash, hex::encode(plutus.script_hash(&cbor)));
+
+            plutus
+        })
+        .collect()
+}
+
+fn unsupported_builtins(result: Result<Blueprint, Error>) -> Vec<(String, PlutusVersion)> {
+    match result {
+        Err(Error::List(errors)) => errors
+            .into_iter()
+            .map(|error| match error {
+                Error::UnsupportedBuiltin {
+                    builtin, plutus, ..
+                } => (builtin, plutus),
+                error => panic!("unexpected error {:?}", error),
+            })
+            .collect(),
+        Err(error) => panic!("unexpected error {:?}", error),
+        Ok(_) => panic!("the build should fail"),
+    }
+}
+
+#[test]
+fn v1_rejects_serialise_data() {
+    let toml = "name = \"test\"\nversion = \"1.0.0\"\nplutus = \"v1\"\n";
+
+    assert_eq!(
+        unsupported_builtins(build("v1_rejects_serialise_data", toml, SERIALISE)),
+        [("serialise_data".to_string(), PlutusVersion::V1)]
+    );
+}
+
+#[test]
+fn v2_accepts_serialise_data() {
+    let toml = "name = \"test\"\nversion = \"1.0.0\"\n";
+
+    assert_eq!(
+        plutus_versions(build("v2_accepts_serialise_data", toml, SERIALISE).unwrap()),
+        [PlutusVersion::V2]
+    );
+}
+
+#[test]
+fn module_plutus_version_override() {
+    let toml = "name = \"test\"\nversion = \"1.0.0\"\nplutus = \"v1\"\n\n[validators.minter]\nplutus = \"v2\"\n";
+
+    assert_eq!(
+        plutus_versions(build("module_plutus_version_override", toml, SERIALISE).unwrap()),
+        [PlutusVersion::V2]
+    );
+
+    let toml = "name = \"test\"\nversion = \"1.0.0\"\n\n[validators.minter]\nplutus = \"v1\"\n";
+
+    assert_eq!(
+        unsupported_builtins(build("module_plutus_version_override_v1", toml, SERIALISE)),
+        [("serialise_data".to_string(), PlutusVersion::V1)]
+    );
+}
@@ -557,6 +557,31 @@ impl Program<DeBruijn> {
     }
 }
 
+impl<T> Term<T> {
+    /// The builtins used in this term, each once, in order of appearance.
+    pub fn builtins(&self) -> Vec<DefaultFunction> {
+        fn go<T>(term: &Term<T>, found: &mut Vec<DefaultFunction>) {
+            match term {
+                Term::Delay(term) | Term::Force(term) | Term::Lambda { body: term, .. } => {
+                    go(term, found)
+                }
+                Term::Apply { function, argument } => {
+                    go(function, found);
+                    go(argument, found);
+                }
+                Term::Builtin(builtin) if !found.contains(builtin) => found.push(*builtin),
+                Term::Var(_) | Term::Constant(_) | Term::Error | Term::Builtin(_) => {}
+            }
+        }
+
+        let mut found = Vec::new();
+
+        go(self, &mut found);
+
+        found
+    }
+}
+
 impl Term<NamedDeBruijn> {
     pub fn is_valid_script_result(&self) -> bool {
         !matches!(self, Term::Error)
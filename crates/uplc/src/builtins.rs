@@ -1,5 +1,7 @@
 use std::{fmt::Display, str::FromStr};
 
+use pallas_primitives::babbage::Language;
+use serde::{Deserialize, Serialize};
 use strum_macros::EnumIter;
 
 use flat_rs::de;
@@ -7,7 +9,7 @@ use flat_rs::de;
 /// All the possible builtin functions in Untyped Plutus Core.
 #[repr(u8)]
 #[allow(non_camel_case_types)]
-#[derive(Debug, Clone, PartialEq, Eq, Copy, EnumIter)]
+#[derive(Debug, Clone, PartialEq, Eq, Copy, EnumIter, Serialize, Deserialize)]
 pub enum DefaultFunction {
     // Integer functions
     AddInteger = 0,
@@ -327,6 +329,19 @@ impl Display for DefaultFunction {
 }
 
 impl DefaultFunction {
+    /// Whether scripts of the given Plutus version can use this function
+    pub fn is_available_in(&self, language: &Language) -> bool {
+        use DefaultFunction::*;
+
+        match language {
+            Language::PlutusV1 => !matches!(
+                self,
+                SerialiseData | VerifyEcdsaSecp256k1Signature | VerifySchnorrSecp256k1Signature
+            ),
+            Language::PlutusV2 => true,
+        }
+    }
+
     pub fn aiken_name(&self) -> String {
         use DefaultFunction::*;
 
@@ -388,3 +403,28 @@ impl DefaultFunction {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use strum::IntoEnumIterator;
+
+    use super::*;
+
+    #[test]
+    fn plutus_v1_builtins() {
+        let missing = DefaultFunction::iter()
+            .filter(|builtin| !builtin.is_available_in(&Language::PlutusV1))
+            .collect::<Vec<_>>();
+
+        assert_eq!(
+            missing,
+            [
+                DefaultFunction::VerifyEcdsaSecp256k1Signature,
+                DefaultFunction::VerifySchnorrSecp256k1Signature,
+                DefaultFunction::SerialiseData,
+            ]
+        );
+
+        assert!(DefaultFunction::iter().all(|builtin| builtin.is_available_in(&Language::PlutusV2)));
+    }
+}
@@ -0,0 +1,329 @@
+use pallas_codec::utils::{Int, KeyValuePairs};
+use pallas_primitives::babbage::{BigInt, Constr, PlutusData};
+use serde_json::{json, Value};
+
+#[derive(thiserror::Error, Debug, PartialEq, Eq)]
+#[error("Invalid Plutus data JSON: {0}")]
+pub struct Error(String);
+
+/// Convert [`PlutusData`] into the detailed JSON schema used by the
+/// `cardano-cli`, e.g. `{ "constructor": 0, "fields": [{ "int": 42 }] }`.
+///
+/// Integers that do not fit in 64 bits are rendered as strings.
+pub fn plutus_data_to_json(data: &PlutusData) -> Value {
+    match data {
+        PlutusData::Constr(constr) => json!({
+            "constructor": constr_index(constr.tag, constr.any_constructor),
+            "fields": constr.fields.iter().map(plutus_data_to_json).collect::<Vec<_>>(),
+        }),
+        PlutusData::Map(pairs) => json!({
+            "map": pairs
+                .iter()
+                .map(|(k, v)| json!({ "k": plutus_data_to_json(k), "v": plutus_data_to_json(v) }))
+                .collect::<Vec<_>>(),
+        }),
+        PlutusData::BigInt(n) => match big_int_to_i128(n) {
+            Some(n) => match i64::try_from(n) {
+                Ok(n) => json!({ "int": n }),
+                Err(_) => match u64::try_from(n) {
+                    Ok(n) => json!({ "int": n }),
+                    Err(_) => json!({ "int": n.to_string() }),
+                },
+            },
+            None => json!({ "int": big_int_to_string(n) }),
+        },
+        PlutusData::BoundedBytes(bytes) => json!({ "bytes": hex::encode(bytes.as_slice()) }),
+        PlutusData::Array(items) => json!({
+            "list": items.iter().map(plutus_data_to_json).collect::<Vec<_>>(),
+        }),
+    }
+}
+
+/// Parse the detailed JSON schema produced by [`plutus_data_to_json`] back
+/// into [`PlutusData`]. Integers may be given as JSON numbers or as strings
+/// of decimal digits when they are too large for a JSON number.
+pub fn json_to_plutus_data(json: &Value) -> Result<PlutusData, Error> {
+    let object = json
+        .as_object()
+        .ok_or_else(|| Error(format!("expected an object, found {}", json)))?;
+
+    if let Some(index) = object.get("constructor") {
+        let index = index
+            .as_u64()
+            .ok_or_else(|| Error(format!("invalid constructor index {}", index)))?;
+
+        let fields = object
+            .get("fields")
+            .and_then(Value::as_array)
+            .ok_or_else(|| Error(format!("constructor {} has no fields list", index)))?
+            .iter()
+            .map(json_to_plutus_data)
+            .collect::<Result<_, _>>()?;
+
+        let (tag, any_constructor) = constr_tag(index);
+
+        Ok(PlutusData::Constr(Constr {
+            tag,
+            any_constructor,
+            fields,
+        }))
+    } else if let Some(pairs) = object.get("map") {
+        let pairs = pairs
+            .as_array()
+            .ok_or_else(|| Error(format!("expected a list of pairs, found {}", pairs)))?
+            .iter()
+            .map(|pair| match (pair.get("k"), pair.get("v")) {
+                (Some(k), Some(v)) => Ok((json_to_plutus_data(k)?, json_to_plutus_data(v)?)),
+                _ => Err(Error(format!(
+                    "expected a {{ \"k\", \"v\" }} pair, found {}",
+                    pair
+                ))),
+            })
+            .collect::<Result<_, _>>()?;
+
+        Ok(PlutusData::Map(KeyValuePairs::Def(pairs)))
+    } else if let Some(items) = object.get("list") {
+        let items = items
+            .as_array()
+            .ok_or_else(|| Error(format!("expected a list, found {}", items)))?
+            .iter()
+            .map(json_to_plutus_data)
+            .collect::<Result<_, _>>()?;
+
+        Ok(PlutusData::Array(items))
+    } else if let Some(bytes) = object.get("bytes") {
+        let bytes = bytes
+            .as_str()
+            .and_then(|bytes| hex::decode(bytes).ok())
+            .ok_or_else(|| Error(format!("expected a hex string, found {}", bytes)))?;
+
+        Ok(PlutusData::BoundedBytes(bytes.into()))
+    } else if let Some(n) = object.get("int") {
+        let big_int = match n {
+            Value::Number(n) => n
+                .as_i64()
+                .map(i128::from)
+                .or_else(|| n.as_u64().map(i128::from)),
+            Value::String(n) => n.parse::<i128>().ok(),
+            _ => None,
+        }
+        .and_then(|n| Int::try_from(n).ok())
+        .map(BigInt::Int);
+
+        let big_int = match (big_int, n.as_str()) {
+            (Some(big_int), _) => big_int,
+            (None, Some(digits)) => {
+                string_to_big_int(digits).ok_or_else(|| Error(format!("invalid integer {}", n)))?
+            }
+            (None, None) => return Err(Error(format!("invalid integer {}", n))),
+        };
+
+        Ok(PlutusData::BigInt(big_int))
+    } else {
+        Err(Error(format!("unknown Plutus data {}", json)))
+    }
+}
+
+/// Translate a constructor index to a cbor tag.
+fn constr_tag(index: u64) -> (u64, Option<u64>) {
+    match index {
+        0..=6 => (121 + index, None),
+        7..=127 => (1280 + index - 7, None),
+        _ => (102, Some(index)),
+    }
+}
+
+/// Translate a cbor tag back to a constructor index.
+pub(crate) fn constr_index(tag: u64, any_constructor: Option<u64>) -> u64 {
+    match tag {
+        121..=127 => tag - 121,
+        1280..=1400 => tag - 1280 + 7,
+        _ => any_constructor.unwrap_or_default(),
+    }
+}
+
+pub(crate) fn big_int_to_i128(n: &BigInt) -> Option<i128> {
+    match n {
+        BigInt::Int(n) => Some(i128::from(*n)),
+        BigInt::BigUInt(bytes) => bytes_to_u128(bytes).and_then(|n| i128::try_from(n).ok()),
+        BigInt::BigNInt(bytes) => bytes_to_u128(bytes)
+            .and_then(|n| i128::try_from(n).ok())
+            .map(|n| -n - 1),
+    }
+}
+
+fn bytes_to_u128(bytes: &[u8]) -> Option<u128> {
+    let bytes = match bytes.iter().position(|b| *b != 0) {
+        Some(start) => &bytes[start..],
+        None => &[],
+    };
+
+    if bytes.len() > 16 {
+        return None;
+    }
+
+    Some(bytes.iter().fold(0, |acc, b| (acc << 8) | *b as u128))
+}
+
+/// Parse an arbitrarily large integer from base 10.
+fn string_to_big_int(digits: &str) -> Option<BigInt> {
+    let (negative, digits) = match digits.strip_prefix('-') {
+        Some(digits) => (true, digits),
+        None => (false, digits),
+    };
+
+    if digits.is_empty() {
+        return None;
+    }
+
+    // Big-endian bytes, multiplied by 10 for each digit.
+    let mut value: Vec<u8> = vec![];
+
+    for digit in digits.chars() {
+        let mut carry = digit.to_digit(10)?;
+
+        for byte in value.iter_mut().rev() {
+            let acc = *byte as u32 * 10 + carry;
+            *byte = acc as u8;
+            carry = acc >> 8;
+        }
+
+        if carry > 0 {
+            value.insert(0, carry as u8);
+        }
+    }
+
+    // A negative big integer `n` is encoded as `-1 - n`, so take one off.
+    if negative {
+        for byte in value.iter_mut().rev() {
+            let (difference, overflow) = byte.overflowing_sub(1);
+            *byte = difference;
+            if !overflow {
+                break;
+            }
+        }
+
+        Some(BigInt::BigNInt(value.into()))
+    } else {
+        Some(BigInt::BigUInt(value.into()))
+    }
+}
+
+/// Render an arbitrarily large integer in base 10.
+fn big_int_to_string(n: &BigInt) -> String {
+    let (negative, bytes) = match n {
+        BigInt::Int(n) => return i128::from(*n).to_string(),
+        BigInt::BigUInt(bytes) => (false, bytes),
+        BigInt::BigNInt(bytes) => (true, bytes),
+    };
+
+    // Repeated division by 10 over the big-endian bytes.
+    let mut digits = vec![];
+    let mut value: Vec<u8> = bytes.to_vec();
+
+    // A negative big integer `n` is encoded as `-1 - n`, so add one back.
+    if negative {
+        for byte in value.iter_mut().rev() {
+            let (sum, overflow) = byte.overflowing_add(1);
+            *byte = sum;
+            if !overflow {
+                break;
+            }
+        }
+
+        if value.iter().all(|b| *b == 0) {
+            value.insert(0, 1);
+        }
+    }
+
+    while value.iter().any(|b| *b != 0) {
+        let mut remainder = 0u32;
+
+        for byte in value.iter_mut() {
+            let acc = (remainder << 8) | *byte as u32;
+            *byte = (acc / 10) as u8;
+            remainder = acc % 10;
+        }
+
+        digits.push(char::from(b'0' + remainder as u8));
+    }
+
+    if digits.is_empty() {
+        digits.push('0');
+    }
+
+    if negative {
+        digits.push('-');
+    }
+
+    digits.iter().rev().collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn constr_with_fields() {
+        let data = PlutusData::Constr(Constr {
+            tag: 122,
+            any_constructor: None,
+            fields: vec![
+                PlutusData::BigInt(BigInt::Int(Int::from(-42))),
+                PlutusData::BoundedBytes(vec![0xde, 0xad].into()),
+                PlutusData::Map(KeyValuePairs::Def(vec![(
+                    PlutusData::Array(vec![]),
+                    PlutusData::BigInt(BigInt::BigUInt(vec![0x01; 17].into())),
+                )])),
+            ],
+        });
+
+        assert_eq!(
+            plutus_data_to_json(&data),
+            json!({
+                "constructor": 1,
+                "fields": [
+                    { "int": -42 },
+                    { "bytes": "dead" },
+                    { "map": [{ "k": { "list": [] }, "v": { "int": "341616807575530379006368233343265341697" } }] },
+                ]
+            })
+        );
+    }
+
+    #[test]
+    fn big_negative_int() {
+        let data = PlutusData::BigInt(BigInt::BigNInt(vec![0xff; 16].into()));
+
+        assert_eq!(
+            plutus_data_to_json(&data),
+            json!({ "int": "-340282366920938463463374607431768211456" })
+        );
+    }
+
+    #[test]
+    fn json_round_trip() {
+        let json = json!({
+            "constructor": 1,
+            "fields": [
+                { "int": -42 },
+                { "int": "-340282366920938463463374607431768211456" },
+                { "int": "341616807575530379006368233343265341697" },
+                { "bytes": "dead" },
+                { "map": [{ "k": { "list": [] }, "v": { "constructor": 200, "fields": [] } }] },
+            ]
+        });
+
+        let data = json_to_plutus_data(&json).unwrap();
+
+        assert_eq!(plutus_data_to_json(&data), json);
+    }
+
+    #[test]
+    fn invalid_json() {
+        assert_eq!(
+            json_to_plutus_data(&json!({ "bytes": "xyz" })),
+            Err(Error(r#"expected a hex string, found "xyz""#.to_string()))
+        );
+    }
+}
@@ -2,6 +2,7 @@ pub mod ast;
 pub mod builtins;
 mod debruijn;
 mod flat;
+pub mod json;
 pub mod machine;
 pub mod parser;
 mod pretty;
@@ -454,7 +454,7 @@ impl Value {
                     if *i == 0 {
                         1
                     } else {
-                        ((i.abs() as f64).log2().floor() as i64 / 64) + 1
+                        ((i.unsigned_abs() as f64).log2().floor() as i64 / 64) + 1
                     }
                 }
                 Constant::ByteString(b) => {
@@ -109,8 +109,8 @@ peg::parser! {
           = "integer" _+ i:big_number() { Constant::Integer(i as i128) }
 
         rule constant_bytestring() -> Constant
-          = "bytestring" _+ "#" i:ident()* {
-            Constant::ByteString(hex::decode(String::from_iter(i)).unwrap())
+          = "bytestring" _+ "#" i:ident()* {?
+            hex::decode(String::from_iter(i)).map(Constant::ByteString).or(Err("hex"))
           }
 
         rule constant_string() -> Constant
@@ -129,12 +129,12 @@ peg::parser! {
           = n:$("-"* ['0'..='9']+) {? n.parse().or(Err("i128")) }
 
         rule constant_data() -> Constant
-          = "data" _+ "#" i:ident()* {
-            Constant::Data(
-              PlutusData::decode_fragment(
-                  hex::decode(String::from_iter(i)).unwrap().as_slice()
-              ).unwrap()
-            )
+          = "data" _+ "#" i:ident()* {?
+            hex::decode(String::from_iter(i))
+              .ok()
+              .and_then(|bytes| PlutusData::decode_fragment(bytes.as_slice()).ok())
+              .map(Constant::Data)
+              .ok_or("cbor encoded data")
           }
 
         rule name() -> Name
@@ -170,4 +170,11 @@ mod test {
             }
         );
     }
+
+    #[test]
+    fn parse_invalid_constants() {
+        assert!(super::term("(con bytestring #0g)").is_err());
+        assert!(super::term("(con data #ff)").is_err());
+        assert!(super::term("(con data #182a)").is_ok());
+    }
 }
@@ -1,5 +1,7 @@
 use pallas_primitives::{
-    babbage::{CostMdls, MintedTx, Redeemer, TransactionInput, TransactionOutput},
+    babbage::{
+        CostMdls, ExUnits, MintedTx, Redeemer, RedeemerTag, TransactionInput, TransactionOutput,
+    },
     Fragment,
 };
 use pallas_traverse::{Era, MultiEraTx};
@@ -7,7 +9,7 @@ use pallas_traverse::{Era, MultiEraTx};
 use error::Error;
 pub use eval::get_script_and_datum_lookup_table;
 pub use phase_one::eval_phase_one;
-use script_context::{ResolvedInput, SlotConfig};
+use script_context::{ResolvedInput, ScriptArguments, SlotConfig};
 
 use crate::{
     ast::{DeBruijn, Program},
@@ -15,8 +17,10 @@ use crate::{
     PlutusData,
 };
 
+pub mod emulator;
 pub mod error;
 mod eval;
+pub mod from_plutus_data;
 mod phase_one;
 pub mod script_context;
 #[cfg(test)]
@@ -50,7 +54,7 @@ pub fn eval_phase_two(
             let mut collected_redeemers = vec![];
 
             for redeemer in rs.iter() {
-                let redeemer = eval::eval_redeemer(
+                let (redeemer, _logs) = eval::eval_redeemer(
                     tx,
                     utxos,
                     slot_config,
@@ -69,6 +73,118 @@ pub fn eval_phase_two(
     }
 }
 
+/// The outcome of evaluating a single redeemer, see [`eval_phase_two_detailed`].
+#[derive(Debug)]
+pub struct RedeemerReport {
+    pub tag: RedeemerTag,
+    pub index: u32,
+    /// The execution units consumed, up to the point of failure
+    /// when the evaluation failed.
+    pub ex_units: ExUnits,
+    pub logs: Vec<String>,
+    pub error: Option<Error>,
+}
+
+/// Like [`eval_phase_two`], but evaluate every redeemer even when one of
+/// them fails, and report the execution units and traces of each of them.
+/// Only phase one failures are returned as an error.
+pub fn eval_phase_two_detailed(
+    tx: &MintedTx,
+    utxos: &[ResolvedInput],
+    cost_mdls: Option<&CostMdls>,
+    initial_budget: Option<&ExBudget>,
+    slot_config: &SlotConfig,
+    run_phase_one: bool,
+) -> Result<Vec<RedeemerReport>, Error> {
+    let redeemers = tx.transaction_witness_set.redeemer.as_ref();
+
+    let lookup_table = get_script_and_datum_lookup_table(tx, utxos);
+
+    if run_phase_one {
+        eval_phase_one(tx, utxos, &lookup_table)?;
+    }
+
+    let reports = redeemers
+        .iter()
+        .flat_map(|rs| rs.iter())
+        .map(|redeemer| {
+            match eval::eval_redeemer(
+                tx,
+                utxos,
+                slot_config,
+                redeemer,
+                &lookup_table,
+                cost_mdls,
+                initial_budget,
+            ) {
+                Ok((evaluated, logs)) => RedeemerReport {
+                    tag: redeemer.tag.clone(),
+                    index: redeemer.index,
+                    ex_units: evaluated.ex_units,
+                    logs,
+                    error: None,
+                },
+                Err(err) => {
+                    let (ex_units, logs) = match &err {
+                        Error::RedeemerError { err, .. } => match err.as_ref() {
+                            Error::Machine(_, remaining, logs) => {
+                                let initial =
+                                    cost_mdls.and(initial_budget).copied().unwrap_or_default();
+
+                                (
+                                    ExUnits {
+                                        mem: (initial.mem - remaining.mem) as u32,
+                                        steps: (initial.cpu - remaining.cpu) as u64,
+                                    },
+                                    logs.clone(),
+                                )
+                            }
+                            _ => (ExUnits { mem: 0, steps: 0 }, vec![]),
+                        },
+                        _ => (ExUnits { mem: 0, steps: 0 }, vec![]),
+                    };
+
+                    RedeemerReport {
+                        tag: redeemer.tag.clone(),
+                        index: redeemer.index,
+                        ex_units,
+                        logs,
+                        error: Some(err),
+                    }
+                }
+            }
+        })
+        .collect();
+
+    Ok(reports)
+}
+
+/// Compute the datum, redeemer and script context that the
+/// script of the redeemer at the given pointer receives, without
+/// evaluating it. This is useful to inspect what a failing
+/// validator was actually applied to.
+pub fn script_arguments(
+    tx: &MintedTx,
+    utxos: &[ResolvedInput],
+    slot_config: &SlotConfig,
+    tag: RedeemerTag,
+    index: u32,
+) -> Result<ScriptArguments, Error> {
+    let redeemer = tx
+        .transaction_witness_set
+        .redeemer
+        .as_ref()
+        .and_then(|rs| rs.iter().find(|r| r.tag == tag && r.index == index))
+        .ok_or_else(|| Error::RedeemerNotFound {
+            tag: eval::redeemer_tag_to_string(&tag),
+            index,
+        })?;
+
+    let lookup_table = get_script_and_datum_lookup_table(tx, utxos);
+
+    eval::get_script_arguments(tx, utxos, slot_config, redeemer, &lookup_table)
+}
+
 /// This function is the same as [`eval_phase_two`]
 /// but the inputs are raw bytes.
 /// initial_budget expects (cpu, mem).
@@ -139,9 +255,9 @@ pub fn apply_params_to_script(
     params_bytes: &[u8], // PlutusData array
     plutus_script_bytes: &[u8],
 ) -> Result<Vec<u8>, Error> {
-    let params = match PlutusData::decode_fragment(params_bytes).unwrap() {
+    let params = match PlutusData::decode_fragment(params_bytes)? {
         PlutusData::Array(res) => res,
-        _ => unreachable!(),
+        _ => return Err(Error::ParamsNotAList),
     };
 
     let mut buffer = Vec::new();
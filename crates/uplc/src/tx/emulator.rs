@@ -0,0 +1,265 @@
+use std::collections::BTreeMap;
+
+use pallas_crypto::hash::Hash;
+use pallas_primitives::{
+    babbage::{CostMdls, MintedTx, Redeemer, TransactionInput, TransactionOutput},
+    Fragment,
+};
+use pallas_traverse::OriginalHash;
+
+use crate::machine::cost_model::ExBudget;
+
+use super::{
+    error::Error,
+    eval_phase_two,
+    script_context::{slot_to_posix, ResolvedInput, SlotConfig},
+};
+
+/// An in-memory ledger holding a UTxO set and a current slot.
+///
+/// Transactions submitted to the emulator go through the script related
+/// phase one checks and phase two evaluation. When they succeed, their
+/// inputs are consumed and their outputs become available to the next
+/// transaction, which makes it possible to chain transactions in tests
+/// without threading [`ResolvedInput`]s by hand.
+///
+/// Like on the ledger, a transaction marked as invalid must have a failing
+/// script, and only its collateral is consumed, in exchange for its
+/// collateral return.
+pub struct Emulator {
+    utxos: BTreeMap<TransactionInput, TransactionOutput>,
+    slot: u64,
+    slot_config: SlotConfig,
+    cost_mdls: Option<CostMdls>,
+    initial_budget: Option<ExBudget>,
+}
+
+/// A copy of the emulator state, see [`Emulator::snapshot`].
+#[derive(Debug, Clone)]
+pub struct Snapshot {
+    utxos: BTreeMap<TransactionInput, TransactionOutput>,
+    slot: u64,
+}
+
+/// The outcome of a successfully submitted transaction.
+#[derive(Debug, Clone)]
+pub struct Submitted {
+    pub tx_id: Hash<32>,
+    /// The evaluated redeemers, none for a transaction marked as invalid.
+    pub redeemers: Vec<Redeemer>,
+}
+
+impl Emulator {
+    pub fn new(slot_config: SlotConfig) -> Self {
+        Self {
+            utxos: BTreeMap::new(),
+            slot: slot_config.zero_slot,
+            slot_config,
+            cost_mdls: None,
+            initial_budget: None,
+        }
+    }
+
+    pub fn with_cost_models(mut self, cost_mdls: CostMdls) -> Self {
+        self.cost_mdls = Some(cost_mdls);
+        self
+    }
+
+    pub fn with_initial_budget(mut self, initial_budget: ExBudget) -> Self {
+        self.initial_budget = Some(initial_budget);
+        self
+    }
+
+    /// Add an output to the UTxO set without going through a transaction.
+    /// This is how the genesis state of a test is set up.
+    pub fn add_utxo(&mut self, input: TransactionInput, output: TransactionOutput) {
+        self.utxos.insert(input, output);
+    }
+
+    pub fn utxo(&self, input: &TransactionInput) -> Option<&TransactionOutput> {
+        self.utxos.get(input)
+    }
+
+    pub fn utxos(&self) -> Vec<ResolvedInput> {
+        self.utxos
+            .iter()
+            .map(|(input, output)| ResolvedInput {
+                input: input.clone(),
+                output: output.clone(),
+            })
+            .collect()
+    }
+
+    pub fn slot(&self) -> u64 {
+        self.slot
+    }
+
+    /// The POSIX time (in milliseconds) at the beginning of the current slot,
+    /// if it is not before the `zero_slot` of the slot configuration.
+    pub fn posix_time(&self) -> Option<u64> {
+        slot_to_posix(self.slot, &self.slot_config)
+    }
+
+    pub fn set_slot(&mut self, slot: u64) {
+        self.slot = slot;
+    }
+
+    pub fn advance_slots(&mut self, slots: u64) {
+        self.slot += slots;
+    }
+
+    pub fn snapshot(&self) -> Snapshot {
+        Snapshot {
+            utxos: self.utxos.clone(),
+            slot: self.slot,
+        }
+    }
+
+    pub fn restore(&mut self, snapshot: Snapshot) {
+        self.utxos = snapshot.utxos;
+        self.slot = snapshot.slot;
+    }
+
+    /// Validate and evaluate a CBOR encoded transaction against the current
+    /// state. On success, the spent inputs are removed from the UTxO set and
+    /// the outputs are added to it, or for a transaction marked as invalid,
+    /// the collateral is replaced by the collateral return. On failure, the
+    /// state is left untouched.
+    pub fn submit(&mut self, tx_bytes: &[u8]) -> Result<Submitted, Error> {
+        let tx = MintedTx::decode_fragment(tx_bytes)?;
+
+        self.validate_interval(&tx)?;
+
+        let resolved = self.resolve_inputs(&tx)?;
+
+        let evaluation = eval_phase_two(
+            &tx,
+            &resolved,
+            self.cost_mdls.as_ref(),
+            self.initial_budget.as_ref(),
+            &self.slot_config,
+            true,
+        );
+
+        // The id of a transaction is the hash of its body as it was
+        // submitted, which may not be encoded canonically.
+        let tx_id = tx.transaction_body.original_hash();
+
+        if !tx.success {
+            return self.collect_collateral(&tx, tx_id, evaluation);
+        }
+
+        let redeemers = evaluation?;
+
+        for input in tx.transaction_body.inputs.iter() {
+            self.utxos.remove(input);
+        }
+
+        for (index, output) in tx.transaction_body.outputs.iter().enumerate() {
+            self.utxos.insert(
+                TransactionInput {
+                    transaction_id: tx_id,
+                    index: index as u64,
+                },
+                output.clone(),
+            );
+        }
+
+        Ok(Submitted { tx_id, redeemers })
+    }
+
+    /// Apply a transaction marked as invalid, given the outcome of its
+    /// evaluation, which must be the failure of one of its scripts.
+    fn collect_collateral(
+        &mut self,
+        tx: &MintedTx,
+        tx_id: Hash<32>,
+        evaluation: Result<Vec<Redeemer>, Error>,
+    ) -> Result<Submitted, Error> {
+        match evaluation {
+            Ok(_) => return Err(Error::ValidationTagMismatch),
+            Err(Error::RedeemerError { ref err, .. }) if matches!(**err, Error::Machine(..)) => {}
+            Err(error) => return Err(error),
+        }
+
+        let body = &tx.transaction_body;
+
+        let collateral = match &body.collateral {
+            Some(collateral) if !collateral.is_empty() => collateral,
+            _ => return Err(Error::NoCollateral),
+        };
+
+        for input in collateral.iter() {
+            self.utxos.remove(input);
+        }
+
+        // The collateral return comes right after the outputs
+        if let Some(output) = &body.collateral_return {
+            self.utxos.insert(
+                TransactionInput {
+                    transaction_id: tx_id,
+                    index: body.outputs.len() as u64,
+                },
+                output.clone(),
+            );
+        }
+
+        Ok(Submitted {
+            tx_id,
+            redeemers: vec![],
+        })
+    }
+
+    fn validate_interval(&self, tx: &MintedTx) -> Result<(), Error> {
+        let lower_bound = tx.transaction_body.validity_interval_start;
+        let upper_bound = tx.transaction_body.ttl;
+
+        let too_early = matches!(lower_bound, Some(start) if start > self.slot);
+        let too_late = matches!(upper_bound, Some(ttl) if self.slot >= ttl);
+
+        if !too_early && !too_late {
+            Ok(())
+        } else {
+            Err(Error::OutsideValidityInterval {
+                slot: self.slot,
+                lower_bound,
+                upper_bound,
+            })
+        }
+    }
+
+    /// Look up every input, reference input and collateral input of a
+    /// transaction in the UTxO set.
+    fn resolve_inputs(&self, tx: &MintedTx) -> Result<Vec<ResolvedInput>, Error> {
+        let body = &tx.transaction_body;
+
+        let inputs = body
+            .inputs
+            .iter()
+            .chain(body.reference_inputs.iter().flatten())
+            .chain(body.collateral.iter().flatten());
+
+        let mut resolved: Vec<ResolvedInput> = Vec::new();
+
+        for input in inputs {
+            if resolved.iter().any(|r| r.input == *input) {
+                continue;
+            }
+
+            match self.utxos.get(input) {
+                Some(output) => resolved.push(ResolvedInput {
+                    input: input.clone(),
+                    output: output.clone(),
+                }),
+                None => {
+                    return Err(Error::UnknownInput {
+                        hash: input.transaction_id.to_string(),
+                        index: input.index,
+                    })
+                }
+            }
+        }
+
+        Ok(resolved)
+    }
+}
@@ -21,6 +21,8 @@ pub enum Error {
         missing: Vec<String>,
         extra: Vec<String>,
     },
+    #[error("No redeemer found for ({}, {})", tag, index)]
+    RedeemerNotFound { tag: String, index: u32 },
     #[error("Extraneous redeemer")]
     ExtraneousRedeemer,
     #[error("Resolved Input not found.")]
@@ -55,6 +57,38 @@ pub enum Error {
         index: u32,
         err: Box<Error>,
     },
+    #[error("Failed to decode {} from Plutus data: {}", expected, reason)]
+    FromPlutusData { expected: String, reason: String },
     #[error("Failed to apply parameters to Plutus script.")]
     ApplyParamsError,
+    #[error("Parameters must be given as a list of Plutus data.")]
+    ParamsNotAList,
+    #[error("Mismatch in required scripts: {} {}", .missing.join(" "), .extra.join(" "))]
+    RequiredScriptsMismatch {
+        missing: Vec<String>,
+        extra: Vec<String>,
+    },
+    #[error("Invalid genesis file: {0}")]
+    InvalidGenesis(String),
+    #[error("Input {}#{} not found in the UTxO set.", hash, index)]
+    UnknownInput { hash: String, index: u64 },
+    #[error(
+        "Slot {} is outside of the validity interval [{:?}, {:?}).",
+        slot,
+        lower_bound,
+        upper_bound
+    )]
+    OutsideValidityInterval {
+        slot: u64,
+        lower_bound: Option<u64>,
+        upper_bound: Option<u64>,
+    },
+    #[error("Slot {} is out of the range of the slot configuration.", slot)]
+    SlotOutOfRange { slot: u64 },
+    #[error("POSIX time {} is out of the range of the slot configuration.", time)]
+    TimeOutOfRange { time: u64 },
+    #[error("Transaction is marked as invalid but its scripts succeed.")]
+    ValidationTagMismatch,
+    #[error("Transaction is marked as invalid but has no collateral.")]
+    NoCollateral,
 }
@@ -16,31 +16,15 @@ use std::{collections::HashMap, convert::TryInto, ops::Deref, vec};
 
 use super::{
     script_context::{
-        ResolvedInput, ScriptContext, ScriptPurpose, SlotConfig, TimeRange, TxInInfo, TxInfo,
-        TxInfoV1, TxInfoV2, TxOut,
+        slot_range_to_posix_range, ResolvedInput, ScriptArguments, ScriptContext, ScriptPurpose,
+        SlotConfig, TimeRange, TxInInfo, TxInfo, TxInfoV1, TxInfoV2, TxOut,
     },
     to_plutus_data::{MintValue, ToPlutusData},
     Error,
 };
 use itertools::Itertools;
 
-fn slot_to_begin_posix_time(slot: u64, sc: &SlotConfig) -> u64 {
-    let ms_after_begin = (slot - sc.zero_slot) * sc.slot_length as u64;
-    sc.zero_time + ms_after_begin
-}
-
-fn slot_range_to_posix_time_range(slot_range: TimeRange, sc: &SlotConfig) -> TimeRange {
-    TimeRange {
-        lower_bound: slot_range
-            .lower_bound
-            .map(|lower_bound| slot_to_begin_posix_time(lower_bound, sc)),
-        upper_bound: slot_range
-            .upper_bound
-            .map(|upper_bound| slot_to_begin_posix_time(upper_bound, sc)),
-    }
-}
-
-fn redeemer_tag_to_string(redeemer_tag: &RedeemerTag) -> String {
+pub(crate) fn redeemer_tag_to_string(redeemer_tag: &RedeemerTag) -> String {
     match redeemer_tag {
         RedeemerTag::Spend => "Spend".to_string(),
         RedeemerTag::Mint => "Mint".to_string(),
@@ -314,13 +298,13 @@ fn get_tx_info_v1(
         .sorted()
         .collect();
 
-    let valid_range = slot_range_to_posix_time_range(
-        TimeRange {
+    let valid_range = slot_range_to_posix_range(
+        &TimeRange {
             lower_bound: body.validity_interval_start,
             upper_bound: body.ttl,
         },
         slot_config,
-    );
+    )?;
 
     let signatories = body
         .required_signers
@@ -391,13 +375,13 @@ fn get_tx_info_v2(
             .collect(),
     );
 
-    let valid_range = slot_range_to_posix_time_range(
-        TimeRange {
+    let valid_range = slot_range_to_posix_range(
+        &TimeRange {
             lower_bound: body.validity_interval_start,
             upper_bound: body.ttl,
         },
         slot_config,
-    );
+    )?;
 
     let signatories = body
         .required_signers
@@ -424,7 +408,7 @@ fn get_tx_info_v2(
                         &tx.transaction_body.withdrawals,
                     )
                     .unwrap(),
-                    r.clone(),
+                    r.data.clone(),
                 )
             })
             .collect(),
@@ -686,6 +670,41 @@ pub fn get_script_and_datum_lookup_table(
     DataLookupTable { datum, scripts }
 }
 
+/// Compute the datum, redeemer and script context that the script
+/// locked by the given redeemer's purpose is applied to.
+pub fn get_script_arguments(
+    tx: &MintedTx,
+    utxos: &[ResolvedInput],
+    slot_config: &SlotConfig,
+    redeemer: &Redeemer,
+    lookup_table: &DataLookupTable,
+) -> Result<ScriptArguments, Error> {
+    let purpose = get_script_purpose(
+        redeemer,
+        &tx.transaction_body.inputs,
+        &tx.transaction_body.mint,
+        &tx.transaction_body.certificates,
+        &tx.transaction_body.withdrawals,
+    )?;
+
+    let (script_version, datum) = match get_execution_purpose(utxos, &purpose, lookup_table)? {
+        ExecutionPurpose::WithDatum(script_version, datum) => (script_version, Some(datum)),
+        ExecutionPurpose::NoDatum(script_version) => (script_version, None),
+    };
+
+    let tx_info = match script_version {
+        ScriptVersion::V1(_) => get_tx_info_v1(tx, utxos, slot_config)?,
+        ScriptVersion::V2(_) => get_tx_info_v2(tx, utxos, slot_config)?,
+        ScriptVersion::Native(_) => return Err(Error::NativeScriptPhaseTwo),
+    };
+
+    Ok(ScriptArguments {
+        datum,
+        redeemer: redeemer.data.clone(),
+        script_context: ScriptContext { tx_info, purpose },
+    })
+}
+
 pub fn eval_redeemer(
     tx: &MintedTx,
     utxos: &[ResolvedInput],
@@ -694,7 +713,7 @@ pub fn eval_redeemer(
     lookup_table: &DataLookupTable,
     cost_mdls_opt: Option<&CostMdls>,
     initial_budget: Option<&ExBudget>,
-) -> Result<Redeemer, Error> {
+) -> Result<(Redeemer, Vec<String>), Error> {
     let result = || {
         let purpose = get_script_purpose(
             redeemer,
@@ -743,7 +762,8 @@ pub fn eval_redeemer(
                         Err(err) => return Err(Error::Machine(err, budget, logs)),
                     }
 
-                    let initial_budget = match initial_budget {
+                    // Without cost models, the machine runs with the default budget.
+                    let initial_budget = match cost_mdls_opt.and(initial_budget) {
                         Some(b) => *b,
                         None => ExBudget::default(),
                     };
@@ -758,7 +778,7 @@ pub fn eval_redeemer(
                         },
                     };
 
-                    Ok(new_redeemer)
+                    Ok((new_redeemer, logs))
                 }
                 ScriptVersion::V2(script) => {
                     let tx_info = get_tx_info_v2(tx, utxos, slot_config)?;
@@ -794,7 +814,8 @@ pub fn eval_redeemer(
                         Err(err) => return Err(Error::Machine(err, budget, logs)),
                     }
 
-                    let initial_budget = match initial_budget {
+                    // Without cost models, the machine runs with the default budget.
+                    let initial_budget = match cost_mdls_opt.and(initial_budget) {
                         Some(b) => *b,
                         None => ExBudget::default(),
                     };
@@ -809,7 +830,7 @@ pub fn eval_redeemer(
                         },
                     };
 
-                    Ok(new_redeemer)
+                    Ok((new_redeemer, logs))
                 }
                 ScriptVersion::Native(_) => Err(Error::NativeScriptPhaseTwo),
             },
@@ -847,7 +868,8 @@ pub fn eval_redeemer(
                         Err(err) => return Err(Error::Machine(err, budget, logs)),
                     }
 
-                    let initial_budget = match initial_budget {
+                    // Without cost models, the machine runs with the default budget.
+                    let initial_budget = match cost_mdls_opt.and(initial_budget) {
                         Some(b) => *b,
                         None => ExBudget::default(),
                     };
@@ -862,7 +884,7 @@ pub fn eval_redeemer(
                         },
                     };
 
-                    Ok(new_redeemer)
+                    Ok((new_redeemer, logs))
                 }
                 ScriptVersion::V2(script) => {
                     let tx_info = get_tx_info_v2(tx, utxos, slot_config)?;
@@ -897,7 +919,8 @@ pub fn eval_redeemer(
                         Err(err) => return Err(Error::Machine(err, budget, logs)),
                     }
 
-                    let initial_budget = match initial_budget {
+                    // Without cost models, the machine runs with the default budget.
+                    let initial_budget = match cost_mdls_opt.and(initial_budget) {
                         Some(b) => *b,
                         None => ExBudget::default(),
                     };
@@ -912,7 +935,7 @@ pub fn eval_redeemer(
                         },
                     };
 
-                    Ok(new_redeemer)
+                    Ok((new_redeemer, logs))
                 }
                 ScriptVersion::Native(_) => Err(Error::NativeScriptPhaseTwo),
             },
@@ -0,0 +1,897 @@
+use pallas_addresses::{
+    Address, Network, Pointer, ShelleyAddress, ShelleyDelegationPart, ShelleyPaymentPart,
+};
+use pallas_codec::utils::{AnyUInt, Bytes, CborWrap, Int, KeyValuePairs};
+use pallas_crypto::hash::Hash;
+use pallas_primitives::babbage::{
+    BigInt, Certificate, DatumOption, LegacyTransactionOutput, Multiasset, PlutusData,
+    PostAlonzoTransactionOutput, StakeCredential, TransactionInput, TransactionOutput, Value,
+};
+
+use super::{
+    error::Error,
+    script_context::{
+        ScriptContext, ScriptPurpose, TimeRange, TxInInfo, TxInfo, TxInfoV1, TxInfoV2, TxOut,
+    },
+    to_plutus_data::MintValue,
+};
+use crate::json::{big_int_to_i128, constr_index};
+
+/// The inverse of [`ToPlutusData`](super::to_plutus_data::ToPlutusData).
+///
+/// Some ledger types carry more information than their Plutus data
+/// representation. Decoding those either fills in what is not part of the
+/// representation (e.g. addresses are always decoded as mainnet addresses)
+/// or fails when the missing part can't be made up (e.g. a reference script
+/// of which only the hash is known).
+pub trait FromPlutusData: Sized {
+    fn from_plutus_data(data: &PlutusData) -> Result<Self, Error>;
+}
+
+fn decode_error(expected: &str, reason: impl Into<String>) -> Error {
+    Error::FromPlutusData {
+        expected: expected.to_string(),
+        reason: reason.into(),
+    }
+}
+
+/// A short description of some data, used in decode errors.
+fn describe(data: &PlutusData) -> String {
+    match data {
+        PlutusData::Constr(constr) => format!(
+            "constructor {} with {} field(s)",
+            constr_index(constr.tag, constr.any_constructor),
+            constr.fields.len()
+        ),
+        PlutusData::Map(pairs) => format!("map with {} entries", pairs.len()),
+        PlutusData::BigInt(_) => "integer".to_string(),
+        PlutusData::BoundedBytes(bytes) => format!("{} bytes", bytes.len()),
+        PlutusData::Array(items) => format!("list with {} items", items.len()),
+    }
+}
+
+/// Unwrap a constructor, returning its index and fields.
+fn constr<'a>(data: &'a PlutusData, expected: &str) -> Result<(u64, &'a [PlutusData]), Error> {
+    match data {
+        PlutusData::Constr(constr) => Ok((
+            constr_index(constr.tag, constr.any_constructor),
+            &constr.fields,
+        )),
+        _ => Err(decode_error(
+            expected,
+            format!("expected a constructor, got {}", describe(data)),
+        )),
+    }
+}
+
+/// Unwrap a constructor with a known index and number of fields.
+fn constr_fields<'a>(
+    data: &'a PlutusData,
+    expected: &str,
+    index: u64,
+    arity: usize,
+) -> Result<&'a [PlutusData], Error> {
+    match constr(data, expected)? {
+        (i, fields) if i == index && fields.len() == arity => Ok(fields),
+        _ => Err(decode_error(
+            expected,
+            format!(
+                "expected constructor {} with {} field(s), got {}",
+                index,
+                arity,
+                describe(data)
+            ),
+        )),
+    }
+}
+
+fn bytes<'a>(data: &'a PlutusData, expected: &str) -> Result<&'a Bytes, Error> {
+    match data {
+        PlutusData::BoundedBytes(bytes) => Ok(bytes),
+        _ => Err(decode_error(
+            expected,
+            format!("expected bytes, got {}", describe(data)),
+        )),
+    }
+}
+
+fn integer(data: &PlutusData, expected: &str) -> Result<i128, Error> {
+    match data {
+        PlutusData::BigInt(n) => {
+            big_int_to_i128(n).ok_or_else(|| decode_error(expected, "integer is too large"))
+        }
+        _ => Err(decode_error(
+            expected,
+            format!("expected an integer, got {}", describe(data)),
+        )),
+    }
+}
+
+fn map<'a>(
+    data: &'a PlutusData,
+    expected: &str,
+) -> Result<&'a KeyValuePairs<PlutusData, PlutusData>, Error> {
+    match data {
+        PlutusData::Map(pairs) => Ok(pairs),
+        _ => Err(decode_error(
+            expected,
+            format!("expected a map, got {}", describe(data)),
+        )),
+    }
+}
+
+impl FromPlutusData for Address {
+    fn from_plutus_data(data: &PlutusData) -> Result<Self, Error> {
+        let fields = constr_fields(data, "Address", 0, 2)?;
+
+        let payment_part = match constr(&fields[0], "Credential")? {
+            (0, [hash]) => ShelleyPaymentPart::Key(Hash::from_plutus_data(hash)?),
+            (1, [hash]) => ShelleyPaymentPart::Script(Hash::from_plutus_data(hash)?),
+            _ => {
+                return Err(decode_error(
+                    "Credential",
+                    format!(
+                        "expected constructor 0 or 1 with 1 field, got {}",
+                        describe(&fields[0])
+                    ),
+                ))
+            }
+        };
+
+        let delegation_part = match constr(&fields[1], "Option<StakingCredential>")? {
+            (0, [staking_credential]) => match constr(staking_credential, "StakingCredential")? {
+                (0, _) => match StakeCredential::from_plutus_data(staking_credential)? {
+                    StakeCredential::AddrKeyhash(hash) => ShelleyDelegationPart::Key(hash),
+                    StakeCredential::Scripthash(hash) => ShelleyDelegationPart::Script(hash),
+                },
+                (1, [slot, tx_idx, cert_idx]) => ShelleyDelegationPart::Pointer(Pointer::new(
+                    u64::from_plutus_data(slot)?,
+                    u64::from_plutus_data(tx_idx)?,
+                    u64::from_plutus_data(cert_idx)?,
+                )),
+                _ => {
+                    return Err(decode_error(
+                        "StakingCredential",
+                        format!(
+                            "expected constructor 0 with 1 field or constructor 1 with 3 fields, got {}",
+                            describe(staking_credential)
+                        ),
+                    ))
+                }
+            },
+            (1, []) => ShelleyDelegationPart::Null,
+            _ => {
+                return Err(decode_error(
+                    "Option<StakingCredential>",
+                    format!(
+                        "expected constructor 0 with 1 field or constructor 1 with no fields, got {}",
+                        describe(&fields[1])
+                    ),
+                ))
+            }
+        };
+
+        Ok(Address::Shelley(ShelleyAddress::new(
+            Network::Mainnet,
+            payment_part,
+            delegation_part,
+        )))
+    }
+}
+
+impl FromPlutusData for TransactionInput {
+    fn from_plutus_data(data: &PlutusData) -> Result<Self, Error> {
+        let fields = constr_fields(data, "TxOutRef", 0, 2)?;
+        let tx_id = constr_fields(&fields[0], "TxId", 0, 1)?;
+
+        Ok(TransactionInput {
+            transaction_id: Hash::from_plutus_data(&tx_id[0])?,
+            index: u64::from_plutus_data(&fields[1])?,
+        })
+    }
+}
+
+impl<const BYTES: usize> FromPlutusData for Hash<BYTES> {
+    fn from_plutus_data(data: &PlutusData) -> Result<Self, Error> {
+        let expected = format!("Hash<{}>", BYTES);
+
+        let bytes: [u8; BYTES] = bytes(data, &expected)?.as_slice().try_into().map_err(|_| {
+            decode_error(
+                &expected,
+                format!("expected {} bytes, got {}", BYTES, describe(data)),
+            )
+        })?;
+
+        Ok(Hash::new(bytes))
+    }
+}
+
+impl FromPlutusData for Bytes {
+    fn from_plutus_data(data: &PlutusData) -> Result<Self, Error> {
+        bytes(data, "Bytes").cloned()
+    }
+}
+
+impl<K: FromPlutusData, V: FromPlutusData> FromPlutusData for (K, V) {
+    fn from_plutus_data(data: &PlutusData) -> Result<Self, Error> {
+        let fields = constr_fields(data, "Tuple", 0, 2)?;
+
+        Ok((
+            K::from_plutus_data(&fields[0])?,
+            V::from_plutus_data(&fields[1])?,
+        ))
+    }
+}
+
+impl<A> FromPlutusData for Vec<A>
+where
+    A: FromPlutusData,
+{
+    fn from_plutus_data(data: &PlutusData) -> Result<Self, Error> {
+        match data {
+            PlutusData::Array(items) => items.iter().map(A::from_plutus_data).collect(),
+            _ => Err(decode_error(
+                "List",
+                format!("expected a list, got {}", describe(data)),
+            )),
+        }
+    }
+}
+
+impl<K, V> FromPlutusData for KeyValuePairs<K, V>
+where
+    K: FromPlutusData + Clone,
+    V: FromPlutusData + Clone,
+{
+    fn from_plutus_data(data: &PlutusData) -> Result<Self, Error> {
+        let pairs = map(data, "Map")?
+            .iter()
+            .map(|(k, v)| Ok((K::from_plutus_data(k)?, V::from_plutus_data(v)?)))
+            .collect::<Result<_, Error>>()?;
+
+        Ok(KeyValuePairs::Def(pairs))
+    }
+}
+
+impl<A: FromPlutusData> FromPlutusData for Option<A> {
+    fn from_plutus_data(data: &PlutusData) -> Result<Self, Error> {
+        match constr(data, "Option")? {
+            (0, [a]) => Ok(Some(A::from_plutus_data(a)?)),
+            (1, []) => Ok(None),
+            _ => Err(decode_error(
+                "Option",
+                format!(
+                    "expected constructor 0 with 1 field or constructor 1 with no fields, got {}",
+                    describe(data)
+                ),
+            )),
+        }
+    }
+}
+
+impl FromPlutusData for Option<DatumOption> {
+    // NoOutputDatum = 0 | OutputDatumHash = 1 | OutputDatum = 2
+    fn from_plutus_data(data: &PlutusData) -> Result<Self, Error> {
+        match constr(data, "OutputDatum")? {
+            (0, []) => Ok(None),
+            (1, [hash]) => Ok(Some(DatumOption::Hash(Hash::from_plutus_data(hash)?))),
+            (2, [datum]) => Ok(Some(DatumOption::Data(CborWrap(datum.clone())))),
+            _ => Err(decode_error(
+                "OutputDatum",
+                format!(
+                    "expected constructor 0, 1 or 2 with at most 1 field, got {}",
+                    describe(data)
+                ),
+            )),
+        }
+    }
+}
+
+impl FromPlutusData for AnyUInt {
+    fn from_plutus_data(data: &PlutusData) -> Result<Self, Error> {
+        u64::from_plutus_data(data).map(AnyUInt::U64)
+    }
+}
+
+impl FromPlutusData for Int {
+    fn from_plutus_data(data: &PlutusData) -> Result<Self, Error> {
+        Int::try_from(integer(data, "Int")?)
+            .map_err(|_| decode_error("Int", "integer doesn't fit in a cbor integer"))
+    }
+}
+
+impl FromPlutusData for BigInt {
+    fn from_plutus_data(data: &PlutusData) -> Result<Self, Error> {
+        match data {
+            PlutusData::BigInt(n) => Ok(n.clone()),
+            _ => Err(decode_error(
+                "BigInt",
+                format!("expected an integer, got {}", describe(data)),
+            )),
+        }
+    }
+}
+
+impl FromPlutusData for i64 {
+    fn from_plutus_data(data: &PlutusData) -> Result<Self, Error> {
+        i64::try_from(integer(data, "i64")?)
+            .map_err(|_| decode_error("i64", "integer is out of range"))
+    }
+}
+
+impl FromPlutusData for u64 {
+    fn from_plutus_data(data: &PlutusData) -> Result<Self, Error> {
+        u64::try_from(integer(data, "u64")?)
+            .map_err(|_| decode_error("u64", "integer is out of range"))
+    }
+}
+
+/// Split a value map into its lovelace quantity and the other assets.
+fn value_parts<A: FromPlutusData + Clone>(
+    data: &PlutusData,
+    expected: &str,
+) -> Result<(A, Multiasset<A>), Error> {
+    let mut lovelace = None;
+    let mut assets = vec![];
+
+    for (policy_id, tokens) in map(data, expected)?.iter() {
+        let policy_id = bytes(policy_id, expected)?;
+
+        if policy_id.is_empty() {
+            let ada = map(tokens, expected)?;
+
+            lovelace = match ada.first() {
+                Some((asset_name, quantity))
+                    if ada.len() == 1 && bytes(asset_name, expected)?.is_empty() =>
+                {
+                    Some(A::from_plutus_data(quantity)?)
+                }
+                _ => {
+                    return Err(decode_error(
+                        expected,
+                        "expected a single asset with an empty name under the ada policy",
+                    ))
+                }
+            };
+        } else {
+            assets.push((
+                Hash::from_plutus_data(&PlutusData::BoundedBytes(policy_id.clone()))?,
+                KeyValuePairs::from_plutus_data(tokens)?,
+            ));
+        }
+    }
+
+    match lovelace {
+        Some(lovelace) => Ok((lovelace, KeyValuePairs::Def(assets))),
+        None => Err(decode_error(expected, "missing ada policy")),
+    }
+}
+
+impl FromPlutusData for Value {
+    fn from_plutus_data(data: &PlutusData) -> Result<Self, Error> {
+        let (coin, assets) = value_parts::<u64>(data, "Value")?;
+
+        if assets.is_empty() {
+            Ok(Value::Coin(coin))
+        } else {
+            Ok(Value::Multiasset(coin, assets))
+        }
+    }
+}
+
+impl FromPlutusData for MintValue {
+    fn from_plutus_data(data: &PlutusData) -> Result<Self, Error> {
+        let (_, mint_value) = value_parts::<i64>(data, "MintValue")?;
+
+        Ok(MintValue { mint_value })
+    }
+}
+
+impl FromPlutusData for TxOut {
+    fn from_plutus_data(data: &PlutusData) -> Result<Self, Error> {
+        let (address, value, datum, script_ref) = match constr(data, "TxOut")? {
+            (0, [address, value, datum]) => (address, value, datum, None),
+            (0, [address, value, datum, script_ref]) => (address, value, datum, Some(script_ref)),
+            _ => {
+                return Err(decode_error(
+                    "TxOut",
+                    format!(
+                        "expected constructor 0 with 3 (v1) or 4 (v2) fields, got {}",
+                        describe(data)
+                    ),
+                ))
+            }
+        };
+
+        let address = Address::from_plutus_data(address)?.to_vec().into();
+        let value = Value::from_plutus_data(value)?;
+
+        match script_ref {
+            None => Ok(TxOut::V1(TransactionOutput::Legacy(
+                LegacyTransactionOutput {
+                    address,
+                    amount: value,
+                    datum_hash: Option::<Hash<32>>::from_plutus_data(datum)?,
+                },
+            ))),
+            Some(script_ref) => match Option::<Hash<28>>::from_plutus_data(script_ref)? {
+                None => Ok(TxOut::V2(TransactionOutput::PostAlonzo(
+                    PostAlonzoTransactionOutput {
+                        address,
+                        value,
+                        datum_option: Option::<DatumOption>::from_plutus_data(datum)?,
+                        script_ref: None,
+                    },
+                ))),
+                Some(hash) => Err(decode_error(
+                    "TxOut",
+                    format!("the reference script {} is only known by its hash", hash),
+                )),
+            },
+        }
+    }
+}
+
+impl FromPlutusData for StakeCredential {
+    fn from_plutus_data(data: &PlutusData) -> Result<Self, Error> {
+        let fields = constr_fields(data, "StakingCredential", 0, 1)?;
+
+        match constr(&fields[0], "Credential")? {
+            (0, [hash]) => Ok(StakeCredential::AddrKeyhash(Hash::from_plutus_data(hash)?)),
+            (1, [hash]) => Ok(StakeCredential::Scripthash(Hash::from_plutus_data(hash)?)),
+            _ => Err(decode_error(
+                "Credential",
+                format!(
+                    "expected constructor 0 or 1 with 1 field, got {}",
+                    describe(&fields[0])
+                ),
+            )),
+        }
+    }
+}
+
+impl FromPlutusData for Certificate {
+    fn from_plutus_data(data: &PlutusData) -> Result<Self, Error> {
+        match constr(data, "DCert")? {
+            (0, [stake_credential]) => Ok(Certificate::StakeRegistration(
+                StakeCredential::from_plutus_data(stake_credential)?,
+            )),
+            (1, [stake_credential]) => Ok(Certificate::StakeDeregistration(
+                StakeCredential::from_plutus_data(stake_credential)?,
+            )),
+            (2, [stake_credential, pool_keyhash]) => Ok(Certificate::StakeDelegation(
+                StakeCredential::from_plutus_data(stake_credential)?,
+                Hash::from_plutus_data(pool_keyhash)?,
+            )),
+            (4, [pool_keyhash, epoch]) => Ok(Certificate::PoolRetirement(
+                Hash::from_plutus_data(pool_keyhash)?,
+                u64::from_plutus_data(epoch)?,
+            )),
+            (index @ (3 | 5 | 6), _) => Err(decode_error(
+                "DCert",
+                format!(
+                    "constructor {} doesn't hold enough information to recover the certificate",
+                    index
+                ),
+            )),
+            _ => Err(decode_error(
+                "DCert",
+                format!("unexpected {}", describe(data)),
+            )),
+        }
+    }
+}
+
+impl FromPlutusData for PlutusData {
+    fn from_plutus_data(data: &PlutusData) -> Result<Self, Error> {
+        Ok(data.clone())
+    }
+}
+
+impl FromPlutusData for TimeRange {
+    fn from_plutus_data(data: &PlutusData) -> Result<Self, Error> {
+        let fields = constr_fields(data, "POSIXTimeRange", 0, 2)?;
+
+        // NegInf = 0 | Finite = 1 | PosInf = 2, the closure is not part of a TimeRange.
+        let bound = |data: &PlutusData, expected: &str, infinity: u64| {
+            let fields = constr_fields(data, expected, 0, 2)?;
+
+            bool::from_plutus_data(&fields[1])?;
+
+            match constr(&fields[0], "Extended")? {
+                (1, [time]) => Ok(Some(u64::from_plutus_data(time)?)),
+                (i, []) if i == infinity => Ok(None),
+                _ => Err(decode_error(
+                    expected,
+                    format!(
+                        "expected constructor 1 with 1 field or constructor {} with no fields, got {}",
+                        infinity,
+                        describe(&fields[0])
+                    ),
+                )),
+            }
+        };
+
+        Ok(TimeRange {
+            lower_bound: bound(&fields[0], "LowerBound", 0)?,
+            upper_bound: bound(&fields[1], "UpperBound", 2)?,
+        })
+    }
+}
+
+impl FromPlutusData for TxInInfo {
+    fn from_plutus_data(data: &PlutusData) -> Result<Self, Error> {
+        let fields = constr_fields(data, "TxInInfo", 0, 2)?;
+
+        Ok(TxInInfo {
+            out_ref: TransactionInput::from_plutus_data(&fields[0])?,
+            resolved: TxOut::from_plutus_data(&fields[1])?,
+        })
+    }
+}
+
+impl FromPlutusData for ScriptPurpose {
+    fn from_plutus_data(data: &PlutusData) -> Result<Self, Error> {
+        match constr(data, "ScriptPurpose")? {
+            (0, [policy_id]) => Ok(ScriptPurpose::Minting(Hash::from_plutus_data(policy_id)?)),
+            (1, [out_ref]) => Ok(ScriptPurpose::Spending(TransactionInput::from_plutus_data(
+                out_ref,
+            )?)),
+            (2, [stake_credential]) => Ok(ScriptPurpose::Rewarding(
+                StakeCredential::from_plutus_data(stake_credential)?,
+            )),
+            (3, [dcert]) => Ok(ScriptPurpose::Certifying(Certificate::from_plutus_data(
+                dcert,
+            )?)),
+            _ => Err(decode_error(
+                "ScriptPurpose",
+                format!(
+                    "expected constructor 0, 1, 2 or 3 with 1 field, got {}",
+                    describe(data)
+                ),
+            )),
+        }
+    }
+}
+
+impl FromPlutusData for TxInfo {
+    fn from_plutus_data(data: &PlutusData) -> Result<Self, Error> {
+        let tx_id = |data: &PlutusData| -> Result<Hash<32>, Error> {
+            Hash::from_plutus_data(&constr_fields(data, "TxId", 0, 1)?[0])
+        };
+
+        match constr(data, "TxInfo")? {
+            (
+                0,
+                [inputs, outputs, fee, mint, dcert, wdrl, valid_range, signatories, datums, id],
+            ) => Ok(TxInfo::V1(TxInfoV1 {
+                inputs: Vec::from_plutus_data(inputs)?,
+                outputs: Vec::from_plutus_data(outputs)?,
+                fee: Value::from_plutus_data(fee)?,
+                mint: MintValue::from_plutus_data(mint)?,
+                dcert: Vec::from_plutus_data(dcert)?,
+                wdrl: Vec::from_plutus_data(wdrl)?,
+                valid_range: TimeRange::from_plutus_data(valid_range)?,
+                signatories: Vec::from_plutus_data(signatories)?,
+                data: Vec::from_plutus_data(datums)?,
+                id: tx_id(id)?,
+            })),
+            (
+                0,
+                [inputs, reference_inputs, outputs, fee, mint, dcert, wdrl, valid_range, signatories, redeemers, datums, id],
+            ) => Ok(TxInfo::V2(TxInfoV2 {
+                inputs: Vec::from_plutus_data(inputs)?,
+                reference_inputs: Vec::from_plutus_data(reference_inputs)?,
+                outputs: Vec::from_plutus_data(outputs)?,
+                fee: Value::from_plutus_data(fee)?,
+                mint: MintValue::from_plutus_data(mint)?,
+                dcert: Vec::from_plutus_data(dcert)?,
+                wdrl: KeyValuePairs::from_plutus_data(wdrl)?,
+                valid_range: TimeRange::from_plutus_data(valid_range)?,
+                signatories: Vec::from_plutus_data(signatories)?,
+                redeemers: KeyValuePairs::from_plutus_data(redeemers)?,
+                data: KeyValuePairs::from_plutus_data(datums)?,
+                id: tx_id(id)?,
+            })),
+            _ => Err(decode_error(
+                "TxInfo",
+                format!(
+                    "expected constructor 0 with 10 (v1) or 12 (v2) fields, got {}",
+                    describe(data)
+                ),
+            )),
+        }
+    }
+}
+
+impl FromPlutusData for ScriptContext {
+    fn from_plutus_data(data: &PlutusData) -> Result<Self, Error> {
+        let fields = constr_fields(data, "ScriptContext", 0, 2)?;
+
+        Ok(ScriptContext {
+            tx_info: TxInfo::from_plutus_data(&fields[0])?,
+            purpose: ScriptPurpose::from_plutus_data(&fields[1])?,
+        })
+    }
+}
+
+impl FromPlutusData for bool {
+    fn from_plutus_data(data: &PlutusData) -> Result<Self, Error> {
+        match constr(data, "Bool")? {
+            (0, []) => Ok(false),
+            (1, []) => Ok(true),
+            _ => Err(decode_error(
+                "Bool",
+                format!(
+                    "expected constructor 0 or 1 with no fields, got {}",
+                    describe(data)
+                ),
+            )),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tx::{script_context::TxInfoV1, to_plutus_data::ToPlutusData};
+    use proptest::{collection::vec, option, prelude::*};
+
+    fn round_trip<T: FromPlutusData + ToPlutusData>(value: T) {
+        let data = value.to_plutus_data();
+        let decoded = T::from_plutus_data(&data).unwrap();
+
+        assert_eq!(decoded.to_plutus_data(), data);
+    }
+
+    prop_compose! {
+        fn hash28()(bytes: [u8; 28]) -> Hash<28> {
+            Hash::new(bytes)
+        }
+    }
+
+    prop_compose! {
+        fn hash32()(bytes: [u8; 32]) -> Hash<32> {
+            Hash::new(bytes)
+        }
+    }
+
+    prop_compose! {
+        fn bytes_up_to_32()(bytes in vec(any::<u8>(), 0..32)) -> Bytes {
+            bytes.into()
+        }
+    }
+
+    fn stake_credential() -> impl Strategy<Value = StakeCredential> {
+        prop_oneof![
+            hash28().prop_map(StakeCredential::AddrKeyhash),
+            hash28().prop_map(StakeCredential::Scripthash),
+        ]
+    }
+
+    fn address() -> impl Strategy<Value = Address> {
+        let payment = prop_oneof![
+            hash28().prop_map(ShelleyPaymentPart::Key),
+            hash28().prop_map(ShelleyPaymentPart::Script),
+        ];
+
+        let delegation = prop_oneof![
+            hash28().prop_map(ShelleyDelegationPart::Key),
+            hash28().prop_map(ShelleyDelegationPart::Script),
+            Just(ShelleyDelegationPart::Null),
+            (any::<u32>(), any::<u32>(), any::<u32>()).prop_map(|(slot, tx_idx, cert_idx)| {
+                ShelleyDelegationPart::Pointer(Pointer::new(
+                    slot as u64,
+                    tx_idx as u64,
+                    cert_idx as u64,
+                ))
+            }),
+        ];
+
+        (payment, delegation).prop_map(|(payment, delegation)| {
+            Address::Shelley(ShelleyAddress::new(Network::Testnet, payment, delegation))
+        })
+    }
+
+    prop_compose! {
+        fn transaction_input()(transaction_id in hash32(), index: u64) -> TransactionInput {
+            TransactionInput { transaction_id, index }
+        }
+    }
+
+    fn multiasset<A: Arbitrary + Clone + 'static>(
+    ) -> impl Strategy<Value = KeyValuePairs<Hash<28>, KeyValuePairs<Bytes, A>>> {
+        vec(
+            (
+                hash28(),
+                vec((bytes_up_to_32(), any::<A>()), 1..3).prop_map(KeyValuePairs::Def),
+            ),
+            0..3,
+        )
+        .prop_map(KeyValuePairs::Def)
+    }
+
+    fn value() -> impl Strategy<Value = Value> {
+        prop_oneof![
+            any::<u64>().prop_map(Value::Coin),
+            (any::<u64>(), multiasset::<u64>())
+                .prop_map(|(coin, assets)| Value::Multiasset(coin, assets)),
+        ]
+    }
+
+    fn datum_option() -> impl Strategy<Value = Option<DatumOption>> {
+        option::of(prop_oneof![
+            hash32().prop_map(DatumOption::Hash),
+            any::<i64>().prop_map(|n| DatumOption::Data(CborWrap(n.to_plutus_data()))),
+        ])
+    }
+
+    fn tx_out() -> impl Strategy<Value = TxOut> {
+        prop_oneof![
+            (address(), value(), option::of(hash32())).prop_map(|(address, amount, datum_hash)| {
+                TxOut::V1(TransactionOutput::Legacy(LegacyTransactionOutput {
+                    address: address.to_vec().into(),
+                    amount,
+                    datum_hash,
+                }))
+            }),
+            (address(), value(), datum_option()).prop_map(|(address, value, datum_option)| {
+                TxOut::V2(TransactionOutput::PostAlonzo(PostAlonzoTransactionOutput {
+                    address: address.to_vec().into(),
+                    value,
+                    datum_option,
+                    script_ref: None,
+                }))
+            }),
+        ]
+    }
+
+    prop_compose! {
+        fn time_range()(
+            lower_bound in option::of(any::<u64>()),
+            upper_bound in option::of(any::<u64>())
+        ) -> TimeRange {
+            TimeRange { lower_bound, upper_bound }
+        }
+    }
+
+    fn certificate() -> impl Strategy<Value = Certificate> {
+        prop_oneof![
+            stake_credential().prop_map(Certificate::StakeRegistration),
+            stake_credential().prop_map(Certificate::StakeDeregistration),
+            (stake_credential(), hash28())
+                .prop_map(|(cred, pool)| Certificate::StakeDelegation(cred, pool)),
+            (hash28(), any::<u64>())
+                .prop_map(|(pool, epoch)| Certificate::PoolRetirement(pool, epoch)),
+        ]
+    }
+
+    fn script_purpose() -> impl Strategy<Value = ScriptPurpose> {
+        prop_oneof![
+            hash28().prop_map(ScriptPurpose::Minting),
+            transaction_input().prop_map(ScriptPurpose::Spending),
+            stake_credential().prop_map(ScriptPurpose::Rewarding),
+            certificate().prop_map(ScriptPurpose::Certifying),
+        ]
+    }
+
+    prop_compose! {
+        fn tx_in_info()(out_ref in transaction_input(), resolved in tx_out()) -> TxInInfo {
+            TxInInfo { out_ref, resolved }
+        }
+    }
+
+    prop_compose! {
+        fn tx_info_v1()(
+            inputs in vec(tx_in_info(), 0..3),
+            outputs in vec(tx_out(), 0..3),
+            fee in any::<u64>(),
+            mint_value in multiasset::<i64>(),
+            dcert in vec(certificate(), 0..2),
+            wdrl in vec((bytes_up_to_32(), any::<u64>()), 0..2),
+            valid_range in time_range(),
+            signatories in vec(hash28(), 0..2),
+            data in vec((hash32(), any::<i64>().prop_map(|n| n.to_plutus_data())), 0..2),
+            id in hash32(),
+        ) -> TxInfo {
+            TxInfo::V1(TxInfoV1 {
+                inputs,
+                outputs,
+                fee: Value::Coin(fee),
+                mint: MintValue { mint_value },
+                dcert,
+                wdrl,
+                valid_range,
+                signatories,
+                data,
+                id,
+            })
+        }
+    }
+
+    prop_compose! {
+        fn tx_info_v2()(
+            inputs in vec(tx_in_info(), 0..3),
+            reference_inputs in vec(tx_in_info(), 0..2),
+            outputs in vec(tx_out(), 0..3),
+            fee in any::<u64>(),
+            mint_value in multiasset::<i64>(),
+            dcert in vec(certificate(), 0..2),
+            wdrl in vec((bytes_up_to_32(), any::<u64>()), 0..2),
+            valid_range in time_range(),
+            signatories in vec(hash28(), 0..2),
+            redeemers in vec((script_purpose(), any::<i64>().prop_map(|n| n.to_plutus_data())), 0..2),
+            data in vec((hash32(), any::<i64>().prop_map(|n| n.to_plutus_data())), 0..2),
+            id in hash32(),
+        ) -> TxInfo {
+            TxInfo::V2(TxInfoV2 {
+                inputs,
+                reference_inputs,
+                outputs,
+                fee: Value::Coin(fee),
+                mint: MintValue { mint_value },
+                dcert,
+                wdrl: KeyValuePairs::Def(wdrl),
+                valid_range,
+                signatories,
+                redeemers: KeyValuePairs::Def(redeemers),
+                data: KeyValuePairs::Def(data),
+                id,
+            })
+        }
+    }
+
+    proptest! {
+        #[test]
+        fn address_round_trip(address in address()) {
+            round_trip(address);
+        }
+
+        #[test]
+        fn value_round_trip(value in value()) {
+            round_trip(value);
+        }
+
+        #[test]
+        fn tx_out_round_trip(tx_out in tx_out()) {
+            round_trip(tx_out);
+        }
+
+        #[test]
+        fn time_range_round_trip(time_range in time_range()) {
+            round_trip(time_range);
+        }
+
+        #[test]
+        fn script_purpose_round_trip(purpose in script_purpose()) {
+            round_trip(purpose);
+        }
+
+        #[test]
+        fn script_context_v1_round_trip(tx_info in tx_info_v1(), purpose in script_purpose()) {
+            round_trip(ScriptContext { tx_info, purpose });
+        }
+
+        #[test]
+        fn script_context_v2_round_trip(tx_info in tx_info_v2(), purpose in script_purpose()) {
+            round_trip(ScriptContext { tx_info, purpose });
+        }
+    }
+
+    #[test]
+    fn descriptive_errors() {
+        let data = true.to_plutus_data();
+
+        assert_eq!(
+            TransactionInput::from_plutus_data(&data)
+                .unwrap_err()
+                .to_string(),
+            "Failed to decode TxOutRef from Plutus data: expected constructor 0 with 2 field(s), got constructor 1 with 0 field(s)"
+        );
+
+        assert!(matches!(
+            Hash::<28>::from_plutus_data(&PlutusData::BoundedBytes(vec![0; 27].into())),
+            Err(Error::FromPlutusData { .. })
+        ));
+    }
+}
@@ -58,16 +58,10 @@ pub fn validate_missing_scripts(
         .collect();
 
     if !missing.is_empty() || !extra.is_empty() {
-        let missing_errors = missing.join(" ");
-        let extra_errors = extra.join(" ");
-
-        unreachable!(
-            "Mismatch in required scripts: {} {}",
-            missing_errors, extra_errors
-        );
+        Err(Error::RequiredScriptsMismatch { missing, extra })
+    } else {
+        Ok(())
     }
-
-    Ok(())
 }
 
 pub fn scripts_needed(
@@ -1,12 +1,12 @@
 use pallas_codec::utils::KeyValuePairs;
 use pallas_crypto::hash::Hash;
 use pallas_primitives::babbage::{
-    AddrKeyhash, Certificate, Coin, DatumHash, PlutusData, PolicyId, Redeemer, RewardAccount,
+    AddrKeyhash, Certificate, Coin, DatumHash, PlutusData, PolicyId, RewardAccount,
     StakeCredential, TransactionInput, TransactionOutput, Value, Withdrawals,
 };
 use serde::Deserialize;
 
-use super::to_plutus_data::MintValue;
+use super::{error::Error, to_plutus_data::MintValue};
 
 #[derive(Debug, PartialEq, Clone, Deserialize)]
 pub struct ResolvedInput {
@@ -58,7 +58,7 @@ pub struct TxInfoV2 {
     pub wdrl: Withdrawals,
     pub valid_range: TimeRange,
     pub signatories: Vec<AddrKeyhash>,
-    pub redeemers: KeyValuePairs<ScriptPurpose, Redeemer>,
+    pub redeemers: KeyValuePairs<ScriptPurpose, PlutusData>,
     pub data: KeyValuePairs<DatumHash, PlutusData>,
     pub id: Hash<32>,
 }
@@ -75,6 +75,14 @@ pub struct ScriptContext {
     pub purpose: ScriptPurpose,
 }
 
+/// The arguments a script is applied to when validating a redeemer.
+#[derive(Debug, PartialEq, Clone)]
+pub struct ScriptArguments {
+    pub datum: Option<PlutusData>,
+    pub redeemer: PlutusData,
+    pub script_context: ScriptContext,
+}
+
 //---- Time conversion: slot range => posix time range
 #[derive(Debug, PartialEq, Eq, Clone)]
 pub struct TimeRange {
@@ -82,6 +90,7 @@ pub struct TimeRange {
     pub upper_bound: Option<u64>,
 }
 
+#[derive(Debug, PartialEq, Eq, Clone)]
 pub struct SlotConfig {
     pub slot_length: u32,
     pub zero_slot: u64,
@@ -90,10 +99,145 @@ pub struct SlotConfig {
 
 impl Default for SlotConfig {
     fn default() -> Self {
+        Self::mainnet()
+    }
+}
+
+impl SlotConfig {
+    pub fn mainnet() -> Self {
         Self {
             slot_length: 1000,
             zero_slot: 4492800,
             zero_time: 1596059091000,
         }
     }
+
+    pub fn preprod() -> Self {
+        Self {
+            slot_length: 1000,
+            zero_slot: 86400,
+            zero_time: 1655769600000,
+        }
+    }
+
+    pub fn preview() -> Self {
+        Self {
+            slot_length: 1000,
+            zero_slot: 0,
+            zero_time: 1666656000000,
+        }
+    }
+
+    /// Derive a config from a Shelley genesis file, using its `systemStart`
+    /// and `slotLength`. This only holds for networks that start directly in
+    /// the Shelley era, like local devnets, since earlier eras shift the
+    /// first Shelley slot.
+    pub fn from_shelley_genesis(genesis: &str) -> Result<Self, Error> {
+        let invalid = |reason: &str| Error::InvalidGenesis(reason.to_string());
+
+        let genesis: serde_json::Value =
+            serde_json::from_str(genesis).map_err(|err| invalid(&err.to_string()))?;
+
+        let system_start = genesis["systemStart"]
+            .as_str()
+            .ok_or_else(|| invalid("missing systemStart"))?;
+
+        let slot_length = genesis["slotLength"]
+            .as_f64()
+            .ok_or_else(|| invalid("missing slotLength"))?;
+
+        Ok(Self {
+            slot_length: (slot_length * 1000.0).round() as u32,
+            zero_slot: 0,
+            zero_time: parse_utc_time(system_start)
+                .ok_or_else(|| invalid(&format!("invalid systemStart {}", system_start)))?,
+        })
+    }
+}
+
+/// POSIX time (in milliseconds) at the beginning of a slot, or `None` for
+/// slots before `zero_slot` and times that don't fit in a `u64`.
+pub fn slot_to_posix(slot: u64, sc: &SlotConfig) -> Option<u64> {
+    let ms_after_begin = slot
+        .checked_sub(sc.zero_slot)?
+        .checked_mul(sc.slot_length as u64)?;
+    sc.zero_time.checked_add(ms_after_begin)
+}
+
+/// The slot enclosing a POSIX time (in milliseconds), or `None` for times
+/// before `zero_time`.
+pub fn posix_to_slot(time: u64, sc: &SlotConfig) -> Option<u64> {
+    let ms_after_begin = time.checked_sub(sc.zero_time)?;
+    sc.zero_slot
+        .checked_add(ms_after_begin / sc.slot_length as u64)
+}
+
+/// Translate a validity interval to the POSIX time range scripts see. Like
+/// the ledger, both bounds map to the beginning of their slot: the lower
+/// bound is inclusive and the upper bound (the ttl) exclusive.
+pub fn slot_range_to_posix_range(
+    slot_range: &TimeRange,
+    sc: &SlotConfig,
+) -> Result<TimeRange, Error> {
+    let convert = |slot: u64| slot_to_posix(slot, sc).ok_or(Error::SlotOutOfRange { slot });
+
+    Ok(TimeRange {
+        lower_bound: slot_range.lower_bound.map(convert).transpose()?,
+        upper_bound: slot_range.upper_bound.map(convert).transpose()?,
+    })
+}
+
+/// The largest validity interval whose POSIX time range fits within the
+/// given one: the lower bound is rounded up to the next slot boundary and
+/// the exclusive upper bound rounded down. A lower bound before `zero_time`
+/// rounds up to `zero_slot`, while no slot ends before an upper bound before
+/// `zero_time`.
+pub fn posix_range_to_slot_range(
+    time_range: &TimeRange,
+    sc: &SlotConfig,
+) -> Result<TimeRange, Error> {
+    let lower_bound = time_range
+        .lower_bound
+        .map(|time| match posix_to_slot(time, sc) {
+            Some(slot) if slot_to_posix(slot, sc) < Some(time) => slot + 1,
+            Some(slot) => slot,
+            None => sc.zero_slot,
+        });
+
+    let upper_bound = time_range
+        .upper_bound
+        .map(|time| posix_to_slot(time, sc).ok_or(Error::TimeOutOfRange { time }))
+        .transpose()?;
+
+    Ok(TimeRange {
+        lower_bound,
+        upper_bound,
+    })
+}
+
+/// Parse a UTC time like `2022-10-25T00:00:00Z` into POSIX milliseconds.
+fn parse_utc_time(s: &str) -> Option<u64> {
+    let (date, time) = s.strip_suffix('Z')?.split_once('T')?;
+
+    let mut date = date.splitn(3, '-').map(|n| n.parse::<i64>().ok());
+    let (year, month, day) = (date.next()??, date.next()??, date.next()??);
+
+    let mut time = time.splitn(3, ':');
+    let hours = time.next()?.parse::<u64>().ok()?;
+    let minutes = time.next()?.parse::<u64>().ok()?;
+    let seconds = time.next()?.parse::<f64>().ok()?;
+
+    if !(1..=12).contains(&month) || !(1..=31).contains(&day) || hours > 23 || minutes > 59 {
+        return None;
+    }
+
+    // Days since 1970-01-01 in the proleptic Gregorian calendar.
+    let y = if month <= 2 { year - 1 } else { year };
+    let era = y.div_euclid(400);
+    let year_of_era = y - era * 400;
+    let day_of_year = (153 * ((month + 9) % 12) + 2) / 5 + day - 1;
+    let day_of_era = year_of_era * 365 + year_of_era / 4 - year_of_era / 100 + day_of_year;
+    let days = u64::try_from(era * 146097 + day_of_era - 719468).ok()?;
+
+    Some(((days * 24 + hours) * 60 + minutes) * 60_000 + (seconds * 1000.0).round() as u64)
 }
@@ -1,13 +1,30 @@
+use std::str::FromStr;
+
 use pallas_codec::utils::MaybeIndefArray;
+use pallas_crypto::hash::{Hash, Hasher};
 use pallas_primitives::{
-    babbage::{CostMdls, TransactionInput, TransactionOutput},
+    babbage::{CostMdls, MintedTx, PlutusData, RedeemerTag, TransactionInput, TransactionOutput},
     Fragment,
 };
 use pallas_traverse::{Era, MultiEraTx};
 
-use crate::machine::cost_model::ExBudget;
+use crate::{
+    ast::{DeBruijn, NamedDeBruijn, Program},
+    machine::cost_model::ExBudget,
+    parser,
+};
 
-use super::{eval_phase_two, ResolvedInput, SlotConfig};
+use super::{
+    apply_params_to_script,
+    emulator::Emulator,
+    error::Error,
+    eval_phase_two, eval_phase_two_detailed, script_arguments,
+    script_context::{
+        posix_range_to_slot_range, posix_to_slot, slot_range_to_posix_range, slot_to_posix,
+        ScriptPurpose, TimeRange, TxInfo,
+    },
+    ResolvedInput, SlotConfig,
+};
 
 #[test]
 fn test_eval() {
@@ -1717,3 +1734,596 @@ fn eval_extraneous_redeemer() {
         _ => unreachable!(),
     };
 }
+
+#[test]
+fn emulator_chains_transactions() {
+    let tx_bytes = hex::decode("84a80081825820975c17a4fed0051be622328efa548e206657d2b65a19224bf6ff8132571e6a5002018282581d60b6c8794e9a7a26599440a4d0fd79cd07644d15917ff13694f1f67235821a000f41f0a1581cc4f241450001af08f3ddbaf9335db79883cbcd81071b8e3508de3055a1400a82581d60b6c8794e9a7a26599440a4d0fd79cd07644d15917ff13694f1f672351a0084192f021a00053b6109a1581cc4f241450001af08f3ddbaf9335db79883cbcd81071b8e3508de3055a1400a0b5820b4f96b0acec8beff2adededa8ba317bcac92174f0f65ccefe569b9a6aac7375a0d818258206c732139de33e916342707de2aebef2252c781640326ff37b86ec99d97f1ba8d011082581d60b6c8794e9a7a26599440a4d0fd79cd07644d15917ff13694f1f672351b00000001af0cdfa2111a0007d912a3008182582031ae74f8058527afb305d7495b10a99422d9337fc199e1f28044f2c477a0f9465840b8b97b7c3b4e19ecfc2fcd9884ee53a35887ee6e4d36901b9ecbac3fe032d7e8a4358305afa573a86396e378255651ed03501906e9def450e588d4bb36f42a050581840100d87980821a000b68081a0cf3a5bf06815909b25909af010000323322323232323232323232323232323232323232332232323232323232323233223232223232533533223233025323233355300f1200135028502623500122333553012120013502b50292350012233350012330314800000488cc0c80080048cc0c400520000013355300e1200123500122335501c0023335001233553012120012350012233550200023550140010012233355500f0150020012335530121200123500122335502000235501300100133355500a01000200130105002300f5001533532350012222222222220045001102a2216135001220023333573466e1cd55ce9baa0044800080808c98c8080cd5ce01081000f1999ab9a3370e6aae7540092000233221233001003002323232323232323232323232323333573466e1cd55cea8062400046666666666664444444444442466666666666600201a01801601401201000e00c00a00800600466a03803a6ae854030cd4070074d5d0a80599a80e00f1aba1500a3335502075ca03e6ae854024ccd54081d7280f9aba1500833501c02835742a00e666aa040052eb4d5d0a8031919191999ab9a3370e6aae75400920002332212330010030023232323333573466e1cd55cea8012400046644246600200600466a066eb4d5d0a801181a1aba135744a004464c6406c66ae700dc0d80d04d55cf280089baa00135742a0046464646666ae68cdc39aab9d5002480008cc8848cc00400c008cd40cdd69aba150023034357426ae8940088c98c80d8cd5ce01b81b01a09aab9e5001137540026ae84d5d1280111931901919ab9c033032030135573ca00226ea8004d5d0a80299a80e3ae35742a008666aa04004a40026ae85400cccd54081d710009aba150023027357426ae8940088c98c80b8cd5ce01781701609aba25001135744a00226ae8940044d5d1280089aba25001135744a00226ae8940044d5d1280089aba25001135744a00226aae7940044dd50009aba150023017357426ae8940088c98c8080cd5ce01081000f080f89931900f99ab9c4901035054350001f135573ca00226ea8004444888ccd54c010480054040cd54c01c480048d400488cd54054008d54024004ccd54c0104800488d4008894cd4ccd54c03048004c8cd409c88ccd400c88008008004d40048800448cc004894cd400840b040040a48d400488cc028008014018400c4cd405001000d4044004cd54c01c480048d400488c8cd5405800cc004014c8004d540a4894cd40044d5402800c884d4008894cd4cc03000802044888cc0080280104c01800c008c8004d5408888448894cd40044008884cc014008ccd54c01c480040140100044484888c00c0104484888c004010c8004d5407c8844894cd400454038884cd403cc010008cd54c01848004010004c8004d5407888448894cd40044d400c88004884ccd401488008c010008ccd54c01c4800401401000488ccd5cd19b8f00200101e01d2350012222222222220091232230023758002640026aa038446666aae7c004940288cd4024c010d5d080118019aba2002015232323333573466e1cd55cea80124000466442466002006004601a6ae854008c014d5d09aba2500223263201533573802c02a02626aae7940044dd50009191919191999ab9a3370e6aae75401120002333322221233330010050040030023232323333573466e1cd55cea80124000466442466002006004602c6ae854008cd4040054d5d09aba2500223263201a33573803603403026aae7940044dd50009aba150043335500875ca00e6ae85400cc8c8c8cccd5cd19b875001480108c84888c008010d5d09aab9e500323333573466e1d4009200223212223001004375c6ae84d55cf280211999ab9a3370ea00690001091100191931900e19ab9c01d01c01a019018135573aa00226ea8004d5d0a80119a8063ae357426ae8940088c98c8058cd5ce00b80b00a09aba25001135744a00226aae7940044dd5000899aa800bae75a224464460046eac004c8004d5406488c8cccd55cf80112804119a80399aa80498031aab9d5002300535573ca00460086ae8800c04c4d5d08008891001091091198008020018891091980080180109119191999ab9a3370ea0029000119091180100198029aba135573ca00646666ae68cdc3a801240044244002464c6402066ae700440400380344d55cea80089baa001232323333573466e1d400520062321222230040053007357426aae79400c8cccd5cd19b875002480108c848888c008014c024d5d09aab9e500423333573466e1d400d20022321222230010053007357426aae7940148cccd5cd19b875004480008c848888c00c014dd71aba135573ca00c464c6402066ae7004404003803403002c4d55cea80089baa001232323333573466e1cd55cea80124000466442466002006004600a6ae854008dd69aba135744a004464c6401866ae700340300284d55cf280089baa0012323333573466e1cd55cea800a400046eb8d5d09aab9e500223263200a33573801601401026ea80048c8c8c8c8c8cccd5cd19b8750014803084888888800c8cccd5cd19b875002480288488888880108cccd5cd19b875003480208cc8848888888cc004024020dd71aba15005375a6ae84d5d1280291999ab9a3370ea00890031199109111111198010048041bae35742a00e6eb8d5d09aba2500723333573466e1d40152004233221222222233006009008300c35742a0126eb8d5d09aba2500923333573466e1d40192002232122222223007008300d357426aae79402c8cccd5cd19b875007480008c848888888c014020c038d5d09aab9e500c23263201333573802802602202001e01c01a01801626aae7540104d55cf280189aab9e5002135573ca00226ea80048c8c8c8c8cccd5cd19b875001480088ccc888488ccc00401401000cdd69aba15004375a6ae85400cdd69aba135744a00646666ae68cdc3a80124000464244600400660106ae84d55cf280311931900619ab9c00d00c00a009135573aa00626ae8940044d55cf280089baa001232323333573466e1d400520022321223001003375c6ae84d55cf280191999ab9a3370ea004900011909118010019bae357426aae7940108c98c8024cd5ce00500480380309aab9d50011375400224464646666ae68cdc3a800a40084244400246666ae68cdc3a8012400446424446006008600c6ae84d55cf280211999ab9a3370ea00690001091100111931900519ab9c00b00a008007006135573aa00226ea80048c8cccd5cd19b8750014800880348cccd5cd19b8750024800080348c98c8018cd5ce00380300200189aab9d37540029309000a4810350543100112330010020072253350021001100612335002223335003220020020013500122001122123300100300222333573466e1c00800401000c488008488004448c8c00400488cc00cc008008005f5f6").unwrap();
+
+    let raw_inputs = hex::decode("84825820b16778c9cf065d9efeefe37ec269b4fc5107ecdbd0dd6bf3274b224165c2edd9008258206c732139de33e916342707de2aebef2252c781640326ff37b86ec99d97f1ba8d01825820975c17a4fed0051be622328efa548e206657d2b65a19224bf6ff8132571e6a500282582018f86700660fc88d0370a8f95ea58f75507e6b27a18a17925ad3b1777eb0d77600").unwrap();
+    let raw_outputs = hex::decode("8482581d60b6c8794e9a7a26599440a4d0fd79cd07644d15917ff13694f1f67235821a000f8548a1581c15be994a64bdb79dde7fe080d8e7ff81b33a9e4860e9ee0d857a8e85a144576177610182581d60b6c8794e9a7a26599440a4d0fd79cd07644d15917ff13694f1f672351b00000001af14b8b482581d60b6c8794e9a7a26599440a4d0fd79cd07644d15917ff13694f1f672351a0098968082581d60b6c8794e9a7a26599440a4d0fd79cd07644d15917ff13694f1f672351a00acd8c6").unwrap();
+
+    let inputs = Vec::<TransactionInput>::decode_fragment(&raw_inputs).unwrap();
+    let outputs = Vec::<TransactionOutput>::decode_fragment(&raw_outputs).unwrap();
+
+    let slot_config = SlotConfig {
+        zero_time: 1660003200000, // Preview network
+        zero_slot: 0,
+        slot_length: 1000,
+    };
+
+    let mut emulator = Emulator::new(slot_config);
+
+    for (input, output) in inputs.into_iter().zip(outputs) {
+        emulator.add_utxo(input, output);
+    }
+
+    let genesis = emulator.snapshot();
+
+    let spent = TransactionInput {
+        transaction_id: Hash::from_str(
+            "975c17a4fed0051be622328efa548e206657d2b65a19224bf6ff8132571e6a50",
+        )
+        .unwrap(),
+        index: 2,
+    };
+
+    let submitted = emulator.submit(&tx_bytes).unwrap();
+
+    assert_eq!(submitted.redeemers.len(), 1);
+    assert!(emulator.utxo(&spent).is_none());
+    assert_eq!(emulator.utxos().len(), 5);
+
+    for index in 0..2 {
+        assert!(emulator
+            .utxo(&TransactionInput {
+                transaction_id: submitted.tx_id,
+                index,
+            })
+            .is_some());
+    }
+
+    assert!(matches!(
+        emulator.submit(&tx_bytes),
+        Err(Error::UnknownInput { index: 2, .. })
+    ));
+
+    // A second transaction, spending the change of the first one. Its fee is
+    // not encoded canonically, which must not change its id.
+    let body = hex::decode(format!(
+        "a30081825820{}01018182581d60{}1a00800000021b0000000000030d40",
+        submitted.tx_id, "b6c8794e9a7a26599440a4d0fd79cd07644d15917ff13694f1f67235",
+    ))
+    .unwrap();
+
+    let next_tx_bytes = [&[0x84], &body[..], &[0xa0, 0xf5, 0xf6]].concat();
+
+    let next = emulator.submit(&next_tx_bytes).unwrap();
+
+    assert_eq!(next.tx_id, Hasher::<256>::hash(&body));
+    assert!(next.redeemers.is_empty());
+    assert!(emulator
+        .utxo(&TransactionInput {
+            transaction_id: submitted.tx_id,
+            index: 1,
+        })
+        .is_none());
+    assert!(emulator
+        .utxo(&TransactionInput {
+            transaction_id: next.tx_id,
+            index: 0,
+        })
+        .is_some());
+
+    emulator.restore(genesis);
+
+    assert!(emulator.utxo(&spent).is_some());
+    assert!(matches!(
+        emulator.submit(&next_tx_bytes),
+        Err(Error::UnknownInput { index: 1, .. })
+    ));
+    assert_eq!(emulator.submit(&tx_bytes).unwrap().tx_id, submitted.tx_id);
+}
+
+#[test]
+fn emulator_collects_collateral_of_invalid_transactions() {
+    // The transaction of `emulator_chains_transactions`, marked as invalid
+    let tx_hex = "84a80081825820975c17a4fed0051be622328efa548e206657d2b65a19224bf6ff8132571e6a5002018282581d60b6c8794e9a7a26599440a4d0fd79cd07644d15917ff13694f1f67235821a000f41f0a1581cc4f241450001af08f3ddbaf9335db79883cbcd81071b8e3508de3055a1400a82581d60b6c8794e9a7a26599440a4d0fd79cd07644d15917ff13694f1f672351a0084192f021a00053b6109a1581cc4f241450001af08f3ddbaf9335db79883cbcd81071b8e3508de3055a1400a0b5820b4f96b0acec8beff2adededa8ba317bcac92174f0f65ccefe569b9a6aac7375a0d818258206c732139de33e916342707de2aebef2252c781640326ff37b86ec99d97f1ba8d011082581d60b6c8794e9a7a26599440a4d0fd79cd07644d15917ff13694f1f672351b00000001af0cdfa2111a0007d912a3008182582031ae74f8058527afb305d7495b10a99422d9337fc199e1f28044f2c477a0f9465840b8b97b7c3b4e19ecfc2fcd9884ee53a35887ee6e4d36901b9ecbac3fe032d7e8a4358305afa573a86396e378255651ed03501906e9def450e588d4bb36f42a050581840100d87980821a000b68081a0cf3a5bf06815909b25909af010000323322323232323232323232323232323232323232332232323232323232323233223232223232533533223233025323233355300f1200135028502623500122333553012120013502b50292350012233350012330314800000488cc0c80080048cc0c400520000013355300e1200123500122335501c0023335001233553012120012350012233550200023550140010012233355500f0150020012335530121200123500122335502000235501300100133355500a01000200130105002300f5001533532350012222222222220045001102a2216135001220023333573466e1cd55ce9baa0044800080808c98c8080cd5ce01081000f1999ab9a3370e6aae7540092000233221233001003002323232323232323232323232323333573466e1cd55cea8062400046666666666664444444444442466666666666600201a01801601401201000e00c00a00800600466a03803a6ae854030cd4070074d5d0a80599a80e00f1aba1500a3335502075ca03e6ae854024ccd54081d7280f9aba1500833501c02835742a00e666aa040052eb4d5d0a8031919191999ab9a3370e6aae75400920002332212330010030023232323333573466e1cd55cea8012400046644246600200600466a066eb4d5d0a801181a1aba135744a004464c6406c66ae700dc0d80d04d55cf280089baa00135742a0046464646666ae68cdc39aab9d5002480008cc8848cc00400c008cd40cdd69aba150023034357426ae8940088c98c80d8cd5ce01b81b01a09aab9e5001137540026ae84d5d1280111931901919ab9c033032030135573ca00226ea8004d5d0a80299a80e3ae35742a008666aa04004a40026ae85400cccd54081d710009aba150023027357426ae8940088c98c80b8cd5ce01781701609aba25001135744a00226ae8940044d5d1280089aba25001135744a00226ae8940044d5d1280089aba25001135744a00226aae7940044dd50009aba150023017357426ae8940088c98c8080cd5ce01081000f080f89931900f99ab9c4901035054350001f135573ca00226ea8004444888ccd54c010480054040cd54c01c480048d400488cd54054008d54024004ccd54c0104800488d4008894cd4ccd54c03048004c8cd409c88ccd400c88008008004d40048800448cc004894cd400840b040040a48d400488cc028008014018400c4cd405001000d4044004cd54c01c480048d400488c8cd5405800cc004014c8004d540a4894cd40044d5402800c884d4008894cd4cc03000802044888cc0080280104c01800c008c8004d5408888448894cd40044008884cc014008ccd54c01c480040140100044484888c00c0104484888c004010c8004d5407c8844894cd400454038884cd403cc010008cd54c01848004010004c8004d5407888448894cd40044d400c88004884ccd401488008c010008ccd54c01c4800401401000488ccd5cd19b8f00200101e01d2350012222222222220091232230023758002640026aa038446666aae7c004940288cd4024c010d5d080118019aba2002015232323333573466e1cd55cea80124000466442466002006004601a6ae854008c014d5d09aba2500223263201533573802c02a02626aae7940044dd50009191919191999ab9a3370e6aae75401120002333322221233330010050040030023232323333573466e1cd55cea80124000466442466002006004602c6ae854008cd4040054d5d09aba2500223263201a33573803603403026aae7940044dd50009aba150043335500875ca00e6ae85400cc8c8c8cccd5cd19b875001480108c84888c008010d5d09aab9e500323333573466e1d4009200223212223001004375c6ae84d55cf280211999ab9a3370ea00690001091100191931900e19ab9c01d01c01a019018135573aa00226ea8004d5d0a80119a8063ae357426ae8940088c98c8058cd5ce00b80b00a09aba25001135744a00226aae7940044dd5000899aa800bae75a224464460046eac004c8004d5406488c8cccd55cf80112804119a80399aa80498031aab9d5002300535573ca00460086ae8800c04c4d5d08008891001091091198008020018891091980080180109119191999ab9a3370ea0029000119091180100198029aba135573ca00646666ae68cdc3a801240044244002464c6402066ae700440400380344d55cea80089baa001232323333573466e1d400520062321222230040053007357426aae79400c8cccd5cd19b875002480108c848888c008014c024d5d09aab9e500423333573466e1d400d20022321222230010053007357426aae7940148cccd5cd19b875004480008c848888c00c014dd71aba135573ca00c464c6402066ae7004404003803403002c4d55cea80089baa001232323333573466e1cd55cea80124000466442466002006004600a6ae854008dd69aba135744a004464c6401866ae700340300284d55cf280089baa0012323333573466e1cd55cea800a400046eb8d5d09aab9e500223263200a33573801601401026ea80048c8c8c8c8c8cccd5cd19b8750014803084888888800c8cccd5cd19b875002480288488888880108cccd5cd19b875003480208cc8848888888cc004024020dd71aba15005375a6ae84d5d1280291999ab9a3370ea00890031199109111111198010048041bae35742a00e6eb8d5d09aba2500723333573466e1d40152004233221222222233006009008300c35742a0126eb8d5d09aba2500923333573466e1d40192002232122222223007008300d357426aae79402c8cccd5cd19b875007480008c848888888c014020c038d5d09aab9e500c23263201333573802802602202001e01c01a01801626aae7540104d55cf280189aab9e5002135573ca00226ea80048c8c8c8c8cccd5cd19b875001480088ccc888488ccc00401401000cdd69aba15004375a6ae85400cdd69aba135744a00646666ae68cdc3a80124000464244600400660106ae84d55cf280311931900619ab9c00d00c00a009135573aa00626ae8940044d55cf280089baa001232323333573466e1d400520022321223001003375c6ae84d55cf280191999ab9a3370ea004900011909118010019bae357426aae7940108c98c8024cd5ce00500480380309aab9d50011375400224464646666ae68cdc3a800a40084244400246666ae68cdc3a8012400446424446006008600c6ae84d55cf280211999ab9a3370ea00690001091100111931900519ab9c00b00a008007006135573aa00226ea80048c8cccd5cd19b8750014800880348cccd5cd19b8750024800080348c98c8018cd5ce00380300200189aab9d37540029309000a4810350543100112330010020072253350021001100612335002223335003220020020013500122001122123300100300222333573466e1c00800401000c488008488004448c8c00400488cc00cc008008005f4f6";
+
+    let raw_inputs = hex::decode("84825820b16778c9cf065d9efeefe37ec269b4fc5107ecdbd0dd6bf3274b224165c2edd9008258206c732139de33e916342707de2aebef2252c781640326ff37b86ec99d97f1ba8d01825820975c17a4fed0051be622328efa548e206657d2b65a19224bf6ff8132571e6a500282582018f86700660fc88d0370a8f95ea58f75507e6b27a18a17925ad3b1777eb0d77600").unwrap();
+    let raw_outputs = hex::decode("8482581d60b6c8794e9a7a26599440a4d0fd79cd07644d15917ff13694f1f67235821a000f8548a1581c15be994a64bdb79dde7fe080d8e7ff81b33a9e4860e9ee0d857a8e85a144576177610182581d60b6c8794e9a7a26599440a4d0fd79cd07644d15917ff13694f1f672351b00000001af14b8b482581d60b6c8794e9a7a26599440a4d0fd79cd07644d15917ff13694f1f672351a0098968082581d60b6c8794e9a7a26599440a4d0fd79cd07644d15917ff13694f1f672351a00acd8c6").unwrap();
+
+    let inputs = Vec::<TransactionInput>::decode_fragment(&raw_inputs).unwrap();
+    let outputs = Vec::<TransactionOutput>::decode_fragment(&raw_outputs).unwrap();
+
+    let slot_config = SlotConfig {
+        zero_time: 1660003200000, // Preview network
+        zero_slot: 0,
+        slot_length: 1000,
+    };
+
+    let mut emulator = Emulator::new(slot_config);
+
+    for (input, output) in inputs.into_iter().zip(outputs) {
+        emulator.add_utxo(input, output);
+    }
+
+    // Its script succeeds, so it can't be marked as invalid
+    assert!(matches!(
+        emulator.submit(&hex::decode(tx_hex).unwrap()),
+        Err(Error::ValidationTagMismatch)
+    ));
+    assert_eq!(emulator.utxos().len(), 4);
+
+    // With `Constr 1 []` as redeemer instead of `Constr 0 []`, its script
+    // fails. The witnesses aren't part of the id of the transaction.
+    let tx_bytes = hex::decode(tx_hex.replace("d87980821a000b6808", "d87a80821a000b6808")).unwrap();
+
+    let spent = TransactionInput {
+        transaction_id: Hash::from_str(
+            "975c17a4fed0051be622328efa548e206657d2b65a19224bf6ff8132571e6a50",
+        )
+        .unwrap(),
+        index: 2,
+    };
+
+    let collateral = TransactionInput {
+        transaction_id: Hash::from_str(
+            "6c732139de33e916342707de2aebef2252c781640326ff37b86ec99d97f1ba8d",
+        )
+        .unwrap(),
+        index: 1,
+    };
+
+    let submitted = emulator.submit(&tx_bytes).unwrap();
+
+    assert!(submitted.redeemers.is_empty());
+    assert!(emulator.utxo(&spent).is_some());
+    assert!(emulator.utxo(&collateral).is_none());
+    assert_eq!(emulator.utxos().len(), 4);
+
+    let tx = MintedTx::decode_fragment(&tx_bytes).unwrap();
+
+    // The collateral return takes the index after the outputs
+    for index in 0..2 {
+        assert!(emulator
+            .utxo(&TransactionInput {
+                transaction_id: submitted.tx_id,
+                index,
+            })
+            .is_none());
+    }
+
+    assert_eq!(
+        emulator.utxo(&TransactionInput {
+            transaction_id: submitted.tx_id,
+            index: 2,
+        }),
+        tx.transaction_body.collateral_return.as_ref()
+    );
+}
+
+#[test]
+fn script_arguments_for_mint_redeemer() {
+    let tx_bytes = hex::decode("84a80081825820975c17a4fed0051be622328efa548e206657d2b65a19224bf6ff8132571e6a5002018282581d60b6c8794e9a7a26599440a4d0fd79cd07644d15917ff13694f1f67235821a000f41f0a1581cc4f241450001af08f3ddbaf9335db79883cbcd81071b8e3508de3055a1400a82581d60b6c8794e9a7a26599440a4d0fd79cd07644d15917ff13694f1f672351a0084192f021a00053b6109a1581cc4f241450001af08f3ddbaf9335db79883cbcd81071b8e3508de3055a1400a0b5820b4f96b0acec8beff2adededa8ba317bcac92174f0f65ccefe569b9a6aac7375a0d818258206c732139de33e916342707de2aebef2252c781640326ff37b86ec99d97f1ba8d011082581d60b6c8794e9a7a26599440a4d0fd79cd07644d15917ff13694f1f672351b00000001af0cdfa2111a0007d912a3008182582031ae74f8058527afb305d7495b10a99422d9337fc199e1f28044f2c477a0f9465840b8b97b7c3b4e19ecfc2fcd9884ee53a35887ee6e4d36901b9ecbac3fe032d7e8a4358305afa573a86396e378255651ed03501906e9def450e588d4bb36f42a050581840100d87980821a000b68081a0cf3a5bf06815909b25909af010000323322323232323232323232323232323232323232332232323232323232323233223232223232533533223233025323233355300f1200135028502623500122333553012120013502b50292350012233350012330314800000488cc0c80080048cc0c400520000013355300e1200123500122335501c0023335001233553012120012350012233550200023550140010012233355500f0150020012335530121200123500122335502000235501300100133355500a01000200130105002300f5001533532350012222222222220045001102a2216135001220023333573466e1cd55ce9baa0044800080808c98c8080cd5ce01081000f1999ab9a3370e6aae7540092000233221233001003002323232323232323232323232323333573466e1cd55cea8062400046666666666664444444444442466666666666600201a01801601401201000e00c00a00800600466a03803a6ae854030cd4070074d5d0a80599a80e00f1aba1500a3335502075ca03e6ae854024ccd54081d7280f9aba1500833501c02835742a00e666aa040052eb4d5d0a8031919191999ab9a3370e6aae75400920002332212330010030023232323333573466e1cd55cea8012400046644246600200600466a066eb4d5d0a801181a1aba135744a004464c6406c66ae700dc0d80d04d55cf280089baa00135742a0046464646666ae68cdc39aab9d5002480008cc8848cc00400c008cd40cdd69aba150023034357426ae8940088c98c80d8cd5ce01b81b01a09aab9e5001137540026ae84d5d1280111931901919ab9c033032030135573ca00226ea8004d5d0a80299a80e3ae35742a008666aa04004a40026ae85400cccd54081d710009aba150023027357426ae8940088c98c80b8cd5ce01781701609aba25001135744a00226ae8940044d5d1280089aba25001135744a00226ae8940044d5d1280089aba25001135744a00226aae7940044dd50009aba150023017357426ae8940088c98c8080cd5ce01081000f080f89931900f99ab9c4901035054350001f135573ca00226ea8004444888ccd54c010480054040cd54c01c480048d400488cd54054008d54024004ccd54c0104800488d4008894cd4ccd54c03048004c8cd409c88ccd400c88008008004d40048800448cc004894cd400840b040040a48d400488cc028008014018400c4cd405001000d4044004cd54c01c480048d400488c8cd5405800cc004014c8004d540a4894cd40044d5402800c884d4008894cd4cc03000802044888cc0080280104c01800c008c8004d5408888448894cd40044008884cc014008ccd54c01c480040140100044484888c00c0104484888c004010c8004d5407c8844894cd400454038884cd403cc010008cd54c01848004010004c8004d5407888448894cd40044d400c88004884ccd401488008c010008ccd54c01c4800401401000488ccd5cd19b8f00200101e01d2350012222222222220091232230023758002640026aa038446666aae7c004940288cd4024c010d5d080118019aba2002015232323333573466e1cd55cea80124000466442466002006004601a6ae854008c014d5d09aba2500223263201533573802c02a02626aae7940044dd50009191919191999ab9a3370e6aae75401120002333322221233330010050040030023232323333573466e1cd55cea80124000466442466002006004602c6ae854008cd4040054d5d09aba2500223263201a33573803603403026aae7940044dd50009aba150043335500875ca00e6ae85400cc8c8c8cccd5cd19b875001480108c84888c008010d5d09aab9e500323333573466e1d4009200223212223001004375c6ae84d55cf280211999ab9a3370ea00690001091100191931900e19ab9c01d01c01a019018135573aa00226ea8004d5d0a80119a8063ae357426ae8940088c98c8058cd5ce00b80b00a09aba25001135744a00226aae7940044dd5000899aa800bae75a224464460046eac004c8004d5406488c8cccd55cf80112804119a80399aa80498031aab9d5002300535573ca00460086ae8800c04c4d5d08008891001091091198008020018891091980080180109119191999ab9a3370ea0029000119091180100198029aba135573ca00646666ae68cdc3a801240044244002464c6402066ae700440400380344d55cea80089baa001232323333573466e1d400520062321222230040053007357426aae79400c8cccd5cd19b875002480108c848888c008014c024d5d09aab9e500423333573466e1d400d20022321222230010053007357426aae7940148cccd5cd19b875004480008c848888c00c014dd71aba135573ca00c464c6402066ae7004404003803403002c4d55cea80089baa001232323333573466e1cd55cea80124000466442466002006004600a6ae854008dd69aba135744a004464c6401866ae700340300284d55cf280089baa0012323333573466e1cd55cea800a400046eb8d5d09aab9e500223263200a33573801601401026ea80048c8c8c8c8c8cccd5cd19b8750014803084888888800c8cccd5cd19b875002480288488888880108cccd5cd19b875003480208cc8848888888cc004024020dd71aba15005375a6ae84d5d1280291999ab9a3370ea00890031199109111111198010048041bae35742a00e6eb8d5d09aba2500723333573466e1d40152004233221222222233006009008300c35742a0126eb8d5d09aba2500923333573466e1d40192002232122222223007008300d357426aae79402c8cccd5cd19b875007480008c848888888c014020c038d5d09aab9e500c23263201333573802802602202001e01c01a01801626aae7540104d55cf280189aab9e5002135573ca00226ea80048c8c8c8c8cccd5cd19b875001480088ccc888488ccc00401401000cdd69aba15004375a6ae85400cdd69aba135744a00646666ae68cdc3a80124000464244600400660106ae84d55cf280311931900619ab9c00d00c00a009135573aa00626ae8940044d55cf280089baa001232323333573466e1d400520022321223001003375c6ae84d55cf280191999ab9a3370ea004900011909118010019bae357426aae7940108c98c8024cd5ce00500480380309aab9d50011375400224464646666ae68cdc3a800a40084244400246666ae68cdc3a8012400446424446006008600c6ae84d55cf280211999ab9a3370ea00690001091100111931900519ab9c00b00a008007006135573aa00226ea80048c8cccd5cd19b8750014800880348cccd5cd19b8750024800080348c98c8018cd5ce00380300200189aab9d37540029309000a4810350543100112330010020072253350021001100612335002223335003220020020013500122001122123300100300222333573466e1c00800401000c488008488004448c8c00400488cc00cc008008005f5f6").unwrap();
+
+    let raw_inputs = hex::decode("84825820b16778c9cf065d9efeefe37ec269b4fc5107ecdbd0dd6bf3274b224165c2edd9008258206c732139de33e916342707de2aebef2252c781640326ff37b86ec99d97f1ba8d01825820975c17a4fed0051be622328efa548e206657d2b65a19224bf6ff8132571e6a500282582018f86700660fc88d0370a8f95ea58f75507e6b27a18a17925ad3b1777eb0d77600").unwrap();
+    let raw_outputs = hex::decode("8482581d60b6c8794e9a7a26599440a4d0fd79cd07644d15917ff13694f1f67235821a000f8548a1581c15be994a64bdb79dde7fe080d8e7ff81b33a9e4860e9ee0d857a8e85a144576177610182581d60b6c8794e9a7a26599440a4d0fd79cd07644d15917ff13694f1f672351b00000001af14b8b482581d60b6c8794e9a7a26599440a4d0fd79cd07644d15917ff13694f1f672351a0098968082581d60b6c8794e9a7a26599440a4d0fd79cd07644d15917ff13694f1f672351a00acd8c6").unwrap();
+
+    let inputs = Vec::<TransactionInput>::decode_fragment(&raw_inputs).unwrap();
+    let outputs = Vec::<TransactionOutput>::decode_fragment(&raw_outputs).unwrap();
+
+    let utxos: Vec<ResolvedInput> = inputs
+        .iter()
+        .zip(outputs.iter())
+        .map(|(input, output)| ResolvedInput {
+            input: input.clone(),
+            output: output.clone(),
+        })
+        .collect();
+
+    let slot_config = SlotConfig {
+        zero_time: 1660003200000, // Preview network
+        zero_slot: 0,
+        slot_length: 1000,
+    };
+
+    let multi_era_tx = MultiEraTx::decode(Era::Babbage, &tx_bytes).unwrap();
+    let tx = multi_era_tx.as_babbage().unwrap();
+
+    let args = script_arguments(tx, &utxos, &slot_config, RedeemerTag::Mint, 0).unwrap();
+
+    assert_eq!(args.datum, None);
+    assert_eq!(
+        args.script_context.purpose,
+        ScriptPurpose::Minting(
+            Hash::from_str("c4f241450001af08f3ddbaf9335db79883cbcd81071b8e3508de3055").unwrap()
+        )
+    );
+    assert!(matches!(args.script_context.tx_info, TxInfo::V2(_)));
+
+    assert!(matches!(
+        script_arguments(tx, &utxos, &slot_config, RedeemerTag::Spend, 0),
+        Err(Error::RedeemerNotFound { index: 0, .. })
+    ));
+}
+
+#[test]
+fn detailed_evaluation_reports_each_redeemer() {
+    let tx_bytes = hex::decode("84a80081825820975c17a4fed0051be622328efa548e206657d2b65a19224bf6ff8132571e6a5002018282581d60b6c8794e9a7a26599440a4d0fd79cd07644d15917ff13694f1f67235821a000f41f0a1581cc4f241450001af08f3ddbaf9335db79883cbcd81071b8e3508de3055a1400a82581d60b6c8794e9a7a26599440a4d0fd79cd07644d15917ff13694f1f672351a0084192f021a00053b6109a1581cc4f241450001af08f3ddbaf9335db79883cbcd81071b8e3508de3055a1400a0b5820b4f96b0acec8beff2adededa8ba317bcac92174f0f65ccefe569b9a6aac7375a0d818258206c732139de33e916342707de2aebef2252c781640326ff37b86ec99d97f1ba8d011082581d60b6c8794e9a7a26599440a4d0fd79cd07644d15917ff13694f1f672351b00000001af0cdfa2111a0007d912a3008182582031ae74f8058527afb305d7495b10a99422d9337fc199e1f28044f2c477a0f9465840b8b97b7c3b4e19ecfc2fcd9884ee53a35887ee6e4d36901b9ecbac3fe032d7e8a4358305afa573a86396e378255651ed03501906e9def450e588d4bb36f42a050581840100d87980821a000b68081a0cf3a5bf06815909b25909af010000323322323232323232323232323232323232323232332232323232323232323233223232223232533533223233025323233355300f1200135028502623500122333553012120013502b50292350012233350012330314800000488cc0c80080048cc0c400520000013355300e1200123500122335501c0023335001233553012120012350012233550200023550140010012233355500f0150020012335530121200123500122335502000235501300100133355500a01000200130105002300f5001533532350012222222222220045001102a2216135001220023333573466e1cd55ce9baa0044800080808c98c8080cd5ce01081000f1999ab9a3370e6aae7540092000233221233001003002323232323232323232323232323333573466e1cd55cea8062400046666666666664444444444442466666666666600201a01801601401201000e00c00a00800600466a03803a6ae854030cd4070074d5d0a80599a80e00f1aba1500a3335502075ca03e6ae854024ccd54081d7280f9aba1500833501c02835742a00e666aa040052eb4d5d0a8031919191999ab9a3370e6aae75400920002332212330010030023232323333573466e1cd55cea8012400046644246600200600466a066eb4d5d0a801181a1aba135744a004464c6406c66ae700dc0d80d04d55cf280089baa00135742a0046464646666ae68cdc39aab9d5002480008cc8848cc00400c008cd40cdd69aba150023034357426ae8940088c98c80d8cd5ce01b81b01a09aab9e5001137540026ae84d5d1280111931901919ab9c033032030135573ca00226ea8004d5d0a80299a80e3ae35742a008666aa04004a40026ae85400cccd54081d710009aba150023027357426ae8940088c98c80b8cd5ce01781701609aba25001135744a00226ae8940044d5d1280089aba25001135744a00226ae8940044d5d1280089aba25001135744a00226aae7940044dd50009aba150023017357426ae8940088c98c8080cd5ce01081000f080f89931900f99ab9c4901035054350001f135573ca00226ea8004444888ccd54c010480054040cd54c01c480048d400488cd54054008d54024004ccd54c0104800488d4008894cd4ccd54c03048004c8cd409c88ccd400c88008008004d40048800448cc004894cd400840b040040a48d400488cc028008014018400c4cd405001000d4044004cd54c01c480048d400488c8cd5405800cc004014c8004d540a4894cd40044d5402800c884d4008894cd4cc03000802044888cc0080280104c01800c008c8004d5408888448894cd40044008884cc014008ccd54c01c480040140100044484888c00c0104484888c004010c8004d5407c8844894cd400454038884cd403cc010008cd54c01848004010004c8004d5407888448894cd40044d400c88004884ccd401488008c010008ccd54c01c4800401401000488ccd5cd19b8f00200101e01d2350012222222222220091232230023758002640026aa038446666aae7c004940288cd4024c010d5d080118019aba2002015232323333573466e1cd55cea80124000466442466002006004601a6ae854008c014d5d09aba2500223263201533573802c02a02626aae7940044dd50009191919191999ab9a3370e6aae75401120002333322221233330010050040030023232323333573466e1cd55cea80124000466442466002006004602c6ae854008cd4040054d5d09aba2500223263201a33573803603403026aae7940044dd50009aba150043335500875ca00e6ae85400cc8c8c8cccd5cd19b875001480108c84888c008010d5d09aab9e500323333573466e1d4009200223212223001004375c6ae84d55cf280211999ab9a3370ea00690001091100191931900e19ab9c01d01c01a019018135573aa00226ea8004d5d0a80119a8063ae357426ae8940088c98c8058cd5ce00b80b00a09aba25001135744a00226aae7940044dd5000899aa800bae75a224464460046eac004c8004d5406488c8cccd55cf80112804119a80399aa80498031aab9d5002300535573ca00460086ae8800c04c4d5d08008891001091091198008020018891091980080180109119191999ab9a3370ea0029000119091180100198029aba135573ca00646666ae68cdc3a801240044244002464c6402066ae700440400380344d55cea80089baa001232323333573466e1d400520062321222230040053007357426aae79400c8cccd5cd19b875002480108c848888c008014c024d5d09aab9e500423333573466e1d400d20022321222230010053007357426aae7940148cccd5cd19b875004480008c848888c00c014dd71aba135573ca00c464c6402066ae7004404003803403002c4d55cea80089baa001232323333573466e1cd55cea80124000466442466002006004600a6ae854008dd69aba135744a004464c6401866ae700340300284d55cf280089baa0012323333573466e1cd55cea800a400046eb8d5d09aab9e500223263200a33573801601401026ea80048c8c8c8c8c8cccd5cd19b8750014803084888888800c8cccd5cd19b875002480288488888880108cccd5cd19b875003480208cc8848888888cc004024020dd71aba15005375a6ae84d5d1280291999ab9a3370ea00890031199109111111198010048041bae35742a00e6eb8d5d09aba2500723333573466e1d40152004233221222222233006009008300c35742a0126eb8d5d09aba2500923333573466e1d40192002232122222223007008300d357426aae79402c8cccd5cd19b875007480008c848888888c014020c038d5d09aab9e500c23263201333573802802602202001e01c01a01801626aae7540104d55cf280189aab9e5002135573ca00226ea80048c8c8c8c8cccd5cd19b875001480088ccc888488ccc00401401000cdd69aba15004375a6ae85400cdd69aba135744a00646666ae68cdc3a80124000464244600400660106ae84d55cf280311931900619ab9c00d00c00a009135573aa00626ae8940044d55cf280089baa001232323333573466e1d400520022321223001003375c6ae84d55cf280191999ab9a3370ea004900011909118010019bae357426aae7940108c98c8024cd5ce00500480380309aab9d50011375400224464646666ae68cdc3a800a40084244400246666ae68cdc3a8012400446424446006008600c6ae84d55cf280211999ab9a3370ea00690001091100111931900519ab9c00b00a008007006135573aa00226ea80048c8cccd5cd19b8750014800880348cccd5cd19b8750024800080348c98c8018cd5ce00380300200189aab9d37540029309000a4810350543100112330010020072253350021001100612335002223335003220020020013500122001122123300100300222333573466e1c00800401000c488008488004448c8c00400488cc00cc008008005f5f6").unwrap();
+
+    let raw_inputs = hex::decode("84825820b16778c9cf065d9efeefe37ec269b4fc5107ecdbd0dd6bf3274b224165c2edd9008258206c732139de33e916342707de2aebef2252c781640326ff37b86ec99d97f1ba8d01825820975c17a4fed0051be622328efa548e206657d2b65a19224bf6ff8132571e6a500282582018f86700660fc88d0370a8f95ea58f75507e6b27a18a17925ad3b1777eb0d77600").unwrap();
+    let raw_outputs = hex::decode("8482581d60b6c8794e9a7a26599440a4d0fd79cd07644d15917ff13694f1f67235821a000f8548a1581c15be994a64bdb79dde7fe080d8e7ff81b33a9e4860e9ee0d857a8e85a144576177610182581d60b6c8794e9a7a26599440a4d0fd79cd07644d15917ff13694f1f672351b00000001af14b8b482581d60b6c8794e9a7a26599440a4d0fd79cd07644d15917ff13694f1f672351a0098968082581d60b6c8794e9a7a26599440a4d0fd79cd07644d15917ff13694f1f672351a00acd8c6").unwrap();
+
+    let inputs = Vec::<TransactionInput>::decode_fragment(&raw_inputs).unwrap();
+    let outputs = Vec::<TransactionOutput>::decode_fragment(&raw_outputs).unwrap();
+
+    let utxos: Vec<ResolvedInput> = inputs
+        .iter()
+        .zip(outputs.iter())
+        .map(|(input, output)| ResolvedInput {
+            input: input.clone(),
+            output: output.clone(),
+        })
+        .collect();
+
+    let slot_config = SlotConfig {
+        zero_time: 1660003200000, // Preview network
+        zero_slot: 0,
+        slot_length: 1000,
+    };
+
+    let multi_era_tx = MultiEraTx::decode(Era::Babbage, &tx_bytes).unwrap();
+    let tx = multi_era_tx.as_babbage().unwrap();
+
+    let redeemers = eval_phase_two(tx, &utxos, None, None, &slot_config, true).unwrap();
+    let reports = eval_phase_two_detailed(tx, &utxos, None, None, &slot_config, true).unwrap();
+
+    assert_eq!(reports.len(), redeemers.len());
+
+    for (report, redeemer) in reports.iter().zip(redeemers.iter()) {
+        assert_eq!(report.tag, redeemer.tag);
+        assert_eq!(report.ex_units, redeemer.ex_units);
+        assert!(report.error.is_none());
+    }
+
+    let costs: Vec<i64> = vec![
+        205665,
+        812,
+        1,
+        1,
+        1000,
+        571,
+        0,
+        1,
+        1000,
+        24177,
+        4,
+        1,
+        1000,
+        32,
+        117366,
+        10475,
+        4,
+        23000,
+        100,
+        23000,
+        100,
+        23000,
+        100,
+        23000,
+        100,
+        23000,
+        100,
+        23000,
+        100,
+        100,
+        100,
+        23000,
+        100,
+        19537,
+        32,
+        175354,
+        32,
+        46417,
+        4,
+        221973,
+        511,
+        0,
+        1,
+        89141,
+        32,
+        497525,
+        14068,
+        4,
+        2,
+        196500,
+        453240,
+        220,
+        0,
+        1,
+        1,
+        1000,
+        28662,
+        4,
+        2,
+        245000,
+        216773,
+        62,
+        1,
+        1060367,
+        12586,
+        1,
+        208512,
+        421,
+        1,
+        187000,
+        1000,
+        52998,
+        1,
+        80436,
+        32,
+        43249,
+        32,
+        1000,
+        32,
+        80556,
+        1,
+        57667,
+        4,
+        1000,
+        10,
+        197145,
+        156,
+        1,
+        197145,
+        156,
+        1,
+        204924,
+        473,
+        1,
+        208896,
+        511,
+        1,
+        52467,
+        32,
+        64832,
+        32,
+        65493,
+        32,
+        22558,
+        32,
+        16563,
+        32,
+        76511,
+        32,
+        196500,
+        453240,
+        220,
+        0,
+        1,
+        1,
+        69522,
+        11687,
+        0,
+        1,
+        60091,
+        32,
+        196500,
+        453240,
+        220,
+        0,
+        1,
+        1,
+        196500,
+        453240,
+        220,
+        0,
+        1,
+        1,
+        1159724,
+        392670,
+        0,
+        2,
+        806990,
+        30482,
+        4,
+        1927926,
+        82523,
+        4,
+        265318,
+        0,
+        4,
+        0,
+        85931,
+        32,
+        205665,
+        812,
+        1,
+        1,
+        41182,
+        32,
+        212342,
+        32,
+        31220,
+        32,
+        32696,
+        32,
+        43357,
+        32,
+        32247,
+        32,
+        38314,
+        32,
+        20000000000,
+        20000000000,
+        9462713,
+        1021,
+        10,
+        20000000000,
+        0,
+        20000000000,
+    ];
+
+    let cost_mdl = CostMdls {
+        plutus_v1: None,
+        plutus_v2: Some(costs),
+    };
+
+    let exhausted = ExBudget { mem: 100, cpu: 100 };
+
+    let reports = eval_phase_two_detailed(
+        tx,
+        &utxos,
+        Some(&cost_mdl),
+        Some(&exhausted),
+        &slot_config,
+        true,
+    )
+    .unwrap();
+
+    assert!(reports.iter().all(|report| report.error.is_some()));
+}
+
+#[test]
+fn slot_conversions() {
+    let sc = SlotConfig::preprod();
+
+    assert_eq!(slot_to_posix(86400, &sc), Some(1655769600000));
+    assert_eq!(slot_to_posix(86401, &sc), Some(1655769601000));
+    assert_eq!(posix_to_slot(1655769601999, &sc), Some(86401));
+
+    let slots = TimeRange {
+        lower_bound: Some(86410),
+        upper_bound: Some(86420),
+    };
+
+    let posix = slot_range_to_posix_range(&slots, &sc).unwrap();
+
+    assert_eq!(
+        posix,
+        TimeRange {
+            lower_bound: Some(1655769610000),
+            upper_bound: Some(1655769620000),
+        }
+    );
+    assert_eq!(posix_range_to_slot_range(&posix, &sc).unwrap(), slots);
+
+    let within = posix_range_to_slot_range(
+        &TimeRange {
+            lower_bound: Some(1655769610001),
+            upper_bound: Some(1655769620999),
+        },
+        &sc,
+    )
+    .unwrap();
+
+    assert_eq!(within.lower_bound, Some(86411));
+    assert_eq!(within.upper_bound, Some(86420));
+}
+
+#[test]
+fn slot_conversions_out_of_range() {
+    let sc = SlotConfig::preprod();
+
+    // The first slot and time of the configuration are the boundaries
+    assert_eq!(slot_to_posix(sc.zero_slot, &sc), Some(sc.zero_time));
+    assert_eq!(slot_to_posix(sc.zero_slot - 1, &sc), None);
+    assert_eq!(posix_to_slot(sc.zero_time, &sc), Some(sc.zero_slot));
+    assert_eq!(posix_to_slot(sc.zero_time - 1, &sc), None);
+    assert_eq!(slot_to_posix(u64::MAX, &sc), None);
+
+    assert!(matches!(
+        slot_range_to_posix_range(
+            &TimeRange {
+                lower_bound: Some(sc.zero_slot - 1),
+                upper_bound: None,
+            },
+            &sc,
+        ),
+        Err(Error::SlotOutOfRange { slot }) if slot == sc.zero_slot - 1
+    ));
+
+    // No slot ends before the first one, but any earlier time rounds up to it
+    assert!(matches!(
+        posix_range_to_slot_range(
+            &TimeRange {
+                lower_bound: None,
+                upper_bound: Some(sc.zero_time - 1),
+            },
+            &sc,
+        ),
+        Err(Error::TimeOutOfRange { time }) if time == sc.zero_time - 1
+    ));
+
+    assert_eq!(
+        posix_range_to_slot_range(
+            &TimeRange {
+                lower_bound: Some(0),
+                upper_bound: Some(sc.zero_time),
+            },
+            &sc,
+        )
+        .unwrap(),
+        TimeRange {
+            lower_bound: Some(sc.zero_slot),
+            upper_bound: Some(sc.zero_slot),
+        }
+    );
+}
+
+#[test]
+fn slot_config_from_shelley_genesis() {
+    let genesis =
+        r#"{ "systemStart": "2022-10-25T00:00:00Z", "slotLength": 1, "epochLength": 86400 }"#;
+
+    assert_eq!(
+        SlotConfig::from_shelley_genesis(genesis).unwrap(),
+        SlotConfig::preview()
+    );
+
+    let devnet = r#"{ "systemStart": "2024-02-29T12:30:15.5Z", "slotLength": 0.1 }"#;
+
+    assert_eq!(
+        SlotConfig::from_shelley_genesis(devnet).unwrap(),
+        SlotConfig {
+            slot_length: 100,
+            zero_slot: 0,
+            zero_time: 1709209815500,
+        }
+    );
+
+    assert!(matches!(
+        SlotConfig::from_shelley_genesis(r#"{ "slotLength": 1 }"#),
+        Err(Error::InvalidGenesis(_))
+    ));
+}
+
+#[test]
+fn apply_params() {
+    let program =
+        parser::program("(program 1.0.0 (lam owner (lam redeemer (lam ctx owner))))").unwrap();
+
+    let program: Program<DeBruijn> = Program::<NamedDeBruijn>::try_from(program).unwrap().into();
+
+    let script = program.to_cbor().unwrap();
+
+    // [42]
+    let params = hex::decode("81182a").unwrap();
+
+    let applied = apply_params_to_script(&params, &script).unwrap();
+
+    let owner = PlutusData::decode_fragment(&hex::decode("182a").unwrap()).unwrap();
+
+    assert_eq!(applied, program.apply_data(owner).to_cbor().unwrap());
+
+    // 42, not in a list
+    assert!(matches!(
+        apply_params_to_script(&hex::decode("182a").unwrap(), &script),
+        Err(Error::ParamsNotAList)
+    ));
+
+    assert!(matches!(
+        apply_params_to_script(&hex::decode("ff").unwrap(), &script),
+        Err(Error::FragmentDecode(_))
+    ));
+}